@@ -0,0 +1,328 @@
+// Type declarations for the native module built from src/js.rs.
+// Constant pool references are resolved to the entry they point to, and are `null` when the
+// index is 0 or broken. Enums with data are `{ type: "Variant", ... }` objects, enums without
+// data are the name of the variant, and access flags are the names of the set flags.
+
+export type ParseMode = "strict" | "lenient";
+
+export interface ParseOptions {
+  /** Defaults to `"lenient"`, which records problems in `diagnostics` instead of throwing */
+  mode?: ParseMode;
+}
+
+/** Thrown for any class or jar that could not be read */
+export interface ParseError extends Error {
+  /** The `ParseError` variant, e.g. `"InvalidConstantTag"`, or `"Cancelled"` */
+  kind: string;
+  /** Byte offset into the class file */
+  offset?: number;
+  /** Logical path of the structure being read, e.g. `"methods[1].attributes[0](Code)"` */
+  path?: string;
+}
+
+export interface JarProgress {
+  /** The entry that was just read */
+  entry: string;
+  /** The number of classes read so far */
+  done: number;
+  total: number;
+}
+
+export interface AsyncJarOptions extends ParseOptions {
+  /** Called on the main thread after each class, throwing rejects the promise */
+  onProgress?: (progress: JarProgress) => void;
+  /** Cancels the scan, the promise is rejected with a `ParseError` of kind `"Cancelled"` */
+  signal?: AbortSignal;
+}
+
+export function parseClass(buffer: Buffer, options?: ParseOptions): Class;
+export function parseJar(path: string, options?: ParseOptions): Jar;
+/** Reads the class on the Node worker pool */
+export function parseClassAsync(buffer: Buffer, options?: ParseOptions): Promise<Class>;
+/** Reads the jar on one of two background threads shared by every call, without blocking the event loop */
+export function parseJarAsync(path: string, options?: AsyncJarOptions): Promise<Jar>;
+
+export interface Jar {
+  manifest: Manifest | null;
+  classes: { entry: string; class: Class }[];
+}
+
+export interface Manifest {
+  main: Record<string, string>;
+  sections: { name: string; attributes: Record<string, string> }[];
+}
+
+type Tagged<K extends string, V> = { type: K; value: V };
+type Ref<T> = T | null;
+
+export type Severity = "Warning" | "Error";
+
+export interface Diagnostic {
+  severity: Severity;
+  offset: number;
+  path: string;
+  message: string;
+}
+
+export interface Class {
+  magic: number;
+  version: ClassVersion;
+  constant_pool: ConstPool;
+  access_flags: ClassFlag[];
+  this_class: Ref<ConstClass>;
+  super_class: Ref<ConstClass>;
+  interfaces: { index: Ref<ConstClass> }[];
+  fields: Field[];
+  methods: Method[];
+  attributes: Attribute[];
+  diagnostics: Diagnostic[];
+}
+
+export interface ClassVersion {
+  /** e.g. `"JDK_17"` */
+  major: string;
+  minor: number;
+}
+
+export type ClassFlag =
+  | "PUBLIC" | "FINAL" | "SUPER" | "INTERFACE" | "ABSTRACT" | "SYNTHETIC" | "ANNOTATION"
+  | "ENUM" | "MODULE";
+export type FieldFlag =
+  | "PUBLIC" | "PRIVATE" | "PROTECTED" | "STATIC" | "FINAL" | "VOLATILE" | "TRANSIENT"
+  | "SYNTHETIC" | "ENUM";
+export type MethodFlag =
+  | "PUBLIC" | "PRIVATE" | "PROTECTED" | "STATIC" | "FINAL" | "SYNCHRONIZED" | "BRIDGE"
+  | "VARARGS" | "NATIVE" | "ABSTRACT" | "STRICT" | "SYNTHETIC";
+export type InnerClassFlag =
+  | "PUBLIC" | "PRIVATE" | "PROTECTED" | "STATIC" | "FINAL" | "INTERFACE" | "ABSTRACT"
+  | "SYNTHETIC" | "ANNOTATION" | "ENUM";
+export type ModuleFlag = "OPEN" | "SYNTHETIC" | "MANDATED";
+export type MethodParameterFlag = "FINAL" | "SYNTHETIC" | "MANDATED";
+export type RequiresFlag = "TRANSITIVE" | "STATIC_PHASE" | "SYNTHETIC" | "MANDATED";
+export type ExportsFlag = "SYNTHETIC" | "MANDATED";
+
+export interface Field {
+  access_flags: FieldFlag[];
+  name_index: Ref<string>;
+  descriptor_index: Ref<string>;
+  attributes: Attribute[];
+}
+
+export interface Method {
+  access_flags: MethodFlag[];
+  name_index: Ref<string>;
+  descriptor_index: Ref<string>;
+  attributes: Attribute[];
+}
+
+// Constant pool
+
+export interface ConstPool {
+  /** Indexed by constant pool index, unusable slots are `null` */
+  entries: (ConstItem | null)[];
+}
+
+export type ConstItem =
+  | Tagged<"Utf8", string>
+  | Tagged<"Integer", { value: number }>
+  | Tagged<"Float", { value: number }>
+  | Tagged<"Long", { value: string }>
+  | Tagged<"Double", { value: number }>
+  | Tagged<"Class", ConstClass>
+  | Tagged<"StringJ", { string_index: Ref<string> }>
+  | Tagged<"FieldRef", MemberRef>
+  | Tagged<"MethodRef", MemberRef>
+  | Tagged<"InterfaceMethodRef", MemberRef>
+  | Tagged<"NameAndType", NameAndType>
+  | Tagged<"MethodHandle", MethodHandle>
+  | Tagged<"MethodType", { descriptor_index: Ref<string> }>
+  | Tagged<"Dynamic", DynamicRef>
+  | Tagged<"InvokeDynamic", DynamicRef>
+  | Tagged<"Module", { name_index: Ref<string> }>
+  | Tagged<"Package", { name_index: Ref<string> }>;
+
+export interface ConstClass {
+  name_index: Ref<string>;
+}
+
+export interface NameAndType {
+  name_index: Ref<string>;
+  descriptor_index: Ref<string>;
+}
+
+export interface MemberRef {
+  class_index: Ref<ConstClass>;
+  name_and_type_index: Ref<NameAndType>;
+}
+
+export interface MethodHandle {
+  reference_kind:
+    | "GetField" | "GetStatic" | "PutField" | "PutStatic" | "InvokeVirtual" | "InvokeStatic"
+    | "InvokeSpecial" | "NewInvokeSpecial" | "InvokeInterface";
+  /** `InvokeStatic` and `InvokeSpecial` refer to an `InterfaceMethod` when it is a method of an interface */
+  reference_index: Tagged<"Field" | "Method" | "InterfaceMethod", Ref<MemberRef>>;
+}
+
+export interface DynamicRef {
+  bootstrap_method_attr_index: number;
+  name_and_type_index: Ref<NameAndType>;
+}
+
+// Attributes
+
+export interface Attribute {
+  attribute_name_index: Ref<string>;
+  info: AttributeInfo;
+}
+
+export type AttributeInfo =
+  | Tagged<"ConstantValue", { constant_value_index: Ref<ConstItem> }>
+  | Tagged<"Code", Code>
+  | Tagged<"StackMapTable", { entries: StackMapFrame[] }>
+  | Tagged<"Exceptions", { exception_index_table: Ref<ConstClass>[] }>
+  | Tagged<"InnerClasses", { classes: InnerClass[] }>
+  | Tagged<"EnclosingMethod", { class_index: Ref<ConstClass>; method_index: Ref<NameAndType> }>
+  | Tagged<"Synthetic", {}>
+  | Tagged<"Signature", { signature_index: Ref<string> }>
+  | Tagged<"SourceFile", { sourcefile_index: Ref<string> }>
+  | Tagged<"SourceDebugExtension", { debug_extension: string }>
+  | Tagged<"LineNumberTable", { line_number_table: { start_pc: number; line_number: number }[] }>
+  | Tagged<"LocalVariableTable", { local_variable_table: LocalVariable[] }>
+  | Tagged<"LocalVariableTypeTable", { local_variable_type_table: LocalVariableType[] }>
+  | Tagged<"Deprecated", {}>
+  | Tagged<"RuntimeVisibleAnnotations", { annotations: Annotation[] }>
+  | Tagged<"RuntimeInvisibleAnnotations", { annotations: Annotation[] }>
+  | Tagged<"RuntimeVisibleParameterAnnotations", { parameter_annotations: { annotations: Annotation[] }[] }>
+  | Tagged<"RuntimeInvisibleParameterAnnotations", { parameter_annotations: { annotations: Annotation[] }[] }>
+  | Tagged<"RuntimeVisibleTypeAnnotations", { annotations: TypeAnnotation[] }>
+  | Tagged<"RuntimeInvisibleTypeAnnotations", { annotations: TypeAnnotation[] }>
+  | Tagged<"AnnotationDefault", { default_value: ElementValue }>
+  | Tagged<"BootstrapMethods", { bootstrap_methods: BootstrapMethod[] }>
+  | Tagged<"MethodParameters", { parameters: { name_index: Ref<string>; access_flags: MethodParameterFlag[] }[] }>
+  | Tagged<"Module", Module>
+  | Tagged<"ModulePackages", { package_index: Ref<{ name_index: Ref<string> }>[] }>
+  | Tagged<"ModuleMainClass", { main_class_index: Ref<ConstClass> }>
+  | Tagged<"NestHost", { host_class_index: Ref<ConstClass> }>
+  | Tagged<"NestMembers", { classes: Ref<ConstClass>[] }>
+  | Tagged<"Record", { components: RecordComponent[] }>
+  | Tagged<"PermittedSubclasses", { classes: Ref<ConstClass>[] }>
+  | Tagged<"Unknown", { bytes: number[]; offset: number }>;
+
+export interface Code {
+  max_stack: number;
+  max_locals: number;
+  code: Instruction[];
+  exception_table: ExceptionTable[];
+  attributes: Attribute[];
+}
+
+export interface ExceptionTable {
+  start_pc: number;
+  end_pc: number;
+  handler_pc: number;
+  /** `null` for a handler that catches everything */
+  catch_type: Ref<ConstClass>;
+}
+
+export interface Instruction {
+  /** The mnemonic, e.g. `"invokevirtual"` */
+  opcode: string;
+  /**
+   * Constant pool operands are resolved. `tableswitch` has `{ default, low, high, offsets }`,
+   * `lookupswitch` has `{ default, pairs: [{ key, offset }] }` and `wide` has the widened
+   * instruction without an `offset`
+   */
+  operands: unknown[];
+  /** Byte offset of the instruction in the method's code */
+  offset: number;
+}
+
+export type VerificationTypeInfo =
+  | { type: "Top" | "Integer" | "Float" | "Double" | "Long" | "Null" | "UninitializedThis" }
+  | Tagged<"Object", Ref<ConstClass>>
+  | Tagged<"Uninitialized", number>;
+
+export type StackMapFrame =
+  | { type: "Same" | "SameExtended"; offset_delta: number }
+  | { type: "SameLocals1StackItem" | "SameLocals1StackItemExtended"; offset_delta: number; stack: VerificationTypeInfo }
+  | { type: "Chop"; k: number; offset_delta: number }
+  | { type: "Append"; offset_delta: number; locals: VerificationTypeInfo[] }
+  | { type: "Full"; offset_delta: number; locals: VerificationTypeInfo[]; stack: VerificationTypeInfo[] };
+
+export interface InnerClass {
+  inner_class_info_index: Ref<ConstClass>;
+  outer_class_info_index: Ref<ConstClass>;
+  inner_name_index: Ref<string>;
+  inner_class_access_flags: InnerClassFlag[];
+}
+
+export interface LocalVariable {
+  start_pc: number;
+  length: number;
+  name_index: Ref<string>;
+  descriptor_index: Ref<string>;
+  index: number;
+}
+
+export interface LocalVariableType {
+  start_pc: number;
+  length: number;
+  name_index: Ref<string>;
+  signature_index: Ref<string>;
+  index: number;
+}
+
+export interface Annotation {
+  type_index: Ref<string>;
+  element_value_pairs: { element_name_index: Ref<string>; value: ElementValue }[];
+}
+
+export type ElementValue =
+  | Tagged<"Byte" | "Char" | "Int" | "Short" | "Boolean", Ref<{ value: number }>>
+  | Tagged<"Float" | "Double", Ref<{ value: number }>>
+  | Tagged<"Long", Ref<{ value: string }>>
+  | Tagged<"String" | "Class", Ref<string>>
+  | { type: "Enum"; type_name_index: Ref<string>; const_name_index: Ref<string> }
+  | Tagged<"Annotation", Annotation>
+  | Tagged<"Array", ElementValue[]>;
+
+export interface TypeAnnotation {
+  target_type: string;
+  target_info: TargetInfo;
+  target_path: { path: { type_path_kind: "Array" | "Nested" | "WildcardBound" | "TypeArgument"; type_argument_index: number }[] };
+  annotation: Annotation;
+}
+
+export type TargetInfo =
+  | { type: "TypeParameter"; type_parameter_index: number }
+  | { type: "Supertype"; supertype_index: number }
+  | { type: "TypeParameterBound"; type_parameter_index: number; bound_index: number }
+  | { type: "Empty" }
+  | { type: "FormalParameter"; formal_parameter_index: number }
+  | { type: "Throws"; throws_type_index: number }
+  | { type: "Localvar"; table: { start_pc: number; length: number; index: number }[] }
+  | { type: "Catch"; exception_table_index: number }
+  | { type: "Offset"; offset: number }
+  | { type: "TypeArgument"; offset: number; type_argument_index: number };
+
+export interface BootstrapMethod {
+  bootstrap_method_ref: Ref<MethodHandle>;
+  bootstrap_arguments: Ref<ConstItem>[];
+}
+
+export interface Module {
+  name_index: Ref<string>;
+  flags: ModuleFlag[];
+  version_index: Ref<string>;
+  requires: { requires_index: Ref<string>; flags: RequiresFlag[]; version_index: Ref<string> }[];
+  exports: { exports_index: Ref<string>; flags: ExportsFlag[]; to_index: Ref<{ name_index: Ref<string> }>[] }[];
+  opens: { opens_index: Ref<string>; flags: ExportsFlag[]; to_index: Ref<string>[] }[];
+  uses: { uses_index: Ref<ConstClass> }[];
+  provides: { provides_index: Ref<ConstClass>; with_index: Ref<ConstClass>[] }[];
+}
+
+export interface RecordComponent {
+  name_index: Ref<string>;
+  descriptor_index: Ref<string>;
+  attributes: Attribute[];
+}
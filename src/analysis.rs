@@ -0,0 +1,4 @@
+//! Recovers source level constructs from the bytecode javac translates them into
+
+pub mod concat;
+pub mod lambda;
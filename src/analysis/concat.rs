@@ -0,0 +1,172 @@
+//! Decodes the string concatenations javac compiles to an `invokedynamic` bootstrapped by
+//! `StringConcatFactory` since Java 9. `makeConcatWithConstants` takes a recipe, in which
+//! `\u0001` stands for the next argument of the call site and `\u0002` for the next constant
+//! after the recipe, and any other text is copied as is. `makeConcat` concatenates its arguments.
+
+use std::fmt::{self, Display};
+
+use crate::{
+    descriptor::{java_class_name, FieldType, MethodDescriptor},
+    error::{ParseError, Result},
+    structs::{
+        attributes::escape_java,
+        bootstrap::{BootstrapArgument, ResolvedDynamic},
+        Class,
+    },
+};
+
+pub const STRING_CONCAT_FACTORY: &str = "java/lang/invoke/StringConcatFactory";
+
+const TAG_ARGUMENT: char = '\u{1}';
+const TAG_CONSTANT: char = '\u{2}';
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConcatPart {
+    /// Text of the recipe
+    Literal(String),
+    /// An argument of the call site, numbered from 0
+    Argument { index: usize, field_type: FieldType },
+    /// A constant passed after the recipe, which javac uses for text containing the tags
+    Constant(BootstrapArgument),
+}
+
+/// A string concatenation, rendered as the Java expression it was compiled from, like
+/// `"Hello " + arg0 + "!"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Concatenation {
+    /// The index of the `InvokeDynamic` entry of the call site
+    pub call_site: u16,
+    pub parts: Vec<ConcatPart>,
+}
+
+impl Concatenation {
+    /// Decodes the call site, or returns `None` if `StringConcatFactory` does not bootstrap it
+    pub fn decode(index: u16, site: &ResolvedDynamic) -> Result<Option<Self>> {
+        let bootstrap_method = &site.bootstrap_method;
+        if bootstrap_method.method.owner != STRING_CONCAT_FACTORY {
+            return Ok(None);
+        }
+        let descriptor = MethodDescriptor::parse(&site.descriptor)?;
+        let mut types = descriptor.parameters.into_iter().enumerate();
+        let mut argument = || {
+            types
+                .next()
+                .map(|(index, field_type)| ConcatPart::Argument { index, field_type })
+        };
+
+        let mut parts = Vec::new();
+        match bootstrap_method.method.name.as_str() {
+            "makeConcat" => parts.extend(std::iter::from_fn(argument)),
+            "makeConcatWithConstants" => {
+                let mut arguments = bootstrap_method.arguments.iter();
+                let recipe = match arguments.next() {
+                    Some(BootstrapArgument::String(recipe)) => recipe,
+                    _ => {
+                        return Err(ParseError::Other(format!(
+                            "Call site {} passes no recipe to makeConcatWithConstants",
+                            index
+                        )))
+                    }
+                };
+                let mut literal = String::new();
+                for c in recipe.chars() {
+                    let part = match c {
+                        TAG_ARGUMENT => argument().ok_or_else(|| {
+                            ParseError::Other(format!(
+                                "The recipe of call site {} uses more arguments than it passes",
+                                index
+                            ))
+                        })?,
+                        TAG_CONSTANT => {
+                            ConcatPart::Constant(arguments.next().cloned().ok_or_else(|| {
+                                ParseError::Other(format!(
+                                    "The recipe of call site {} uses more constants than it passes",
+                                    index
+                                ))
+                            })?)
+                        }
+                        c => {
+                            literal.push(c);
+                            continue;
+                        }
+                    };
+                    if !literal.is_empty() {
+                        parts.push(ConcatPart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                }
+                if !literal.is_empty() {
+                    parts.push(ConcatPart::Literal(literal));
+                }
+                if argument().is_some() || arguments.next().is_some() {
+                    return Err(ParseError::Other(format!(
+                        "The recipe of call site {} leaves out some of its arguments",
+                        index
+                    )));
+                }
+            }
+            _ => return Ok(None),
+        }
+        Ok(Some(Concatenation {
+            call_site: index,
+            parts,
+        }))
+    }
+}
+
+/// The string concatenation of the `InvokeDynamic` entry at `index`, or `None` if it is not one
+pub fn concatenation(class: &Class, index: u16) -> Result<Option<Concatenation>> {
+    Concatenation::decode(index, &class.resolve_dynamic(index)?)
+}
+
+impl ConcatPart {
+    fn is_string(&self) -> bool {
+        match self {
+            ConcatPart::Literal(_) | ConcatPart::Constant(BootstrapArgument::String(_)) => true,
+            ConcatPart::Argument {
+                field_type: FieldType::Object(name),
+                ..
+            } => name == "java/lang/String",
+            _ => false,
+        }
+    }
+}
+
+impl Display for ConcatPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcatPart::Literal(text) | ConcatPart::Constant(BootstrapArgument::String(text)) => {
+                write!(f, "\"{}\"", escape_java(text, '"'))
+            }
+            ConcatPart::Argument { index, .. } => write!(f, "arg{}", index),
+            ConcatPart::Constant(BootstrapArgument::Int(value)) => write!(f, "{}", value),
+            ConcatPart::Constant(BootstrapArgument::Long(value)) => write!(f, "{}L", value),
+            ConcatPart::Constant(BootstrapArgument::Float(value)) => write!(f, "{:?}f", value),
+            ConcatPart::Constant(BootstrapArgument::Double(value)) => write!(f, "{:?}", value),
+            ConcatPart::Constant(BootstrapArgument::Class(name)) => match FieldType::parse(name) {
+                Ok(array @ FieldType::Array { .. }) => write!(f, "{}.class", array.to_java()),
+                _ => write!(f, "{}.class", java_class_name(name)),
+            },
+            ConcatPart::Constant(constant) => write!(f, "{:?}", constant),
+        }
+    }
+}
+
+impl Display for Concatenation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Without a string among the first two operands, `+` would add numbers instead
+        if !self.parts.iter().take(2).any(ConcatPart::is_string) {
+            write!(f, "\"\"")?;
+            if !self.parts.is_empty() {
+                write!(f, " + ")?;
+            }
+        }
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
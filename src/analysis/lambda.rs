@@ -0,0 +1,245 @@
+//! Finds the lambdas and method references of a class. javac compiles each one to an
+//! `invokedynamic` bootstrapped by `LambdaMetafactory.metafactory`, or by `altMetafactory` when
+//! the lambda is serializable or implements extra interfaces. The static arguments of the call
+//! site name the interface method and the method implementing it, and the call site descriptor
+//! lists the values the lambda captures.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{
+    descriptor::{FieldType, MethodDescriptor},
+    error::{ParseError, Result},
+    structs::{
+        access_flags::MethodFlags,
+        attributes::AttributeInfo,
+        bootstrap::{BootstrapArgument, ResolvedDynamic, Resolver},
+        const_types::{MethodHandleReferenceKind, ResolvedMemberRef},
+        Class, Instruction,
+    },
+};
+
+pub const LAMBDA_METAFACTORY: &str = "java/lang/invoke/LambdaMetafactory";
+
+// The flags `altMetafactory` takes as its fourth argument
+const FLAG_SERIALIZABLE: i32 = 1;
+const FLAG_MARKERS: i32 = 2;
+const FLAG_BRIDGES: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LambdaKind {
+    /// A lambda expression, implemented by a synthetic method of the class
+    Lambda,
+    /// A method reference, implemented by the method it refers to
+    MethodReference,
+}
+
+/// A lambda or method reference, with the call site that creates it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lambda {
+    /// The name of the method that creates the lambda
+    pub caller: String,
+    pub caller_descriptor: String,
+    /// The offset of the `invokedynamic` in the code of the caller
+    pub offset: u32,
+    /// The index of the `InvokeDynamic` entry of the call site
+    pub call_site: u16,
+    pub kind: LambdaKind,
+    /// The internal name of the functional interface
+    pub interface: String,
+    /// The name of the single abstract method of the interface
+    pub method_name: String,
+    /// The erased signature of the abstract method
+    pub method_type: MethodDescriptor,
+    /// The signature of the abstract method with the type arguments of the interface filled in
+    pub instantiated_method_type: MethodDescriptor,
+    pub implementation_kind: MethodHandleReferenceKind,
+    /// The method that is called when the abstract method is
+    pub implementation: ResolvedMemberRef,
+    /// The types of the values captured when the lambda is created, which are passed to the
+    /// implementation before the arguments of the abstract method. A method reference bound to
+    /// an object captures that object.
+    pub captured: Vec<FieldType>,
+    pub serializable: bool,
+    /// The internal names of further interfaces the lambda implements, like `Marker` in
+    /// `(Runnable & Marker) () -> {}`
+    pub marker_interfaces: Vec<String>,
+    /// Further signatures of the abstract method that need a bridge
+    pub bridges: Vec<MethodDescriptor>,
+}
+
+/// Every lambda and method reference the methods of the class create, in the order of the
+/// methods and of the call sites within them
+pub fn lambdas(class: &Class) -> Result<Vec<Lambda>> {
+    let pool = &class.constant_pool;
+    let this_class = &class.this_class.get(pool)?.name_index.get(pool)?.str;
+    // javac reuses the call site of a lambda that is created in several places, like the
+    // deserialization of a serializable lambda
+    let mut call_sites = HashMap::new();
+    let mut resolver = Resolver::new(class);
+    let mut lambdas = Vec::new();
+    for method in &class.methods {
+        let code = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::Code(code) => Some(code),
+                _ => None,
+            });
+        for instruction in code.iter().flat_map(|code| &code.code) {
+            let index = match &instruction.instruction {
+                Instruction::invokedynamic(index) => index.index,
+                _ => continue,
+            };
+            let site = match call_sites.entry(index) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(resolver.dynamic(index)?),
+            };
+            let bootstrap_method = &site.bootstrap_method.method;
+            if bootstrap_method.owner != LAMBDA_METAFACTORY
+                || !matches!(
+                    bootstrap_method.name.as_str(),
+                    "metafactory" | "altMetafactory"
+                )
+            {
+                continue;
+            }
+            let mut lambda = call_site(index, site)?;
+            lambda.caller = method.name_index.get(pool)?.str.clone();
+            lambda.caller_descriptor = method.descriptor_index.get(pool)?.str.clone();
+            lambda.offset = instruction.offset;
+            lambda.kind = if implemented_by_synthetic(class, this_class, &lambda.implementation)? {
+                LambdaKind::Lambda
+            } else {
+                LambdaKind::MethodReference
+            };
+            lambdas.push(lambda);
+        }
+    }
+    Ok(lambdas)
+}
+
+/// Reads the lambda from the arguments of its call site, leaving the caller empty
+fn call_site(index: u16, site: &ResolvedDynamic) -> Result<Lambda> {
+    let bootstrap_method = &site.bootstrap_method;
+    let unexpected = || {
+        ParseError::Other(format!(
+            "Call site {} passes unexpected arguments to LambdaMetafactory.{}",
+            index, bootstrap_method.method.name
+        ))
+    };
+
+    let descriptor = MethodDescriptor::parse(&site.descriptor)?;
+    let interface = match descriptor.return_type {
+        Some(FieldType::Object(interface)) => interface,
+        _ => {
+            return Err(ParseError::Other(format!(
+                "Call site {} of LambdaMetafactory.{} does not return an interface",
+                index, bootstrap_method.method.name
+            )))
+        }
+    };
+
+    let mut arguments = bootstrap_method.arguments.iter();
+    let (method_type, (implementation_kind, implementation), instantiated_method_type) =
+        match (arguments.next(), arguments.next(), arguments.next()) {
+            (
+                Some(BootstrapArgument::MethodType(method_type)),
+                Some(BootstrapArgument::MethodHandle {
+                    reference_kind,
+                    member,
+                }),
+                Some(BootstrapArgument::MethodType(instantiated_method_type)),
+            ) => (
+                MethodDescriptor::parse(method_type)?,
+                (reference_kind.clone(), member.clone()),
+                MethodDescriptor::parse(instantiated_method_type)?,
+            ),
+            _ => return Err(unexpected()),
+        };
+
+    let mut lambda = Lambda {
+        caller: String::new(),
+        caller_descriptor: String::new(),
+        offset: 0,
+        call_site: index,
+        kind: LambdaKind::Lambda,
+        interface,
+        method_name: site.name.clone(),
+        method_type,
+        instantiated_method_type,
+        implementation_kind,
+        implementation,
+        captured: descriptor.parameters,
+        serializable: false,
+        marker_interfaces: Vec::new(),
+        bridges: Vec::new(),
+    };
+    if bootstrap_method.method.name == "metafactory" {
+        return match arguments.next() {
+            None => Ok(lambda),
+            Some(_) => Err(unexpected()),
+        };
+    }
+
+    // altMetafactory goes on with its flags, then the marker interfaces and the bridges, each
+    // preceded by their count, if the flags ask for them
+    let flags = int(&mut arguments).ok_or_else(unexpected)?;
+    lambda.serializable = flags & FLAG_SERIALIZABLE != 0;
+    let marker_count = if flags & FLAG_MARKERS != 0 {
+        int(&mut arguments).ok_or_else(unexpected)?
+    } else {
+        0
+    };
+    for _ in 0..marker_count {
+        match arguments.next() {
+            Some(BootstrapArgument::Class(marker)) => lambda.marker_interfaces.push(marker.clone()),
+            _ => return Err(unexpected()),
+        }
+    }
+    let bridge_count = if flags & FLAG_BRIDGES != 0 {
+        int(&mut arguments).ok_or_else(unexpected)?
+    } else {
+        0
+    };
+    for _ in 0..bridge_count {
+        match arguments.next() {
+            Some(BootstrapArgument::MethodType(bridge)) => {
+                lambda.bridges.push(MethodDescriptor::parse(bridge)?)
+            }
+            _ => return Err(unexpected()),
+        }
+    }
+    match arguments.next() {
+        None => Ok(lambda),
+        Some(_) => Err(unexpected()),
+    }
+}
+
+fn int(arguments: &mut std::slice::Iter<BootstrapArgument>) -> Option<i32> {
+    match arguments.next() {
+        Some(BootstrapArgument::Int(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Whether the implementation is a synthetic method of the class, which javac generates for the
+/// body of a lambda expression
+fn implemented_by_synthetic(
+    class: &Class,
+    this_class: &str,
+    implementation: &ResolvedMemberRef,
+) -> Result<bool> {
+    if implementation.owner != this_class {
+        return Ok(false);
+    }
+    let pool = &class.constant_pool;
+    for method in &class.methods {
+        if method.access_flags.contains(MethodFlags::SYNTHETIC)
+            && method.name_index.get(pool)?.str == implementation.name
+            && method.descriptor_index.get(pool)?.str == implementation.descriptor
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,151 @@
+//! A textual assembly language for classes, so bytecode can be edited by hand and assembled back
+//! into a class file. `print` writes a class in this syntax and `assemble` reads it back:
+//!
+//! ```text
+//! .version 61 0
+//! .class public super Hello
+//! .super java/lang/Object
+//!
+//! .method public static main ([Ljava/lang/String;)V
+//!     .code stack 2 locals 1
+//!             getstatic java/lang/System out Ljava/io/PrintStream;
+//!             ldc String "Hello"
+//!             invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+//!             return
+//!     .end code
+//! .end method
+//! .end class
+//! ```
+//!
+//! Constants are written by value and interned into the constant pool, or refer to a slot as
+//! `[12]`. Where the kind of constant is not implied, like for `ldc`, the value is preceded by its
+//! kind: `Int`, `Float`, `Long`, `Double`, `Class`, `String`, `Field`, `Method`,
+//! `InterfaceMethod`, `NameAndType`, `MethodHandle`, `MethodType`, `Dynamic`, `InvokeDynamic`,
+//! `Module`, `Package`, `Utf8` or `Utf8Bytes`. `.const [12] = ...` places a constant at a given
+//! slot, which is how `print` keeps the pool exactly as it was.
+//!
+//! Jump targets and every other code offset are symbolic labels, defined by prefixing an
+//! instruction with `name:`. Attributes are written with a directive named after them, like
+//! `.linenumbertable`, and ones that are not understood as `.attribute Name "hex bytes"`.
+//! Comments start with `;`.
+
+mod lexer;
+mod parser;
+mod printer;
+
+use std::convert::TryFrom;
+
+use bitflags::Flags;
+
+use crate::{
+    error::Result,
+    structs::{
+        const_types::MethodHandleReferenceKind,
+        opcodes::{ArrayType, Opcode},
+        Class,
+    },
+};
+
+/// Writes the class in the assembly syntax
+pub fn print(class: &Class) -> Result<String> {
+    printer::print(class)
+}
+
+/// Builds a class from its assembly syntax
+pub fn assemble(text: &str) -> Result<Class> {
+    parser::parse(text)
+}
+
+/// The names of the set flags in lowercase, with any bits without a name in hex
+fn flag_words<F: Flags<Bits = u16>>(flags: &F) -> Vec<String> {
+    let mut words: Vec<String> = flags
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+    let unnamed = flags.bits() & !F::all().bits();
+    if unnamed != 0 {
+        words.push(format!("{:#06x}", unnamed));
+    }
+    words
+}
+
+fn parse_flag<F: Flags<Bits = u16>>(word: &str) -> Option<F> {
+    match word.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok().map(F::from_bits_retain),
+        None if word.chars().all(|c| c.is_ascii_lowercase() || c == '_') => {
+            F::from_name(&word.to_uppercase())
+        }
+        None => None,
+    }
+}
+
+fn opcode_name(opcode: Opcode) -> String {
+    // `return` is a keyword in Rust
+    format!("{:?}", opcode).trim_end_matches('_').to_string()
+}
+
+fn opcode(name: &str) -> Option<Opcode> {
+    (0..=u8::MAX)
+        .filter_map(|byte| Opcode::try_from(byte).ok())
+        .find(|&opcode| opcode_name(opcode) == name)
+}
+
+/// `int` for `T_INT`
+fn array_type_name(array_type: ArrayType) -> String {
+    format!("{:?}", array_type)
+        .trim_start_matches("T_")
+        .to_lowercase()
+}
+
+fn array_type(name: &str) -> Option<ArrayType> {
+    (0..=u8::MAX)
+        .filter_map(|byte| ArrayType::try_from(byte).ok())
+        .find(|&array_type| array_type_name(array_type) == name)
+}
+
+const REFERENCE_KINDS: [(MethodHandleReferenceKind, &str); 9] = [
+    (MethodHandleReferenceKind::GetField, "getField"),
+    (MethodHandleReferenceKind::GetStatic, "getStatic"),
+    (MethodHandleReferenceKind::PutField, "putField"),
+    (MethodHandleReferenceKind::PutStatic, "putStatic"),
+    (MethodHandleReferenceKind::InvokeVirtual, "invokeVirtual"),
+    (MethodHandleReferenceKind::InvokeStatic, "invokeStatic"),
+    (MethodHandleReferenceKind::InvokeSpecial, "invokeSpecial"),
+    (
+        MethodHandleReferenceKind::NewInvokeSpecial,
+        "newInvokeSpecial",
+    ),
+    (
+        MethodHandleReferenceKind::InvokeInterface,
+        "invokeInterface",
+    ),
+];
+
+fn reference_kind_name(kind: &MethodHandleReferenceKind) -> &'static str {
+    REFERENCE_KINDS
+        .iter()
+        .find(|(candidate, _)| candidate == kind)
+        .map(|(_, name)| *name)
+        .unwrap_or_default()
+}
+
+fn reference_kind(name: &str) -> Option<MethodHandleReferenceKind> {
+    REFERENCE_KINDS
+        .iter()
+        .find(|(_, candidate)| *candidate == name)
+        .map(|(kind, _)| kind.clone())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
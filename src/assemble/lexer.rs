@@ -0,0 +1,165 @@
+//! Splits assembly text into lines of tokens, and quotes text so it reads back as one token
+
+use crate::error::{ParseError, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Token {
+    /// A word starting with `.`, like `.method` or `.end`
+    Directive(String),
+    /// A bare name, number, label or keyword
+    Word(String),
+    /// A quoted string with its escapes resolved
+    Str(String),
+    /// A constant pool slot, written `[12]`
+    Ref(u16),
+}
+
+#[derive(Debug)]
+pub(super) struct Line {
+    /// The line number in the text, starting at 1
+    pub number: usize,
+    pub tokens: Vec<Token>,
+}
+
+/// Tokenizes every line, leaving out the ones that are blank or only hold a comment
+pub(super) fn tokenize(text: &str) -> Result<Vec<Line>> {
+    let mut lines = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let tokens = tokenize_line(line).map_err(|reason| ParseError::InvalidAssembly {
+            line: i + 1,
+            reason,
+        })?;
+        if !tokens.is_empty() {
+            lines.push(Line {
+                number: i + 1,
+                tokens,
+            });
+        }
+    }
+    Ok(lines)
+}
+
+fn tokenize_line(line: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            // Comments only start at the beginning of a token, as descriptors contain `;`
+            break;
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::Str(string(&mut chars)?));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(classify(word)?);
+        }
+    }
+    Ok(tokens)
+}
+
+fn classify(word: String) -> std::result::Result<Token, String> {
+    if word.starts_with('.') {
+        return Ok(Token::Directive(word));
+    }
+    if let Some(digits) = reference_digits(&word) {
+        return digits
+            .parse()
+            .map(Token::Ref)
+            .map_err(|_| format!("Constant pool index {} is out of range", word));
+    }
+    Ok(Token::Word(word))
+}
+
+/// The digits of a `[12]` reference
+fn reference_digits(word: &str) -> Option<&str> {
+    let digits = word.strip_prefix('[')?.strip_suffix(']')?;
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+/// Reads the rest of a string after its opening quote
+fn string(chars: &mut std::iter::Peekable<std::str::Chars>) -> std::result::Result<String, String> {
+    let mut result = String::new();
+    // A high surrogate waiting for the low surrogate of a `\u` pair
+    let mut high: Option<u16> = None;
+    loop {
+        let c = chars.next().ok_or("Unterminated string")?;
+        if high.is_some() && (c != '\\' || chars.peek() != Some(&'u')) {
+            return Err("Unpaired surrogate in string".to_string());
+        }
+        match c {
+            '"' => return Ok(result),
+            '\\' => match chars.next().ok_or("Unterminated string")? {
+                'n' => result.push('\n'),
+                'r' => result.push('\r'),
+                't' => result.push('\t'),
+                'b' => result.push('\u{8}'),
+                'f' => result.push('\u{c}'),
+                '0' => result.push('\0'),
+                '"' => result.push('"'),
+                '\'' => result.push('\''),
+                '\\' => result.push('\\'),
+                'u' => {
+                    let digits: String = chars.by_ref().take(4).collect();
+                    let unit = u16::from_str_radix(&digits, 16)
+                        .ok()
+                        .filter(|_| digits.len() == 4)
+                        .ok_or_else(|| format!("Invalid escape \\u{}", digits))?;
+                    let units = match high.take() {
+                        Some(high) => vec![high, unit],
+                        None if (0xD800..0xDC00).contains(&unit) => {
+                            high = Some(unit);
+                            continue;
+                        }
+                        None => vec![unit],
+                    };
+                    let decoded = String::from_utf16(&units)
+                        .map_err(|_| "Unpaired surrogate in string".to_string())?;
+                    result.push_str(&decoded);
+                }
+                c => return Err(format!("Invalid escape \\{}", c)),
+            },
+            c => result.push(c),
+        }
+    }
+}
+
+/// Writes `text` as a bare word when it reads back as one, and as a quoted string otherwise
+pub(super) fn quote(text: &str) -> String {
+    let bare = !text.is_empty()
+        && !text.starts_with(['.', ';'])
+        && !text.ends_with(':')
+        && reference_digits(text).is_none()
+        && !text
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '"');
+    if bare {
+        return text.to_string();
+    }
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
@@ -0,0 +1,1497 @@
+//! Reads the assembly syntax back into a class
+
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+};
+
+use bitflags::Flags;
+
+use crate::{
+    error::{ParseError, Result},
+    structs::{
+        attributes::*,
+        class::MAGIC,
+        const_pool::{ConstPoolBuilder, TryFromItem},
+        const_types::{
+            Class as ConstClass, Double, Dynamic, FieldRef, Float, Integer, InterfaceMethodRef,
+            InvokeDynamic, Long, MethodHandle, MethodHandleReference, MethodRef, MethodType,
+            Module as ModuleConst, NameAndType, Package, StringJ, Utf8,
+        },
+        instructions::{Instruction, LookupSwitch, MatchOffset, TableSwitch, Wide},
+        opcodes::Opcode,
+        Class, ClassVersion, ConstItem, Field, Index, Interface, MajorVersion, Method,
+        OffsetInstruction,
+    },
+};
+
+use super::{
+    array_type,
+    lexer::{tokenize, Line, Token},
+    opcode, parse_flag, reference_kind, unhex,
+};
+
+pub(super) fn parse(text: &str) -> Result<Class> {
+    let lines = tokenize(text)?;
+    let mut parser = Parser {
+        lines: &lines,
+        next: 0,
+        line: 0,
+        pool: ConstPoolBuilder::new(),
+    };
+    parser.class().map_err(|err| match err {
+        err @ ParseError::InvalidAssembly { .. } => err,
+        err => ParseError::InvalidAssembly {
+            line: parser.line,
+            reason: err.to_string(),
+        },
+    })
+}
+
+/// The attributes that have a directive of their own, which is the name in lowercase
+const ATTRIBUTES: [&str; 30] = [
+    "ConstantValue",
+    "Code",
+    "StackMapTable",
+    "Exceptions",
+    "InnerClasses",
+    "EnclosingMethod",
+    "Synthetic",
+    "Signature",
+    "SourceFile",
+    "SourceDebugExtension",
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "Deprecated",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "AnnotationDefault",
+    "BootstrapMethods",
+    "MethodParameters",
+    "Module",
+    "ModulePackages",
+    "ModuleMainClass",
+    "NestHost",
+    "NestMembers",
+    "Record",
+    "PermittedSubclasses",
+];
+
+/// The offsets of the labels defined in a `Code`
+type Labels = HashMap<String, u32>;
+
+/// The tokens of one line, consumed from the front
+struct Tokens<'a> {
+    line: usize,
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(line: &'a Line) -> Self {
+        Tokens {
+            line: line.number,
+            tokens: &line.tokens,
+            position: 0,
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> ParseError {
+        ParseError::InvalidAssembly {
+            line: self.line,
+            reason: reason.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&'a Token> {
+        let token = self
+            .peek()
+            .ok_or_else(|| self.error("Unexpected end of line"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn remaining(&self) -> usize {
+        self.tokens.len() - self.position
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    fn word(&mut self) -> Result<&'a str> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(self.error(format!("Expected a word, found {:?}", token))),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.is_word(keyword) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected {}", keyword)))
+        }
+    }
+
+    fn directive(&mut self) -> Result<&'a str> {
+        match self.next()? {
+            Token::Directive(directive) => Ok(directive),
+            token => Err(self.error(format!("Expected a directive, found {:?}", token))),
+        }
+    }
+
+    /// A name, which may be quoted
+    fn text(&mut self) -> Result<&'a str> {
+        match self.next()? {
+            Token::Word(text) | Token::Str(text) => Ok(text),
+            token => Err(self.error(format!("Expected a name, found {:?}", token))),
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a str> {
+        match self.next()? {
+            Token::Str(text) => Ok(text),
+            token => Err(self.error(format!("Expected a string, found {:?}", token))),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self) -> Result<T> {
+        let word = self.word()?;
+        word.parse()
+            .map_err(|_| self.error(format!("Invalid number {}", word)))
+    }
+
+    /// Takes a `[12]` slot reference if there is one
+    fn reference(&mut self) -> Option<u16> {
+        match self.peek() {
+            Some(Token::Ref(index)) => {
+                self.position += 1;
+                Some(*index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Takes a `name:` label definition if there is one
+    fn label(&mut self) -> Option<&'a str> {
+        match self.peek() {
+            Some(Token::Word(word)) if word.len() > 1 && word.ends_with(':') => {
+                self.position += 1;
+                Some(&word[..word.len() - 1])
+            }
+            _ => None,
+        }
+    }
+
+    fn finish(&self) -> Result<()> {
+        match self.peek() {
+            Some(token) => Err(self.error(format!("Unexpected {:?}", token))),
+            None => Ok(()),
+        }
+    }
+}
+
+// NaN is written as its bits, as there are many of them
+fn float(text: &str) -> Option<f32> {
+    match text.strip_prefix("0x") {
+        Some(bits) => u32::from_str_radix(bits, 16).ok().map(f32::from_bits),
+        None => text.parse().ok(),
+    }
+}
+
+fn double(text: &str) -> Option<f64> {
+    match text.strip_prefix("0x") {
+        Some(bits) => u64::from_str_radix(bits, 16).ok().map(f64::from_bits),
+        None => text.parse().ok(),
+    }
+}
+
+/// Looks up an enum generated by `gen_try_from` by the name of its variant
+fn variant<T: TryFrom<u16> + std::fmt::Debug>(name: &str) -> Option<T> {
+    (0..=u8::MAX as u16)
+        .filter_map(|value| T::try_from(value).ok())
+        .find(|variant| format!("{:?}", variant) == name)
+}
+
+struct Parser<'a> {
+    lines: &'a [Line],
+    next: usize,
+    // The number of the line being parsed, for errors that are not about a token
+    line: usize,
+    pool: ConstPoolBuilder,
+}
+
+/// An instruction whose jump targets are only known once all labels are defined
+struct Pending<'a> {
+    line: usize,
+    offset: u32,
+    instruction: Instruction,
+    // Like `Instruction::branch_targets`, the default of a switch comes first
+    targets: Vec<&'a str>,
+}
+
+impl<'a> Parser<'a> {
+    fn next_line(&mut self) -> Option<Tokens<'a>> {
+        let lines = self.lines;
+        let line = lines.get(self.next)?;
+        self.next += 1;
+        self.line = line.number;
+        Some(Tokens::new(line))
+    }
+
+    /// The next line of a block, or `None` once its `.end` is reached
+    fn block_line(&mut self, block: &str) -> Result<Option<Tokens<'a>>> {
+        let mut tokens = self
+            .next_line()
+            .ok_or_else(|| ParseError::InvalidAssembly {
+                line: self.line,
+                reason: format!("Missing .end {}", block),
+            })?;
+        if !matches!(tokens.peek(), Some(Token::Directive(directive)) if directive == ".end") {
+            return Ok(Some(tokens));
+        }
+        tokens.next()?;
+        if tokens.word()? != block {
+            return Err(tokens.error(format!("Expected .end {}", block)));
+        }
+        tokens.finish()?;
+        Ok(None)
+    }
+
+    fn class(&mut self) -> Result<Class> {
+        self.constants()?;
+
+        let mut version = None;
+        let mut header = None;
+        let mut super_class = None;
+        let mut interfaces = Vec::new();
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        let mut attributes = Vec::new();
+        let mut ended = false;
+        while let Some(mut tokens) = self.next_line() {
+            if ended {
+                return Err(tokens.error("Unexpected line after .end class"));
+            }
+            let directive = tokens.directive()?;
+            match directive {
+                ".version" => {
+                    let major = tokens.number::<u16>()?;
+                    let major: MajorVersion = major.try_into()?;
+                    version = Some(ClassVersion {
+                        major,
+                        minor: tokens.number()?,
+                    });
+                }
+                ".class" => {
+                    let count = tokens.remaining().saturating_sub(1);
+                    let flags = self.flags(&mut tokens, count)?;
+                    header = Some((flags, self.slot(&mut tokens, "Class")?));
+                }
+                ".super" => super_class = Some(self.slot(&mut tokens, "Class")?),
+                ".implements" => interfaces.push(Interface {
+                    index: self.slot(&mut tokens, "Class")?,
+                }),
+                // Declared before anything else is parsed
+                ".const" => continue,
+                ".field" => {
+                    let (access_flags, name_index, descriptor_index) = self.member(&mut tokens)?;
+                    fields.push(Field {
+                        access_flags,
+                        name_index,
+                        descriptor_index,
+                        attributes: self.attributes("field", None)?,
+                    });
+                }
+                ".method" => {
+                    let (access_flags, name_index, descriptor_index) = self.member(&mut tokens)?;
+                    methods.push(Method {
+                        access_flags,
+                        name_index,
+                        descriptor_index,
+                        attributes: self.attributes("method", None)?,
+                    });
+                }
+                ".end" => {
+                    tokens.keyword("class")?;
+                    ended = true;
+                }
+                _ => attributes.push(self.attribute(directive, &mut tokens, None)?),
+            }
+            tokens.finish()?;
+        }
+
+        let missing = |directive: &str| ParseError::InvalidAssembly {
+            line: self.line,
+            reason: format!("Missing {}", directive),
+        };
+        let version = version.ok_or_else(|| missing(".version"))?;
+        let (access_flags, this_class) = header.ok_or_else(|| missing(".class"))?;
+        let super_class = super_class.ok_or_else(|| missing(".super"))?;
+        let constant_pool = std::mem::take(&mut self.pool).build()?;
+        Ok(Class {
+            magic: MAGIC,
+            version,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+            attributes,
+            diagnostics: Vec::new(),
+        })
+    }
+
+    /// Places the `.const` declarations first, so constants written by value find them
+    fn constants(&mut self) -> Result<()> {
+        let mut declarations = Vec::new();
+        for line in self.lines {
+            let mut tokens = Tokens::new(line);
+            if !matches!(tokens.peek(), Some(Token::Directive(directive)) if directive == ".const")
+            {
+                continue;
+            }
+            tokens.next()?;
+            let index = tokens
+                .reference()
+                .filter(|&index| index != 0)
+                .ok_or_else(|| tokens.error("Expected a constant pool slot like [1]"))?;
+            tokens.keyword("=")?;
+            let wide = tokens.is_word("Long") || tokens.is_word("Double");
+            self.pool.reserve(index as usize + if wide { 2 } else { 1 });
+            declarations.push((tokens, index));
+        }
+        for (mut tokens, index) in declarations {
+            self.line = tokens.line;
+            if self.pool.entry(index).is_some() {
+                return Err(tokens.error(format!("Constant pool slot {} is declared twice", index)));
+            }
+            let kind = tokens.word()?;
+            let item = self.item(&mut tokens, kind)?;
+            tokens.finish()?;
+            self.pool
+                .declare(index, item)
+                .map_err(|err| tokens.error(err.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// The value of a constant of the given kind
+    fn item(&mut self, tokens: &mut Tokens<'a>, kind: &str) -> Result<ConstItem> {
+        Ok(match kind {
+            "Utf8" => ConstItem::Utf8(Utf8::new(tokens.text()?)),
+            "Utf8Bytes" => {
+                let bytes =
+                    unhex(tokens.string()?).ok_or_else(|| tokens.error("Invalid hex bytes"))?;
+                ConstItem::Utf8(Utf8::from_bytes(bytes))
+            }
+            "Int" => ConstItem::Integer(Integer {
+                value: tokens.number()?,
+            }),
+            "Float" => {
+                let word = tokens.word()?;
+                let value =
+                    float(word).ok_or_else(|| tokens.error(format!("Invalid float {}", word)))?;
+                ConstItem::Float(Float { value })
+            }
+            "Long" => ConstItem::Long(Long {
+                value: tokens.number()?,
+            }),
+            "Double" => {
+                let word = tokens.word()?;
+                let value =
+                    double(word).ok_or_else(|| tokens.error(format!("Invalid double {}", word)))?;
+                ConstItem::Double(Double { value })
+            }
+            "Class" => ConstItem::Class(ConstClass {
+                name_index: self.slot(tokens, "Utf8")?,
+            }),
+            "String" => ConstItem::StringJ(StringJ {
+                string_index: self.slot(tokens, "Utf8")?,
+            }),
+            "Field" => ConstItem::FieldRef(FieldRef {
+                class_index: self.slot(tokens, "Class")?,
+                name_and_type_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "Method" => ConstItem::MethodRef(MethodRef {
+                class_index: self.slot(tokens, "Class")?,
+                name_and_type_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "InterfaceMethod" => ConstItem::InterfaceMethodRef(InterfaceMethodRef {
+                class_index: self.slot(tokens, "Class")?,
+                name_and_type_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "NameAndType" => ConstItem::NameAndType(NameAndType {
+                name_index: self.slot(tokens, "Utf8")?,
+                descriptor_index: self.slot(tokens, "Utf8")?,
+            }),
+            "MethodHandle" => {
+                let word = tokens.word()?;
+                let reference_kind = reference_kind(word)
+                    .ok_or_else(|| tokens.error(format!("Unknown reference kind {}", word)))?;
+                // Whether an `InvokeStatic` refers to an interface is settled once the pool is built
+                let reference_index = self.constant(tokens)?.index;
+                ConstItem::MethodHandle(MethodHandle {
+                    reference_index: MethodHandleReference::for_kind(
+                        &reference_kind,
+                        reference_index,
+                    ),
+                    reference_kind,
+                })
+            }
+            "MethodType" => ConstItem::MethodType(MethodType {
+                descriptor_index: self.slot(tokens, "Utf8")?,
+            }),
+            "Dynamic" => ConstItem::Dynamic(Dynamic {
+                bootstrap_method_attr_index: tokens.number()?,
+                name_and_type_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "InvokeDynamic" => ConstItem::InvokeDynamic(InvokeDynamic {
+                bootstrap_method_attr_index: tokens.number()?,
+                name_and_type_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "Module" => ConstItem::Module(ModuleConst {
+                name_index: self.slot(tokens, "Utf8")?,
+            }),
+            "Package" => ConstItem::Package(Package {
+                name_index: self.slot(tokens, "Utf8")?,
+            }),
+            _ => return Err(tokens.error(format!("Unknown kind of constant {}", kind))),
+        })
+    }
+
+    /// A constant whose kind is implied, either as a `[12]` slot or by value
+    fn slot<T: TryFromItem>(&mut self, tokens: &mut Tokens<'a>, kind: &str) -> Result<Index<T>> {
+        if let Some(index) = tokens.reference() {
+            return Ok(Index::new(index));
+        }
+        let item = self.item(tokens, kind)?;
+        Ok(Index::new(self.pool.add(item)?.index))
+    }
+
+    /// A constant preceded by its kind, or a `[12]` slot
+    fn constant(&mut self, tokens: &mut Tokens<'a>) -> Result<Index<ConstItem>> {
+        if let Some(index) = tokens.reference() {
+            return Ok(Index::new(index));
+        }
+        let kind = tokens.word()?;
+        let item = self.item(tokens, kind)?;
+        Ok(Index::new(self.pool.add(item)?.index))
+    }
+
+    /// Exactly `count` flags
+    fn flags<F: Flags<Bits = u16>>(&self, tokens: &mut Tokens<'a>, count: usize) -> Result<F> {
+        let mut flags = F::empty();
+        for _ in 0..count {
+            let word = tokens.word()?;
+            let flag = parse_flag::<F>(word)
+                .ok_or_else(|| tokens.error(format!("Unknown flag {}", word)))?;
+            flags.insert(flag);
+        }
+        Ok(flags)
+    }
+
+    /// As many flags as there are before the next keyword
+    fn optional_flags<F: Flags<Bits = u16>>(&self, tokens: &mut Tokens<'a>) -> F {
+        let mut flags = F::empty();
+        while let Some(Token::Word(word)) = tokens.peek() {
+            match parse_flag::<F>(word) {
+                Some(flag) => flags.insert(flag),
+                None => break,
+            }
+            tokens.position += 1;
+        }
+        flags
+    }
+
+    /// The flags, name and descriptor of a field or method
+    fn member<F: Flags<Bits = u16>>(
+        &mut self,
+        tokens: &mut Tokens<'a>,
+    ) -> Result<(F, Index<Utf8>, Index<Utf8>)> {
+        let count = tokens.remaining().saturating_sub(2);
+        let flags = self.flags(tokens, count)?;
+        Ok((
+            flags,
+            self.slot(tokens, "Utf8")?,
+            self.slot(tokens, "Utf8")?,
+        ))
+    }
+
+    /// The attributes of a block, up to its `.end`
+    fn attributes(&mut self, block: &str, labels: Option<&Labels>) -> Result<Attributes> {
+        let mut attributes = Vec::new();
+        while let Some(mut tokens) = self.block_line(block)? {
+            let directive = tokens.directive()?;
+            attributes.push(self.attribute(directive, &mut tokens, labels)?);
+            tokens.finish()?;
+        }
+        Ok(attributes)
+    }
+
+    fn attribute(
+        &mut self,
+        directive: &str,
+        tokens: &mut Tokens<'a>,
+        labels: Option<&Labels>,
+    ) -> Result<Attribute> {
+        let (name_index, directive) = if directive == ".attribute" {
+            let name_index = self.slot(tokens, "Utf8")?;
+            if let Some(Token::Str(_)) = tokens.peek() {
+                let bytes =
+                    unhex(tokens.string()?).ok_or_else(|| tokens.error("Invalid hex bytes"))?;
+                return Ok(Attribute {
+                    attribute_name_index: name_index,
+                    info: AttributeInfo::Unknown(Unknown { bytes, offset: 0 }),
+                });
+            }
+            (Some(name_index), tokens.directive()?)
+        } else {
+            (None, directive)
+        };
+        let name = *ATTRIBUTES
+            .iter()
+            .find(|name| directive[1..] == name.to_lowercase())
+            .ok_or_else(|| tokens.error(format!("Unknown directive {}", directive)))?;
+        let attribute_name_index = match name_index {
+            Some(index) => index,
+            None => self.pool.utf8(name)?,
+        };
+        let block = name.to_lowercase();
+
+        let info = match name {
+            "ConstantValue" => AttributeInfo::ConstantValue(ConstantValue {
+                constant_value_index: self.constant(tokens)?,
+            }),
+            "Code" => AttributeInfo::Code(self.code(tokens)?),
+            "StackMapTable" => {
+                tokens.finish()?;
+                AttributeInfo::StackMapTable(self.stack_map_table(labels)?)
+            }
+            "Exceptions" => AttributeInfo::Exceptions(Exceptions {
+                exception_index_table: self.classes(tokens)?,
+            }),
+            "InnerClasses" => {
+                tokens.finish()?;
+                let mut classes = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    let inner_class_info_index = self.slot(&mut tokens, "Class")?;
+                    let outer_class_info_index = self.slot(&mut tokens, "Class")?;
+                    let inner_name_index = self.slot(&mut tokens, "Utf8")?;
+                    let count = tokens.remaining();
+                    classes.push(InnerClass {
+                        inner_class_info_index,
+                        outer_class_info_index,
+                        inner_name_index,
+                        inner_class_access_flags: self.flags(&mut tokens, count)?,
+                    });
+                }
+                AttributeInfo::InnerClasses(InnerClasses { classes })
+            }
+            "EnclosingMethod" => AttributeInfo::EnclosingMethod(EnclosingMethod {
+                class_index: self.slot(tokens, "Class")?,
+                method_index: self.slot(tokens, "NameAndType")?,
+            }),
+            "Synthetic" => AttributeInfo::Synthetic(Synthetic()),
+            "Signature" => AttributeInfo::Signature(Signature {
+                signature_index: self.slot(tokens, "Utf8")?,
+            }),
+            "SourceFile" => AttributeInfo::SourceFile(SourceFile {
+                sourcefile_index: self.slot(tokens, "Utf8")?,
+            }),
+            "SourceDebugExtension" => {
+                let debug_extension = if tokens.remaining() == 2 {
+                    let kind = tokens.word()?;
+                    match self.item(tokens, kind)? {
+                        ConstItem::Utf8(utf8) => utf8,
+                        _ => return Err(tokens.error("Expected Utf8Bytes")),
+                    }
+                } else {
+                    Utf8::new(tokens.text()?)
+                };
+                AttributeInfo::SourceDebugExtension(SourceDebugExtension { debug_extension })
+            }
+            "LineNumberTable" => {
+                tokens.finish()?;
+                let mut line_number_table = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    line_number_table.push(LineNumber {
+                        start_pc: offset(&mut tokens, labels)?,
+                        line_number: tokens.number()?,
+                    });
+                    tokens.finish()?;
+                }
+                AttributeInfo::LineNumberTable(LineNumberTable { line_number_table })
+            }
+            "LocalVariableTable" => {
+                tokens.finish()?;
+                let mut local_variable_table = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    let index = tokens.number()?;
+                    let name_index = self.slot(&mut tokens, "Utf8")?;
+                    let descriptor_index = self.slot(&mut tokens, "Utf8")?;
+                    let (start_pc, length) = range(&mut tokens, labels)?;
+                    local_variable_table.push(LocalVariable {
+                        start_pc,
+                        length,
+                        name_index,
+                        descriptor_index,
+                        index,
+                    });
+                    tokens.finish()?;
+                }
+                AttributeInfo::LocalVariableTable(LocalVariableTable {
+                    local_variable_table,
+                })
+            }
+            "LocalVariableTypeTable" => {
+                tokens.finish()?;
+                let mut local_variable_type_table = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    let index = tokens.number()?;
+                    let name_index = self.slot(&mut tokens, "Utf8")?;
+                    let signature_index = self.slot(&mut tokens, "Utf8")?;
+                    let (start_pc, length) = range(&mut tokens, labels)?;
+                    local_variable_type_table.push(LocalVariableType {
+                        start_pc,
+                        length,
+                        name_index,
+                        signature_index,
+                        index,
+                    });
+                    tokens.finish()?;
+                }
+                AttributeInfo::LocalVariableTypeTable(LocalVariableTypeTable {
+                    local_variable_type_table,
+                })
+            }
+            "Deprecated" => AttributeInfo::Deprecated(Deprecated()),
+            "RuntimeVisibleAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeVisibleAnnotations(RuntimeVisibleAnnotations {
+                    annotations: self.annotations(&block)?,
+                })
+            }
+            "RuntimeInvisibleAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeInvisibleAnnotations(RuntimeInvisibleAnnotations {
+                    annotations: self.annotations(&block)?,
+                })
+            }
+            "RuntimeVisibleParameterAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeVisibleParameterAnnotations(
+                    RuntimeVisibleParameterAnnotations {
+                        parameter_annotations: self.parameter_annotations(&block)?,
+                    },
+                )
+            }
+            "RuntimeInvisibleParameterAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeInvisibleParameterAnnotations(
+                    RuntimeInvisibleParameterAnnotations {
+                        parameter_annotations: self.parameter_annotations(&block)?,
+                    },
+                )
+            }
+            "RuntimeVisibleTypeAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeVisibleTypeAnnotations(RuntimeVisibleTypeAnnotations {
+                    annotations: self.type_annotations(&block, labels)?,
+                })
+            }
+            "RuntimeInvisibleTypeAnnotations" => {
+                tokens.finish()?;
+                AttributeInfo::RuntimeInvisibleTypeAnnotations(RuntimeInvisibleTypeAnnotations {
+                    annotations: self.type_annotations(&block, labels)?,
+                })
+            }
+            "AnnotationDefault" => AttributeInfo::AnnotationDefault(AnnotationDefault {
+                default_value: self.element_value(tokens)?,
+            }),
+            "BootstrapMethods" => {
+                tokens.finish()?;
+                let mut bootstrap_methods = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    let bootstrap_method_ref = self.slot(&mut tokens, "MethodHandle")?;
+                    let mut bootstrap_arguments = Vec::new();
+                    while tokens.remaining() > 0 {
+                        bootstrap_arguments.push(self.constant(&mut tokens)?);
+                    }
+                    bootstrap_methods.push(BootstrapMethod {
+                        bootstrap_method_ref,
+                        bootstrap_arguments,
+                    });
+                }
+                AttributeInfo::BootstrapMethods(BootstrapMethods { bootstrap_methods })
+            }
+            "MethodParameters" => {
+                tokens.finish()?;
+                let mut parameters = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    let name_index = self.slot(&mut tokens, "Utf8")?;
+                    let count = tokens.remaining();
+                    parameters.push(MethodParameter {
+                        name_index,
+                        access_flags: self.flags(&mut tokens, count)?,
+                    });
+                }
+                AttributeInfo::MethodParameters(MethodParameters { parameters })
+            }
+            "Module" => AttributeInfo::Module(self.module(tokens)?),
+            "ModulePackages" => {
+                let mut package_index = Vec::new();
+                while tokens.remaining() > 0 {
+                    package_index.push(self.slot(tokens, "Package")?);
+                }
+                AttributeInfo::ModulePackages(ModulePackages { package_index })
+            }
+            "ModuleMainClass" => AttributeInfo::ModuleMainClass(ModuleMainClass {
+                main_class_index: self.slot(tokens, "Class")?,
+            }),
+            "NestHost" => AttributeInfo::NestHost(NestHost {
+                host_class_index: self.slot(tokens, "Class")?,
+            }),
+            "NestMembers" => AttributeInfo::NestMembers(NestMembers {
+                classes: self.classes(tokens)?,
+            }),
+            "Record" => {
+                tokens.finish()?;
+                let mut components = Vec::new();
+                while let Some(mut tokens) = self.block_line(&block)? {
+                    expect_directive(&mut tokens, ".component")?;
+                    let name_index = self.slot(&mut tokens, "Utf8")?;
+                    let descriptor_index = self.slot(&mut tokens, "Utf8")?;
+                    tokens.finish()?;
+                    components.push(RecordComponent {
+                        name_index,
+                        descriptor_index,
+                        attributes: self.attributes("component", None)?,
+                    });
+                }
+                AttributeInfo::Record(Record { components })
+            }
+            "PermittedSubclasses" => AttributeInfo::PermittedSubclasses(PermittedSubclasses {
+                classes: self.classes(tokens)?,
+            }),
+            _ => unreachable!(),
+        };
+        Ok(Attribute {
+            attribute_name_index,
+            info,
+        })
+    }
+
+    /// The classes up to the end of the line
+    fn classes(&mut self, tokens: &mut Tokens<'a>) -> Result<Vec<Index<ConstClass>>> {
+        let mut classes = Vec::new();
+        while tokens.remaining() > 0 {
+            classes.push(self.slot(tokens, "Class")?);
+        }
+        Ok(classes)
+    }
+
+    fn code(&mut self, tokens: &mut Tokens<'a>) -> Result<Code> {
+        tokens.keyword("stack")?;
+        let max_stack = tokens.number()?;
+        tokens.keyword("locals")?;
+        let max_locals = tokens.number()?;
+        tokens.finish()?;
+
+        let mut labels = Labels::new();
+        let mut pending = Vec::new();
+        let mut offset: u32 = 0;
+        let mut exception_table = Vec::new();
+        let mut attributes = Vec::new();
+        while let Some(mut tokens) = self.block_line("code")? {
+            while let Some(label) = tokens.label() {
+                if labels.insert(label.to_string(), offset).is_some() {
+                    return Err(tokens.error(format!("Label {} is defined twice", label)));
+                }
+            }
+            match tokens.peek() {
+                None => continue,
+                Some(Token::Directive(_)) => {
+                    let directive = tokens.directive()?;
+                    if directive == ".catch" {
+                        exception_table.push(self.catch(&mut tokens, &labels)?);
+                    } else {
+                        attributes.push(self.attribute(directive, &mut tokens, Some(&labels))?);
+                    }
+                }
+                Some(_) if !exception_table.is_empty() || !attributes.is_empty() => {
+                    return Err(tokens.error(
+                        "Instructions must come before the exception table and attributes",
+                    ));
+                }
+                Some(_) => {
+                    let line = tokens.line;
+                    let (instruction, targets) = self.instruction(&mut tokens)?;
+                    let mut bytes = Vec::new();
+                    instruction
+                        .write_at(&mut bytes, offset)
+                        .map_err(|err| tokens.error(err.to_string()))?;
+                    pending.push(Pending {
+                        line,
+                        offset,
+                        instruction,
+                        targets,
+                    });
+                    offset += bytes.len() as u32;
+                    if offset > u16::MAX as u32 {
+                        return Err(tokens.error("The code is longer than 65535 bytes"));
+                    }
+                }
+            }
+            tokens.finish()?;
+        }
+
+        let mut code = Vec::with_capacity(pending.len());
+        for mut instruction in pending {
+            let line = instruction.line;
+            let error = |reason: String| ParseError::InvalidAssembly { line, reason };
+            let mut relative = Vec::with_capacity(instruction.targets.len());
+            for target in &instruction.targets {
+                let target = resolve(target, Some(&labels))
+                    .ok_or_else(|| error(format!("Unknown label {}", target)))?;
+                relative.push(target as i64 - instruction.offset as i64);
+            }
+            set_targets(&mut instruction.instruction, &relative).map_err(error)?;
+            code.push(OffsetInstruction {
+                offset: instruction.offset,
+                instruction: instruction.instruction,
+            });
+        }
+        Ok(Code {
+            max_stack,
+            max_locals,
+            code,
+            exception_table,
+            attributes,
+        })
+    }
+
+    fn catch(&mut self, tokens: &mut Tokens<'a>, labels: &Labels) -> Result<ExceptionTable> {
+        let catch_type = if tokens.is_word("any") {
+            tokens.next()?;
+            Index::new(0)
+        } else {
+            self.slot(tokens, "Class")?
+        };
+        tokens.keyword("from")?;
+        let start_pc = offset(tokens, Some(labels))?;
+        tokens.keyword("to")?;
+        let end_pc = offset(tokens, Some(labels))?;
+        tokens.keyword("using")?;
+        let handler_pc = offset(tokens, Some(labels))?;
+        Ok(ExceptionTable {
+            start_pc,
+            end_pc,
+            handler_pc,
+            catch_type,
+        })
+    }
+
+    /// An instruction with its jump targets set to 0, and the labels of the targets
+    fn instruction(&mut self, tokens: &mut Tokens<'a>) -> Result<(Instruction, Vec<&'a str>)> {
+        let name = tokens.word()?;
+        let opcode =
+            opcode(name).ok_or_else(|| tokens.error(format!("Unknown instruction {}", name)))?;
+        if let Some(instruction) = Instruction::simple(opcode) {
+            return Ok((instruction, Vec::new()));
+        }
+        let instruction = match opcode {
+            Opcode::bipush => Instruction::bipush(tokens.number()?),
+            Opcode::sipush => Instruction::sipush(tokens.number()?),
+            Opcode::ldc => Instruction::ldc(self.constant(tokens)?),
+            Opcode::ldc_w => Instruction::ldc_w(self.constant(tokens)?),
+            Opcode::ldc2_w => Instruction::ldc2_w(self.constant(tokens)?),
+            Opcode::iload => Instruction::iload(tokens.number()?),
+            Opcode::lload => Instruction::lload(tokens.number()?),
+            Opcode::fload => Instruction::fload(tokens.number()?),
+            Opcode::dload => Instruction::dload(tokens.number()?),
+            Opcode::aload => Instruction::aload(tokens.number()?),
+            Opcode::istore => Instruction::istore(tokens.number()?),
+            Opcode::lstore => Instruction::lstore(tokens.number()?),
+            Opcode::fstore => Instruction::fstore(tokens.number()?),
+            Opcode::dstore => Instruction::dstore(tokens.number()?),
+            Opcode::astore => Instruction::astore(tokens.number()?),
+            Opcode::iinc => Instruction::iinc(tokens.number()?, tokens.number()?),
+            Opcode::ifeq => Instruction::ifeq(0),
+            Opcode::ifne => Instruction::ifne(0),
+            Opcode::iflt => Instruction::iflt(0),
+            Opcode::ifge => Instruction::ifge(0),
+            Opcode::ifgt => Instruction::ifgt(0),
+            Opcode::ifle => Instruction::ifle(0),
+            Opcode::if_icmpeq => Instruction::if_icmpeq(0),
+            Opcode::if_icmpne => Instruction::if_icmpne(0),
+            Opcode::if_icmplt => Instruction::if_icmplt(0),
+            Opcode::if_icmpge => Instruction::if_icmpge(0),
+            Opcode::if_icmpgt => Instruction::if_icmpgt(0),
+            Opcode::if_icmple => Instruction::if_icmple(0),
+            Opcode::if_acmpeq => Instruction::if_acmpeq(0),
+            Opcode::if_acmpne => Instruction::if_acmpne(0),
+            Opcode::goto => Instruction::goto(0),
+            Opcode::jsr => Instruction::jsr(0),
+            Opcode::ret => Instruction::ret(tokens.number()?),
+            Opcode::tableswitch => return self.table_switch(tokens),
+            Opcode::lookupswitch => return self.lookup_switch(tokens),
+            Opcode::getstatic => Instruction::getstatic(self.slot(tokens, "Field")?),
+            Opcode::putstatic => Instruction::putstatic(self.slot(tokens, "Field")?),
+            Opcode::getfield => Instruction::getfield(self.slot(tokens, "Field")?),
+            Opcode::putfield => Instruction::putfield(self.slot(tokens, "Field")?),
+            Opcode::invokevirtual => Instruction::invokevirtual(self.slot(tokens, "Method")?),
+            Opcode::invokespecial => Instruction::invokespecial(self.constant(tokens)?),
+            Opcode::invokestatic => Instruction::invokestatic(self.constant(tokens)?),
+            Opcode::invokeinterface => Instruction::invokeinterface(
+                self.slot(tokens, "InterfaceMethod")?,
+                tokens.number()?,
+            ),
+            Opcode::invokedynamic => {
+                Instruction::invokedynamic(self.slot(tokens, "InvokeDynamic")?)
+            }
+            Opcode::new => Instruction::new(self.slot(tokens, "Class")?),
+            Opcode::newarray => {
+                let word = tokens.word()?;
+                let array_type = array_type(word)
+                    .ok_or_else(|| tokens.error(format!("Unknown array type {}", word)))?;
+                Instruction::newarray(array_type)
+            }
+            Opcode::anewarray => Instruction::anewarray(self.slot(tokens, "Class")?),
+            Opcode::checkcast => Instruction::checkcast(self.slot(tokens, "Class")?),
+            Opcode::instanceof => Instruction::instanceof(self.slot(tokens, "Class")?),
+            Opcode::wide => Instruction::wide(wide(tokens)?),
+            Opcode::multianewarray => {
+                Instruction::multianewarray(self.slot(tokens, "Class")?, tokens.number()?)
+            }
+            Opcode::ifnull => Instruction::ifnull(0),
+            Opcode::ifnonnull => Instruction::ifnonnull(0),
+            Opcode::goto_w => Instruction::goto_w(0),
+            Opcode::jsr_w => Instruction::jsr_w(0),
+            _ => return Err(tokens.error(format!("{} cannot be used in a class file", name))),
+        };
+        // The only operand left is the target of a branch
+        let targets = if instruction.branch_targets(0).is_empty() {
+            Vec::new()
+        } else {
+            vec![tokens.word()?]
+        };
+        Ok((instruction, targets))
+    }
+
+    /// A `tableswitch low high`, followed by a line for each target and one for the default
+    fn table_switch(&mut self, tokens: &mut Tokens<'a>) -> Result<(Instruction, Vec<&'a str>)> {
+        let low: i32 = tokens.number()?;
+        let high: i32 = tokens.number()?;
+        let mut targets = vec![""];
+        loop {
+            let mut tokens = self.switch_line()?;
+            if tokens.is_word("default:") {
+                tokens.next()?;
+                targets[0] = tokens.word()?;
+                tokens.finish()?;
+                break;
+            }
+            targets.push(tokens.word()?);
+            tokens.finish()?;
+        }
+        if targets.len() as i64 - 1 != high as i64 - low as i64 + 1 {
+            return Err(tokens.error(format!(
+                "tableswitch from {} to {} has {} targets",
+                low,
+                high,
+                targets.len() - 1
+            )));
+        }
+        let switch = TableSwitch {
+            default: 0,
+            low,
+            high,
+            offsets: vec![0; targets.len() - 1],
+        };
+        Ok((Instruction::tableswitch(switch), targets))
+    }
+
+    /// A `lookupswitch`, followed by a `key: target` line for each key and one for the default
+    fn lookup_switch(&mut self, tokens: &mut Tokens<'a>) -> Result<(Instruction, Vec<&'a str>)> {
+        tokens.finish()?;
+        let mut targets = vec![""];
+        let mut pairs = Vec::new();
+        loop {
+            let mut tokens = self.switch_line()?;
+            let key = tokens
+                .label()
+                .ok_or_else(|| tokens.error("Expected a key like 1:"))?;
+            if key == "default" {
+                targets[0] = tokens.word()?;
+                tokens.finish()?;
+                break;
+            }
+            let key = key
+                .parse()
+                .map_err(|_| tokens.error(format!("Invalid key {}", key)))?;
+            pairs.push(MatchOffset { key, offset: 0 });
+            targets.push(tokens.word()?);
+            tokens.finish()?;
+        }
+        let switch = LookupSwitch { default: 0, pairs };
+        Ok((Instruction::lookupswitch(switch), targets))
+    }
+
+    fn switch_line(&mut self) -> Result<Tokens<'a>> {
+        self.next_line().ok_or_else(|| ParseError::InvalidAssembly {
+            line: self.line,
+            reason: "Missing the default: of the switch".to_string(),
+        })
+    }
+
+    fn stack_map_table(&mut self, labels: Option<&Labels>) -> Result<StackMapTable> {
+        let mut entries = Vec::new();
+        let mut previous: Option<u16> = None;
+        while let Some(mut tokens) = self.block_line("stackmaptable")? {
+            let kind = tokens.word()?;
+            let offset = offset(&mut tokens, labels)?;
+            // Every frame but the first is one past the previous offset plus the delta
+            let delta = match previous {
+                None => Some(offset),
+                Some(previous) => offset.checked_sub(previous).and_then(|d| d.checked_sub(1)),
+            };
+            let offset_delta =
+                delta.ok_or_else(|| tokens.error("Frames must be in order of their offset"))?;
+            previous = Some(offset);
+
+            // A frame is widened when edits moved it out of reach of its compact form
+            let frame = match kind {
+                "same" if offset_delta <= 63 => StackMapFrame::Same { offset_delta },
+                "same" | "same_extended" => StackMapFrame::SameExtended { offset_delta },
+                "same_locals_1_stack_item" if offset_delta <= 63 => {
+                    StackMapFrame::SameLocals1StackItem {
+                        offset_delta,
+                        stack: self.verification_type(&mut tokens, labels)?,
+                    }
+                }
+                "same_locals_1_stack_item" | "same_locals_1_stack_item_extended" => {
+                    StackMapFrame::SameLocals1StackItemExtended {
+                        offset_delta,
+                        stack: self.verification_type(&mut tokens, labels)?,
+                    }
+                }
+                "chop" => StackMapFrame::Chop {
+                    k: tokens.number()?,
+                    offset_delta,
+                },
+                "append" => StackMapFrame::Append {
+                    offset_delta,
+                    locals: self.verification_types(&mut tokens, labels, None)?,
+                },
+                "full" => {
+                    tokens.keyword("locals")?;
+                    let locals = self.verification_types(&mut tokens, labels, Some("stack"))?;
+                    tokens.keyword("stack")?;
+                    StackMapFrame::Full {
+                        offset_delta,
+                        locals,
+                        stack: self.verification_types(&mut tokens, labels, None)?,
+                    }
+                }
+                _ => return Err(tokens.error(format!("Unknown frame type {}", kind))),
+            };
+            tokens.finish()?;
+            entries.push(frame);
+        }
+        Ok(StackMapTable { entries })
+    }
+
+    /// The types up to `until`, or to the end of the line
+    fn verification_types(
+        &mut self,
+        tokens: &mut Tokens<'a>,
+        labels: Option<&Labels>,
+        until: Option<&str>,
+    ) -> Result<Vec<VerificationTypeInfo>> {
+        let mut types = Vec::new();
+        while tokens.remaining() > 0 && !until.is_some_and(|until| tokens.is_word(until)) {
+            types.push(self.verification_type(tokens, labels)?);
+        }
+        Ok(types)
+    }
+
+    fn verification_type(
+        &mut self,
+        tokens: &mut Tokens<'a>,
+        labels: Option<&Labels>,
+    ) -> Result<VerificationTypeInfo> {
+        let word = tokens.word()?;
+        Ok(match word {
+            "Top" => VerificationTypeInfo::Top,
+            "Integer" => VerificationTypeInfo::Integer,
+            "Float" => VerificationTypeInfo::Float,
+            "Double" => VerificationTypeInfo::Double,
+            "Long" => VerificationTypeInfo::Long,
+            "Null" => VerificationTypeInfo::Null,
+            "UninitializedThis" => VerificationTypeInfo::UninitializedThis,
+            "Object" => VerificationTypeInfo::Object(self.slot(tokens, "Class")?),
+            "Uninitialized" => VerificationTypeInfo::Uninitialized(offset(tokens, labels)?),
+            _ => return Err(tokens.error(format!("Unknown verification type {}", word))),
+        })
+    }
+
+    /// The `.annotation` blocks up to `.end block`
+    fn annotations(&mut self, block: &str) -> Result<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+        while let Some(mut tokens) = self.block_line(block)? {
+            expect_directive(&mut tokens, ".annotation")?;
+            annotations.push(self.annotation(&mut tokens, "annotation")?);
+        }
+        Ok(annotations)
+    }
+
+    fn parameter_annotations(&mut self, block: &str) -> Result<Vec<ParameterAnnotations>> {
+        let mut parameters = Vec::new();
+        while let Some(mut tokens) = self.block_line(block)? {
+            expect_directive(&mut tokens, ".parameter")?;
+            tokens.finish()?;
+            parameters.push(ParameterAnnotations {
+                annotations: self.annotations("parameter")?,
+            });
+        }
+        Ok(parameters)
+    }
+
+    /// The type of an annotation at the end of the line, followed by its elements up to
+    /// `.end block`
+    fn annotation(&mut self, tokens: &mut Tokens<'a>, block: &str) -> Result<Annotation> {
+        let type_index = self.slot(tokens, "Utf8")?;
+        tokens.finish()?;
+        let mut element_value_pairs = Vec::new();
+        while let Some(mut tokens) = self.block_line(block)? {
+            let element_name_index = self.slot(&mut tokens, "Utf8")?;
+            tokens.keyword("=")?;
+            element_value_pairs.push(ElementValuePair {
+                element_name_index,
+                value: self.element_value(&mut tokens)?,
+            });
+            tokens.finish()?;
+        }
+        Ok(Annotation {
+            type_index,
+            element_value_pairs,
+        })
+    }
+
+    fn element_value(&mut self, tokens: &mut Tokens<'a>) -> Result<ElementValue> {
+        let tag = tokens.word()?;
+        Ok(match tag {
+            "byte" => ElementValue::Byte(self.slot(tokens, "Int")?),
+            "char" => ElementValue::Char(self.slot(tokens, "Int")?),
+            "double" => ElementValue::Double(self.slot(tokens, "Double")?),
+            "float" => ElementValue::Float(self.slot(tokens, "Float")?),
+            "int" => ElementValue::Int(self.slot(tokens, "Int")?),
+            "long" => ElementValue::Long(self.slot(tokens, "Long")?),
+            "short" => ElementValue::Short(self.slot(tokens, "Int")?),
+            "boolean" => ElementValue::Boolean(self.slot(tokens, "Int")?),
+            "string" => ElementValue::String(self.slot(tokens, "Utf8")?),
+            "enum" => ElementValue::Enum {
+                type_name_index: self.slot(tokens, "Utf8")?,
+                const_name_index: self.slot(tokens, "Utf8")?,
+            },
+            "class" => ElementValue::Class(self.slot(tokens, "Utf8")?),
+            "annotation" => ElementValue::Annotation(self.annotation(tokens, "annotation")?),
+            "array" => {
+                tokens.finish()?;
+                let mut values = Vec::new();
+                while let Some(mut tokens) = self.block_line("array")? {
+                    values.push(self.element_value(&mut tokens)?);
+                    tokens.finish()?;
+                }
+                ElementValue::Array(values)
+            }
+            _ => return Err(tokens.error(format!("Unknown element value {}", tag))),
+        })
+    }
+
+    fn type_annotations(
+        &mut self,
+        block: &str,
+        labels: Option<&Labels>,
+    ) -> Result<Vec<TypeAnnotation>> {
+        let mut annotations = Vec::new();
+        while let Some(mut tokens) = self.block_line(block)? {
+            expect_directive(&mut tokens, ".typeannotation")?;
+            let word = tokens.word()?;
+            let target_type: TargetType =
+                variant(word).ok_or_else(|| tokens.error(format!("Unknown target {}", word)))?;
+            let target_info = match target_type {
+                TargetType::ClassTypeParameter | TargetType::MethodTypeParameter => {
+                    TargetInfo::TypeParameter {
+                        type_parameter_index: tokens.number()?,
+                    }
+                }
+                TargetType::Supertype => TargetInfo::Supertype {
+                    supertype_index: tokens.number()?,
+                },
+                TargetType::ClassTypeParameterBound | TargetType::MethodTypeParameterBound => {
+                    TargetInfo::TypeParameterBound {
+                        type_parameter_index: tokens.number()?,
+                        bound_index: tokens.number()?,
+                    }
+                }
+                TargetType::Field | TargetType::MethodReturn | TargetType::MethodReceiver => {
+                    TargetInfo::Empty
+                }
+                TargetType::MethodFormalParameter => TargetInfo::FormalParameter {
+                    formal_parameter_index: tokens.number()?,
+                },
+                TargetType::Throws => TargetInfo::Throws {
+                    throws_type_index: tokens.number()?,
+                },
+                TargetType::LocalVariable | TargetType::ResourceVariable => {
+                    let count: u16 = tokens.number()?;
+                    let mut table = Vec::with_capacity(count as usize);
+                    for _ in 0..count {
+                        let start_pc = offset(&mut tokens, labels)?;
+                        let end = offset(&mut tokens, labels)?;
+                        let length = end
+                            .checked_sub(start_pc)
+                            .ok_or_else(|| tokens.error("The range ends before it starts"))?;
+                        table.push(LocalVarTarget {
+                            start_pc,
+                            length,
+                            index: tokens.number()?,
+                        });
+                    }
+                    TargetInfo::Localvar { table }
+                }
+                TargetType::ExceptionParameter => TargetInfo::Catch {
+                    exception_table_index: tokens.number()?,
+                },
+                TargetType::Instanceof
+                | TargetType::New
+                | TargetType::ConstructorReference
+                | TargetType::MethodReference => TargetInfo::Offset {
+                    offset: offset(&mut tokens, labels)?,
+                },
+                TargetType::Cast
+                | TargetType::ConstructorInvocationTypeArgument
+                | TargetType::MethodInvocationTypeArgument
+                | TargetType::ConstructorReferenceTypeArgument
+                | TargetType::MethodReferenceTypeArgument => TargetInfo::TypeArgument {
+                    offset: offset(&mut tokens, labels)?,
+                    type_argument_index: tokens.number()?,
+                },
+            };
+            let mut path = Vec::new();
+            if tokens.is_word("path") {
+                tokens.next()?;
+                // The last word is the type of the annotation
+                while tokens.remaining() > 1 {
+                    let word = tokens.word()?;
+                    let type_path_kind = variant(word)
+                        .ok_or_else(|| tokens.error(format!("Unknown path kind {}", word)))?;
+                    path.push(TypePathEntry {
+                        type_path_kind,
+                        type_argument_index: tokens.number()?,
+                    });
+                }
+            }
+            annotations.push(TypeAnnotation {
+                target_type,
+                target_info,
+                target_path: TypePath { path },
+                annotation: self.annotation(&mut tokens, "typeannotation")?,
+            });
+        }
+        Ok(annotations)
+    }
+
+    fn module(&mut self, tokens: &mut Tokens<'a>) -> Result<Module> {
+        let name_index = self.slot(tokens, "Utf8")?;
+        let flags = self.optional_flags(tokens);
+        let version_index = self.version(tokens)?;
+        tokens.finish()?;
+        let mut module = Module {
+            name_index,
+            flags,
+            version_index,
+            requires: Vec::new(),
+            exports: Vec::new(),
+            opens: Vec::new(),
+            uses: Vec::new(),
+            provides: Vec::new(),
+        };
+        while let Some(mut tokens) = self.block_line("module")? {
+            let word = tokens.word()?;
+            match word {
+                "requires" => module.requires.push(Requires {
+                    requires_index: self.slot(&mut tokens, "Utf8")?,
+                    flags: self.optional_flags(&mut tokens),
+                    version_index: self.version(&mut tokens)?,
+                }),
+                "exports" => module.exports.push(Exports {
+                    exports_index: self.slot(&mut tokens, "Utf8")?,
+                    flags: self.optional_flags(&mut tokens),
+                    to_index: self.targets(&mut tokens, "Module")?,
+                }),
+                "opens" => module.opens.push(Opens {
+                    opens_index: self.slot(&mut tokens, "Utf8")?,
+                    flags: self.optional_flags(&mut tokens),
+                    to_index: self.targets(&mut tokens, "Utf8")?,
+                }),
+                "uses" => module.uses.push(Uses {
+                    uses_index: self.slot(&mut tokens, "Class")?,
+                }),
+                "provides" => {
+                    let provides_index = self.slot(&mut tokens, "Class")?;
+                    tokens.keyword("with")?;
+                    module.provides.push(Provides {
+                        provides_index,
+                        with_index: self.classes(&mut tokens)?,
+                    });
+                }
+                _ => return Err(tokens.error(format!("Unknown module directive {}", word))),
+            }
+            tokens.finish()?;
+        }
+        Ok(module)
+    }
+
+    // The version of a module is optional
+    fn version(&mut self, tokens: &mut Tokens<'a>) -> Result<Index<Utf8>> {
+        if tokens.is_word("version") {
+            tokens.next()?;
+            self.slot(tokens, "Utf8")
+        } else {
+            Ok(Index::new(0))
+        }
+    }
+
+    /// The modules a package is exported or opened to
+    fn targets<T: TryFromItem>(
+        &mut self,
+        tokens: &mut Tokens<'a>,
+        kind: &str,
+    ) -> Result<Vec<Index<T>>> {
+        let mut targets = Vec::new();
+        if tokens.is_word("to") {
+            tokens.next()?;
+            while tokens.remaining() > 0 {
+                targets.push(self.slot(tokens, kind)?);
+            }
+        }
+        Ok(targets)
+    }
+}
+
+fn expect_directive(tokens: &mut Tokens, expected: &str) -> Result<()> {
+    if tokens.directive()? == expected {
+        Ok(())
+    } else {
+        Err(tokens.error(format!("Expected {}", expected)))
+    }
+}
+
+/// The offset of a label, or an offset written as a number
+fn resolve(word: &str, labels: Option<&Labels>) -> Option<u32> {
+    match labels.and_then(|labels| labels.get(word)) {
+        Some(&offset) => Some(offset),
+        None => word.parse().ok(),
+    }
+}
+
+fn offset(tokens: &mut Tokens, labels: Option<&Labels>) -> Result<u16> {
+    let word = tokens.word()?;
+    let offset =
+        resolve(word, labels).ok_or_else(|| tokens.error(format!("Unknown label {}", word)))?;
+    u16::try_from(offset).map_err(|_| tokens.error(format!("Offset {} is out of range", offset)))
+}
+
+/// `from start to end`, as the start and length
+fn range(tokens: &mut Tokens, labels: Option<&Labels>) -> Result<(u16, u16)> {
+    tokens.keyword("from")?;
+    let start = offset(tokens, labels)?;
+    tokens.keyword("to")?;
+    let end = offset(tokens, labels)?;
+    let length = end
+        .checked_sub(start)
+        .ok_or_else(|| tokens.error("The range ends before it starts"))?;
+    Ok((start, length))
+}
+
+fn wide(tokens: &mut Tokens) -> Result<Wide> {
+    let name = tokens.word()?;
+    let index = tokens.number()?;
+    Ok(match name {
+        "iload" => Wide::iload(index),
+        "lload" => Wide::lload(index),
+        "fload" => Wide::fload(index),
+        "dload" => Wide::dload(index),
+        "aload" => Wide::aload(index),
+        "istore" => Wide::istore(index),
+        "lstore" => Wide::lstore(index),
+        "fstore" => Wide::fstore(index),
+        "dstore" => Wide::dstore(index),
+        "astore" => Wide::astore(index),
+        "ret" => Wide::ret(index),
+        "iinc" => Wide::iinc(index, tokens.number()?),
+        _ => return Err(tokens.error(format!("{} cannot be widened", name))),
+    })
+}
+
+/// Sets the jump offsets of an instruction, in the order of `Instruction::branch_targets`
+fn set_targets(instruction: &mut Instruction, relative: &[i64]) -> std::result::Result<(), String> {
+    let short = |offset: i64| {
+        i16::try_from(offset).map_err(|_| format!("A jump of {} bytes needs a wide branch", offset))
+    };
+    let long = |offset: i64| {
+        i32::try_from(offset).map_err(|_| format!("A jump of {} bytes is out of range", offset))
+    };
+    match instruction {
+        Instruction::ifeq(target)
+        | Instruction::ifne(target)
+        | Instruction::iflt(target)
+        | Instruction::ifge(target)
+        | Instruction::ifgt(target)
+        | Instruction::ifle(target)
+        | Instruction::if_icmpeq(target)
+        | Instruction::if_icmpne(target)
+        | Instruction::if_icmplt(target)
+        | Instruction::if_icmpge(target)
+        | Instruction::if_icmpgt(target)
+        | Instruction::if_icmple(target)
+        | Instruction::if_acmpeq(target)
+        | Instruction::if_acmpne(target)
+        | Instruction::goto(target)
+        | Instruction::jsr(target)
+        | Instruction::ifnull(target)
+        | Instruction::ifnonnull(target) => *target = short(relative[0])?,
+        Instruction::goto_w(target) | Instruction::jsr_w(target) => *target = long(relative[0])?,
+        Instruction::tableswitch(switch) => {
+            switch.default = long(relative[0])?;
+            for (target, &offset) in switch.offsets.iter_mut().zip(&relative[1..]) {
+                *target = long(offset)?;
+            }
+        }
+        Instruction::lookupswitch(switch) => {
+            switch.default = long(relative[0])?;
+            for (pair, &offset) in switch.pairs.iter_mut().zip(&relative[1..]) {
+                pair.offset = long(offset)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
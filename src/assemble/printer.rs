@@ -0,0 +1,991 @@
+//! Writes classes in the assembly syntax
+//!
+//! The whole constant pool is written out as `.const` declarations, and everywhere else a
+//! constant is written by value when assembling that value finds the same slot again. This keeps
+//! the assembled class identical to the printed one, even when the pool holds duplicates.
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{
+    error::Result,
+    structs::{
+        attributes::{
+            Annotation, Attribute, AttributeInfo, Code, ElementValue, Module, StackMapFrame,
+            TargetInfo, TypeAnnotation, VerificationTypeInfo,
+        },
+        const_types::Utf8,
+        instructions::{Instruction, OffsetInstruction, Wide},
+        Class, ConstItem, ConstPoolBuilder,
+    },
+    writer::instructions::write_code,
+};
+
+use super::{array_type_name, flag_words, hex, lexer::quote, opcode_name, reference_kind_name};
+
+pub(super) fn print(class: &Class) -> Result<String> {
+    let mut printer = Printer {
+        class,
+        pool: ConstPoolBuilder::from_pool(class.constant_pool.clone()),
+        out: String::new(),
+        indent: 0,
+        labels: None,
+    };
+    printer.class()?;
+    Ok(printer.out)
+}
+
+const INDENT: &str = "    ";
+
+struct Printer<'a> {
+    class: &'a Class,
+    pool: ConstPoolBuilder,
+    out: String,
+    indent: usize,
+    // Set while printing the attributes of a `Code`, to write its offsets as labels
+    labels: Option<Labels>,
+}
+
+struct Labels {
+    // The offsets of the instructions and the end of the code
+    boundaries: HashSet<u32>,
+    // The offsets that have been written as a label
+    referenced: BTreeSet<u32>,
+}
+
+/// The keyword written before a constant of this kind
+fn tag(item: &ConstItem) -> &'static str {
+    match item {
+        ConstItem::Utf8(utf8) if text(utf8).is_none() => "Utf8Bytes",
+        ConstItem::Utf8(_) => "Utf8",
+        ConstItem::Integer(_) => "Int",
+        ConstItem::Float(_) => "Float",
+        ConstItem::Long(_) => "Long",
+        ConstItem::Double(_) => "Double",
+        ConstItem::Class(_) => "Class",
+        ConstItem::StringJ(_) => "String",
+        ConstItem::FieldRef(_) => "Field",
+        ConstItem::MethodRef(_) => "Method",
+        ConstItem::InterfaceMethodRef(_) => "InterfaceMethod",
+        ConstItem::NameAndType(_) => "NameAndType",
+        ConstItem::MethodHandle(_) => "MethodHandle",
+        ConstItem::MethodType(_) => "MethodType",
+        ConstItem::Dynamic(_) => "Dynamic",
+        ConstItem::InvokeDynamic(_) => "InvokeDynamic",
+        ConstItem::Module(_) => "Module",
+        ConstItem::Package(_) => "Package",
+    }
+}
+
+/// The string as a token, unless its bytes are not what encoding the string gives
+fn text(utf8: &Utf8) -> Option<String> {
+    if cesu8::to_java_cesu8(&utf8.str) == utf8.encoded() {
+        Some(quote(&utf8.str))
+    } else {
+        None
+    }
+}
+
+/// The raw bytes of strings that cannot be written as text
+fn utf8_bytes(utf8: &Utf8) -> String {
+    format!("\"{}\"", hex(&utf8.encoded()))
+}
+
+// NaN is written as its bits, as there are many of them
+fn float(value: f32) -> String {
+    if value.is_nan() {
+        format!("{:#010x}", value.to_bits())
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+fn double(value: f64) -> String {
+    if value.is_nan() {
+        format!("{:#018x}", value.to_bits())
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+fn verification_type_name(info: &VerificationTypeInfo) -> &'static str {
+    match info {
+        VerificationTypeInfo::Top => "Top",
+        VerificationTypeInfo::Integer => "Integer",
+        VerificationTypeInfo::Float => "Float",
+        VerificationTypeInfo::Double => "Double",
+        VerificationTypeInfo::Long => "Long",
+        VerificationTypeInfo::Null => "Null",
+        VerificationTypeInfo::UninitializedThis => "UninitializedThis",
+        VerificationTypeInfo::Object(_) => "Object",
+        VerificationTypeInfo::Uninitialized(_) => "Uninitialized",
+    }
+}
+
+impl Printer<'_> {
+    fn line(&mut self, text: impl AsRef<str>) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text.as_ref());
+        self.out.push('\n');
+    }
+
+    fn class(&mut self) -> Result<()> {
+        let class = self.class;
+        self.line(format!(
+            ".version {} {}",
+            class.version.major as u16, class.version.minor
+        ));
+        let mut words = vec![".class".to_string()];
+        words.extend(flag_words(&class.access_flags));
+        words.push(self.slot(class.this_class.index, "Class"));
+        self.line(words.join(" "));
+        self.line(format!(
+            ".super {}",
+            self.slot(class.super_class.index, "Class")
+        ));
+        for interface in &class.interfaces {
+            self.line(format!(
+                ".implements {}",
+                self.slot(interface.index.index, "Class")
+            ));
+        }
+        self.out.push('\n');
+
+        for (i, entry) in class.constant_pool.entries.iter().enumerate() {
+            if let Some(item) = entry {
+                let declaration =
+                    format!(".const [{}] = {} {}", i, tag(item), self.form(item, false));
+                self.line(declaration);
+            }
+        }
+        self.out.push('\n');
+
+        for field in &class.fields {
+            let mut words = vec![".field".to_string()];
+            words.extend(flag_words(&field.access_flags));
+            words.push(self.slot(field.name_index.index, "Utf8"));
+            words.push(self.slot(field.descriptor_index.index, "Utf8"));
+            self.line(words.join(" "));
+            self.indent += 1;
+            self.attributes(&field.attributes)?;
+            self.indent -= 1;
+            self.line(".end field");
+            self.out.push('\n');
+        }
+        for method in &class.methods {
+            let mut words = vec![".method".to_string()];
+            words.extend(flag_words(&method.access_flags));
+            words.push(self.slot(method.name_index.index, "Utf8"));
+            words.push(self.slot(method.descriptor_index.index, "Utf8"));
+            self.line(words.join(" "));
+            self.indent += 1;
+            self.attributes(&method.attributes)?;
+            self.indent -= 1;
+            self.line(".end method");
+            self.out.push('\n');
+        }
+        self.attributes(&class.attributes)?;
+        self.line(".end class");
+        Ok(())
+    }
+
+    /// Whether the constant is written by value: assembling the value must find this slot
+    fn canonical(&self, index: u16) -> bool {
+        let item = match self.pool.entry(index) {
+            Some(item) => item,
+            None => return false,
+        };
+        if self.pool.find(item) != Some(index) {
+            return false;
+        }
+        match item {
+            ConstItem::Utf8(_)
+            | ConstItem::Integer(_)
+            | ConstItem::Float(_)
+            | ConstItem::Long(_)
+            | ConstItem::Double(_) => true,
+            ConstItem::Class(class) => self.is(class.name_index.index, "Utf8"),
+            ConstItem::StringJ(string) => self.is(string.string_index.index, "Utf8"),
+            ConstItem::FieldRef(member) => {
+                self.is(member.class_index.index, "Class")
+                    && self.is(member.name_and_type_index.index, "NameAndType")
+            }
+            ConstItem::MethodRef(member) => {
+                self.is(member.class_index.index, "Class")
+                    && self.is(member.name_and_type_index.index, "NameAndType")
+            }
+            ConstItem::InterfaceMethodRef(member) => {
+                self.is(member.class_index.index, "Class")
+                    && self.is(member.name_and_type_index.index, "NameAndType")
+            }
+            ConstItem::NameAndType(name_and_type) => {
+                self.is(name_and_type.name_index.index, "Utf8")
+                    && self.is(name_and_type.descriptor_index.index, "Utf8")
+            }
+            ConstItem::MethodHandle(handle) => ["Field", "Method", "InterfaceMethod"]
+                .iter()
+                .any(|kind| self.is(handle.reference_index.index(), kind)),
+            ConstItem::MethodType(method_type) => {
+                self.is(method_type.descriptor_index.index, "Utf8")
+            }
+            ConstItem::Dynamic(dynamic) => {
+                self.is(dynamic.name_and_type_index.index, "NameAndType")
+            }
+            ConstItem::InvokeDynamic(dynamic) => {
+                self.is(dynamic.name_and_type_index.index, "NameAndType")
+            }
+            ConstItem::Module(module) => self.is(module.name_index.index, "Utf8"),
+            ConstItem::Package(package) => self.is(package.name_index.index, "Utf8"),
+        }
+    }
+
+    fn is(&self, index: u16, kind: &str) -> bool {
+        self.pool.entry(index).map(tag) == Some(kind) && self.canonical(index)
+    }
+
+    /// A constant whose kind is implied, like the class of `new`
+    fn slot(&self, index: u16, kind: &str) -> String {
+        match self.pool.entry(index) {
+            Some(item) if self.is(index, kind) => self.form(item, true),
+            _ => format!("[{}]", index),
+        }
+    }
+
+    /// A constant preceded by its kind, like the operand of `ldc`
+    fn constant(&self, index: u16) -> String {
+        match self.pool.entry(index) {
+            Some(item) if self.canonical(index) => {
+                format!("{} {}", tag(item), self.form(item, true))
+            }
+            _ => format!("[{}]", index),
+        }
+    }
+
+    /// The value of a constant, where the constants it refers to are written by value if `inline`
+    /// and as slots otherwise
+    fn form(&self, item: &ConstItem, inline: bool) -> String {
+        let child = |index: u16, kind: &str| {
+            if inline {
+                self.slot(index, kind)
+            } else {
+                format!("[{}]", index)
+            }
+        };
+        match item {
+            ConstItem::Utf8(utf8) => text(utf8).unwrap_or_else(|| utf8_bytes(utf8)),
+            ConstItem::Integer(integer) => integer.value.to_string(),
+            ConstItem::Float(value) => float(value.value),
+            ConstItem::Long(long) => long.value.to_string(),
+            ConstItem::Double(value) => double(value.value),
+            ConstItem::Class(class) => child(class.name_index.index, "Utf8"),
+            ConstItem::StringJ(string) => child(string.string_index.index, "Utf8"),
+            ConstItem::FieldRef(member) => format!(
+                "{} {}",
+                child(member.class_index.index, "Class"),
+                child(member.name_and_type_index.index, "NameAndType")
+            ),
+            ConstItem::MethodRef(member) => format!(
+                "{} {}",
+                child(member.class_index.index, "Class"),
+                child(member.name_and_type_index.index, "NameAndType")
+            ),
+            ConstItem::InterfaceMethodRef(member) => format!(
+                "{} {}",
+                child(member.class_index.index, "Class"),
+                child(member.name_and_type_index.index, "NameAndType")
+            ),
+            ConstItem::NameAndType(name_and_type) => format!(
+                "{} {}",
+                child(name_and_type.name_index.index, "Utf8"),
+                child(name_and_type.descriptor_index.index, "Utf8")
+            ),
+            ConstItem::MethodHandle(handle) => {
+                let reference = if inline {
+                    self.constant(handle.reference_index.index())
+                } else {
+                    format!("[{}]", handle.reference_index.index())
+                };
+                format!(
+                    "{} {}",
+                    reference_kind_name(&handle.reference_kind),
+                    reference
+                )
+            }
+            ConstItem::MethodType(method_type) => child(method_type.descriptor_index.index, "Utf8"),
+            ConstItem::Dynamic(dynamic) => format!(
+                "{} {}",
+                dynamic.bootstrap_method_attr_index,
+                child(dynamic.name_and_type_index.index, "NameAndType")
+            ),
+            ConstItem::InvokeDynamic(dynamic) => format!(
+                "{} {}",
+                dynamic.bootstrap_method_attr_index,
+                child(dynamic.name_and_type_index.index, "NameAndType")
+            ),
+            ConstItem::Module(module) => child(module.name_index.index, "Utf8"),
+            ConstItem::Package(package) => child(package.name_index.index, "Utf8"),
+        }
+    }
+
+    fn classes(&self, indices: impl Iterator<Item = u16>) -> String {
+        indices
+            .map(|index| self.slot(index, "Class"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// An offset in the code, as a label when it is the start of an instruction or the end
+    fn label(&mut self, offset: u32) -> String {
+        match &mut self.labels {
+            Some(labels) if labels.boundaries.contains(&offset) => {
+                labels.referenced.insert(offset);
+                format!("L{}", offset)
+            }
+            _ => offset.to_string(),
+        }
+    }
+
+    fn attributes(&mut self, attributes: &[Attribute]) -> Result<()> {
+        for attribute in attributes {
+            self.attribute(attribute)?;
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self, attribute: &Attribute) -> Result<()> {
+        let name_index = attribute.attribute_name_index.index;
+        let name = attribute.info.name();
+        let directive = if let AttributeInfo::Unknown(unknown) = &attribute.info {
+            let name = self.slot(name_index, "Utf8");
+            self.line(format!(".attribute {} \"{}\"", name, hex(&unknown.bytes)));
+            return Ok(());
+        } else if self.slot(name_index, "Utf8") == name {
+            format!(".{}", name.to_lowercase())
+        } else {
+            // The name is interned when assembling, which would not find this slot
+            format!(".attribute [{}] .{}", name_index, name.to_lowercase())
+        };
+
+        match &attribute.info {
+            AttributeInfo::ConstantValue(constant) => {
+                let value = self.constant(constant.constant_value_index.index);
+                self.line(format!("{} {}", directive, value));
+            }
+            AttributeInfo::Code(code) => self.code(&directive, code)?,
+            AttributeInfo::StackMapTable(table) => {
+                self.line(directive);
+                self.indent += 1;
+                let mut offset: Option<u32> = None;
+                for frame in &table.entries {
+                    let delta = frame.offset_delta() as u32;
+                    let next = offset.map_or(delta, |previous| previous + delta + 1);
+                    offset = Some(next);
+                    let text = self.stack_map_frame(frame, next);
+                    self.line(text);
+                }
+                self.indent -= 1;
+                self.line(".end stackmaptable");
+            }
+            AttributeInfo::Exceptions(exceptions) => {
+                let classes = self.classes(
+                    exceptions
+                        .exception_index_table
+                        .iter()
+                        .map(|index| index.index),
+                );
+                self.line(format!("{} {}", directive, classes));
+            }
+            AttributeInfo::InnerClasses(inner_classes) => {
+                self.line(directive);
+                self.indent += 1;
+                for class in &inner_classes.classes {
+                    let mut words = vec![
+                        self.slot(class.inner_class_info_index.index, "Class"),
+                        self.slot(class.outer_class_info_index.index, "Class"),
+                        self.slot(class.inner_name_index.index, "Utf8"),
+                    ];
+                    words.extend(flag_words(&class.inner_class_access_flags));
+                    self.line(words.join(" "));
+                }
+                self.indent -= 1;
+                self.line(".end innerclasses");
+            }
+            AttributeInfo::EnclosingMethod(enclosing) => {
+                let text = format!(
+                    "{} {} {}",
+                    directive,
+                    self.slot(enclosing.class_index.index, "Class"),
+                    self.slot(enclosing.method_index.index, "NameAndType")
+                );
+                self.line(text);
+            }
+            AttributeInfo::Synthetic(_) | AttributeInfo::Deprecated(_) => self.line(directive),
+            AttributeInfo::Signature(signature) => {
+                let text = self.slot(signature.signature_index.index, "Utf8");
+                self.line(format!("{} {}", directive, text));
+            }
+            AttributeInfo::SourceFile(source_file) => {
+                let text = self.slot(source_file.sourcefile_index.index, "Utf8");
+                self.line(format!("{} {}", directive, text));
+            }
+            AttributeInfo::SourceDebugExtension(extension) => {
+                let extension = &extension.debug_extension;
+                let text = text(extension)
+                    .unwrap_or_else(|| format!("Utf8Bytes {}", utf8_bytes(extension)));
+                self.line(format!("{} {}", directive, text));
+            }
+            AttributeInfo::LineNumberTable(table) => {
+                self.line(directive);
+                self.indent += 1;
+                for line in &table.line_number_table {
+                    let start = self.label(line.start_pc as u32);
+                    self.line(format!("{} {}", start, line.line_number));
+                }
+                self.indent -= 1;
+                self.line(".end linenumbertable");
+            }
+            AttributeInfo::LocalVariableTable(table) => {
+                self.line(directive);
+                self.indent += 1;
+                for variable in &table.local_variable_table {
+                    let text = self.local_variable(
+                        variable.index,
+                        variable.name_index.index,
+                        variable.descriptor_index.index,
+                        variable.start_pc,
+                        variable.length,
+                    );
+                    self.line(text);
+                }
+                self.indent -= 1;
+                self.line(".end localvariabletable");
+            }
+            AttributeInfo::LocalVariableTypeTable(table) => {
+                self.line(directive);
+                self.indent += 1;
+                for variable in &table.local_variable_type_table {
+                    let text = self.local_variable(
+                        variable.index,
+                        variable.name_index.index,
+                        variable.signature_index.index,
+                        variable.start_pc,
+                        variable.length,
+                    );
+                    self.line(text);
+                }
+                self.indent -= 1;
+                self.line(".end localvariabletypetable");
+            }
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) => {
+                self.annotations(&directive, name, &annotations.annotations)
+            }
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => {
+                self.annotations(&directive, name, &annotations.annotations)
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) => {
+                self.line(directive);
+                self.indent += 1;
+                for parameter in &parameters.parameter_annotations {
+                    self.annotations(".parameter", "parameter", &parameter.annotations);
+                }
+                self.indent -= 1;
+                self.line(format!(".end {}", name.to_lowercase()));
+            }
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(parameters) => {
+                self.line(directive);
+                self.indent += 1;
+                for parameter in &parameters.parameter_annotations {
+                    self.annotations(".parameter", "parameter", &parameter.annotations);
+                }
+                self.indent -= 1;
+                self.line(format!(".end {}", name.to_lowercase()));
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) => {
+                self.type_annotations(&directive, name, &annotations.annotations)
+            }
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => {
+                self.type_annotations(&directive, name, &annotations.annotations)
+            }
+            AttributeInfo::AnnotationDefault(default) => {
+                self.element_value(format!("{} ", directive), &default.default_value)
+            }
+            AttributeInfo::BootstrapMethods(methods) => {
+                self.line(directive);
+                self.indent += 1;
+                for method in &methods.bootstrap_methods {
+                    let mut words =
+                        vec![self.slot(method.bootstrap_method_ref.index, "MethodHandle")];
+                    for argument in &method.bootstrap_arguments {
+                        words.push(self.constant(argument.index));
+                    }
+                    self.line(words.join(" "));
+                }
+                self.indent -= 1;
+                self.line(".end bootstrapmethods");
+            }
+            AttributeInfo::MethodParameters(parameters) => {
+                self.line(directive);
+                self.indent += 1;
+                for parameter in &parameters.parameters {
+                    let mut words = vec![self.slot(parameter.name_index.index, "Utf8")];
+                    words.extend(flag_words(&parameter.access_flags));
+                    self.line(words.join(" "));
+                }
+                self.indent -= 1;
+                self.line(".end methodparameters");
+            }
+            AttributeInfo::Module(module) => self.module(&directive, module),
+            AttributeInfo::ModulePackages(packages) => {
+                let packages = packages
+                    .package_index
+                    .iter()
+                    .map(|index| self.slot(index.index, "Package"))
+                    .collect::<Vec<_>>();
+                self.line(format!("{} {}", directive, packages.join(" ")));
+            }
+            AttributeInfo::ModuleMainClass(main_class) => {
+                let class = self.slot(main_class.main_class_index.index, "Class");
+                self.line(format!("{} {}", directive, class));
+            }
+            AttributeInfo::NestHost(host) => {
+                let class = self.slot(host.host_class_index.index, "Class");
+                self.line(format!("{} {}", directive, class));
+            }
+            AttributeInfo::NestMembers(members) => {
+                let classes = self.classes(members.classes.iter().map(|index| index.index));
+                self.line(format!("{} {}", directive, classes));
+            }
+            AttributeInfo::Record(record) => {
+                self.line(directive);
+                self.indent += 1;
+                for component in &record.components {
+                    let text = format!(
+                        ".component {} {}",
+                        self.slot(component.name_index.index, "Utf8"),
+                        self.slot(component.descriptor_index.index, "Utf8")
+                    );
+                    self.line(text);
+                    self.indent += 1;
+                    self.attributes(&component.attributes)?;
+                    self.indent -= 1;
+                    self.line(".end component");
+                }
+                self.indent -= 1;
+                self.line(".end record");
+            }
+            AttributeInfo::PermittedSubclasses(subclasses) => {
+                let classes = self.classes(subclasses.classes.iter().map(|index| index.index));
+                self.line(format!("{} {}", directive, classes));
+            }
+            AttributeInfo::Unknown(_) => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn code(&mut self, directive: &str, code: &Code) -> Result<()> {
+        self.line(format!(
+            "{} stack {} locals {}",
+            directive, code.max_stack, code.max_locals
+        ));
+        let length = write_code(&code.code)?.len() as u32;
+        let mut boundaries: HashSet<u32> = code.code.iter().map(|i| i.offset).collect();
+        boundaries.insert(length);
+        let outer = self.labels.replace(Labels {
+            boundaries,
+            referenced: BTreeSet::new(),
+        });
+
+        let mut instructions = Vec::with_capacity(code.code.len());
+        for instruction in &code.code {
+            instructions.push((instruction.offset, self.instruction(instruction)));
+        }
+        // The exception table and attributes come after the instructions, but the labels they
+        // refer to are needed first
+        let out = std::mem::take(&mut self.out);
+        self.indent += 1;
+        for exception in &code.exception_table {
+            let catch_type = match self.slot(exception.catch_type.index, "Class") {
+                _ if exception.catch_type.index == 0 => "any".to_string(),
+                name if name == "any" => "\"any\"".to_string(),
+                name => name,
+            };
+            let text = format!(
+                ".catch {} from {} to {} using {}",
+                catch_type,
+                self.label(exception.start_pc as u32),
+                self.label(exception.end_pc as u32),
+                self.label(exception.handler_pc as u32)
+            );
+            self.line(text);
+        }
+        self.attributes(&code.attributes)?;
+        self.indent -= 1;
+        let rest = std::mem::replace(&mut self.out, out);
+        let labels = std::mem::replace(&mut self.labels, outer).unwrap_or_else(|| Labels {
+            boundaries: HashSet::new(),
+            referenced: BTreeSet::new(),
+        });
+
+        let define = |offset: u32| {
+            if labels.referenced.contains(&offset) {
+                format!("L{}:", offset)
+            } else {
+                String::new()
+            }
+        };
+        for (offset, lines) in instructions {
+            let mut lines = lines.into_iter();
+            if let Some(first) = lines.next() {
+                self.line(format!("{:<7} {}", define(offset), first));
+            }
+            for line in lines {
+                self.line(format!("{:<7} {}{}", "", INDENT, line));
+            }
+        }
+        if labels.referenced.contains(&length) {
+            self.line(define(length));
+        }
+        self.out.push_str(&rest);
+        self.line(".end code");
+        Ok(())
+    }
+
+    /// The lines of an instruction, more than one for switches
+    fn instruction(&mut self, instruction: &OffsetInstruction) -> Vec<String> {
+        let name = opcode_name(instruction.instruction.opcode());
+        let targets = instruction.instruction.branch_targets(instruction.offset);
+        let operands = match &instruction.instruction {
+            Instruction::bipush(value) => value.to_string(),
+            Instruction::sipush(value) => value.to_string(),
+            Instruction::ldc(index)
+            | Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => self.constant(index.index),
+            Instruction::iload(slot)
+            | Instruction::lload(slot)
+            | Instruction::fload(slot)
+            | Instruction::dload(slot)
+            | Instruction::aload(slot)
+            | Instruction::istore(slot)
+            | Instruction::lstore(slot)
+            | Instruction::fstore(slot)
+            | Instruction::dstore(slot)
+            | Instruction::astore(slot)
+            | Instruction::ret(slot) => slot.to_string(),
+            Instruction::iinc(slot, value) => format!("{} {}", slot, value),
+            Instruction::tableswitch(switch) => {
+                let mut lines = vec![format!("{} {} {}", name, switch.low, switch.high)];
+                for &target in &targets[1..] {
+                    lines.push(self.label(target));
+                }
+                lines.push(format!("default: {}", self.label(targets[0])));
+                return lines;
+            }
+            Instruction::lookupswitch(switch) => {
+                let mut lines = vec![name];
+                for (pair, &target) in switch.pairs.iter().zip(&targets[1..]) {
+                    lines.push(format!("{}: {}", pair.key, self.label(target)));
+                }
+                lines.push(format!("default: {}", self.label(targets[0])));
+                return lines;
+            }
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => self.slot(index.index, "Field"),
+            Instruction::invokevirtual(index) => self.slot(index.index, "Method"),
+            Instruction::invokeinterface(index, count) => {
+                format!("{} {}", self.slot(index.index, "InterfaceMethod"), count)
+            }
+            Instruction::invokedynamic(index) => self.slot(index.index, "InvokeDynamic"),
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index) => self.slot(index.index, "Class"),
+            Instruction::newarray(array_type) => array_type_name(*array_type),
+            Instruction::wide(wide) => match wide {
+                Wide::iinc(slot, value) => format!("iinc {} {}", slot, value),
+                _ => format!("{} {}", opcode_name(wide.opcode()), wide.index()),
+            },
+            Instruction::multianewarray(index, dimensions) => {
+                format!("{} {}", self.slot(index.index, "Class"), dimensions)
+            }
+            // The remaining instructions with operands are branches
+            _ if !targets.is_empty() => self.label(targets[0]),
+            _ => String::new(),
+        };
+        if operands.is_empty() {
+            vec![name]
+        } else {
+            vec![format!("{} {}", name, operands)]
+        }
+    }
+
+    fn stack_map_frame(&mut self, frame: &StackMapFrame, offset: u32) -> String {
+        let at = self.label(offset);
+        match frame {
+            StackMapFrame::Same { .. } => format!("same {}", at),
+            StackMapFrame::SameLocals1StackItem { stack, .. } => {
+                format!(
+                    "same_locals_1_stack_item {} {}",
+                    at,
+                    self.verification_type(stack)
+                )
+            }
+            StackMapFrame::SameLocals1StackItemExtended { stack, .. } => format!(
+                "same_locals_1_stack_item_extended {} {}",
+                at,
+                self.verification_type(stack)
+            ),
+            StackMapFrame::Chop { k, .. } => format!("chop {} {}", at, k),
+            StackMapFrame::SameExtended { .. } => format!("same_extended {}", at),
+            StackMapFrame::Append { locals, .. } => {
+                format!("append {} {}", at, self.verification_types(locals))
+            }
+            StackMapFrame::Full { locals, stack, .. } => {
+                let locals = self.verification_types(locals);
+                let stack = self.verification_types(stack);
+                format!("full {} locals {} stack {}", at, locals, stack)
+                    .trim_end()
+                    .to_string()
+            }
+        }
+    }
+
+    fn verification_types(&mut self, types: &[VerificationTypeInfo]) -> String {
+        types
+            .iter()
+            .map(|info| self.verification_type(info))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn verification_type(&mut self, info: &VerificationTypeInfo) -> String {
+        let name = verification_type_name(info);
+        match info {
+            VerificationTypeInfo::Object(class) => {
+                format!("{} {}", name, self.slot(class.index, "Class"))
+            }
+            VerificationTypeInfo::Uninitialized(offset) => {
+                format!("{} {}", name, self.label(*offset as u32))
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    fn local_variable(
+        &mut self,
+        index: u16,
+        name: u16,
+        descriptor: u16,
+        start_pc: u16,
+        length: u16,
+    ) -> String {
+        format!(
+            "{} {} {} from {} to {}",
+            index,
+            self.slot(name, "Utf8"),
+            self.slot(descriptor, "Utf8"),
+            self.label(start_pc as u32),
+            self.label(start_pc as u32 + length as u32)
+        )
+    }
+
+    /// A block of annotations ending with `.end block`
+    fn annotations(&mut self, directive: &str, block: &str, annotations: &[Annotation]) {
+        self.line(directive);
+        self.indent += 1;
+        for annotation in annotations {
+            self.annotation(".annotation".to_string(), annotation, ".end annotation");
+        }
+        self.indent -= 1;
+        self.line(format!(".end {}", block.to_lowercase()));
+    }
+
+    fn annotation(&mut self, header: String, annotation: &Annotation, end: &str) {
+        let type_name = self.slot(annotation.type_index.index, "Utf8");
+        self.line(format!("{} {}", header, type_name));
+        self.indent += 1;
+        for pair in &annotation.element_value_pairs {
+            let name = self.slot(pair.element_name_index.index, "Utf8");
+            self.element_value(format!("{} = ", name), &pair.value);
+        }
+        self.indent -= 1;
+        self.line(end);
+    }
+
+    fn element_value(&mut self, prefix: String, value: &ElementValue) {
+        let text = match value {
+            ElementValue::Byte(index) => format!("byte {}", self.slot(index.index, "Int")),
+            ElementValue::Char(index) => format!("char {}", self.slot(index.index, "Int")),
+            ElementValue::Double(index) => format!("double {}", self.slot(index.index, "Double")),
+            ElementValue::Float(index) => format!("float {}", self.slot(index.index, "Float")),
+            ElementValue::Int(index) => format!("int {}", self.slot(index.index, "Int")),
+            ElementValue::Long(index) => format!("long {}", self.slot(index.index, "Long")),
+            ElementValue::Short(index) => format!("short {}", self.slot(index.index, "Int")),
+            ElementValue::Boolean(index) => format!("boolean {}", self.slot(index.index, "Int")),
+            ElementValue::String(index) => format!("string {}", self.slot(index.index, "Utf8")),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "enum {} {}",
+                self.slot(type_name_index.index, "Utf8"),
+                self.slot(const_name_index.index, "Utf8")
+            ),
+            ElementValue::Class(index) => format!("class {}", self.slot(index.index, "Utf8")),
+            ElementValue::Annotation(annotation) => {
+                let header = format!("{}annotation", prefix);
+                return self.annotation(header, annotation, ".end annotation");
+            }
+            ElementValue::Array(values) => {
+                self.line(format!("{}array", prefix));
+                self.indent += 1;
+                for value in values {
+                    self.element_value(String::new(), value);
+                }
+                self.indent -= 1;
+                return self.line(".end array");
+            }
+        };
+        self.line(format!("{}{}", prefix, text));
+    }
+
+    fn type_annotations(&mut self, directive: &str, block: &str, annotations: &[TypeAnnotation]) {
+        self.line(directive);
+        self.indent += 1;
+        for annotation in annotations {
+            let mut words = vec![
+                ".typeannotation".to_string(),
+                format!("{:?}", annotation.target_type),
+            ];
+            match &annotation.target_info {
+                TargetInfo::TypeParameter {
+                    type_parameter_index,
+                } => words.push(type_parameter_index.to_string()),
+                TargetInfo::Supertype { supertype_index } => {
+                    words.push(supertype_index.to_string())
+                }
+                TargetInfo::TypeParameterBound {
+                    type_parameter_index,
+                    bound_index,
+                } => {
+                    words.push(type_parameter_index.to_string());
+                    words.push(bound_index.to_string());
+                }
+                TargetInfo::Empty => {}
+                TargetInfo::FormalParameter {
+                    formal_parameter_index,
+                } => words.push(formal_parameter_index.to_string()),
+                TargetInfo::Throws { throws_type_index } => {
+                    words.push(throws_type_index.to_string())
+                }
+                TargetInfo::Localvar { table } => {
+                    words.push(table.len().to_string());
+                    for variable in table {
+                        words.push(self.label(variable.start_pc as u32));
+                        words.push(self.label(variable.start_pc as u32 + variable.length as u32));
+                        words.push(variable.index.to_string());
+                    }
+                }
+                TargetInfo::Catch {
+                    exception_table_index,
+                } => words.push(exception_table_index.to_string()),
+                TargetInfo::Offset { offset } => words.push(self.label(*offset as u32)),
+                TargetInfo::TypeArgument {
+                    offset,
+                    type_argument_index,
+                } => {
+                    words.push(self.label(*offset as u32));
+                    words.push(type_argument_index.to_string());
+                }
+            }
+            if !annotation.target_path.path.is_empty() {
+                words.push("path".to_string());
+                for entry in &annotation.target_path.path {
+                    words.push(format!("{:?}", entry.type_path_kind));
+                    words.push(entry.type_argument_index.to_string());
+                }
+            }
+            self.annotation(
+                words.join(" "),
+                &annotation.annotation,
+                ".end typeannotation",
+            );
+        }
+        self.indent -= 1;
+        self.line(format!(".end {}", block.to_lowercase()));
+    }
+
+    fn module(&mut self, directive: &str, module: &Module) {
+        let mut words = vec![
+            directive.to_string(),
+            self.slot(module.name_index.index, "Utf8"),
+        ];
+        words.extend(flag_words(&module.flags));
+        self.version(&mut words, module.version_index.index);
+        self.line(words.join(" "));
+        self.indent += 1;
+        for requires in &module.requires {
+            let mut words = vec![
+                "requires".to_string(),
+                self.slot(requires.requires_index.index, "Utf8"),
+            ];
+            words.extend(flag_words(&requires.flags));
+            self.version(&mut words, requires.version_index.index);
+            self.line(words.join(" "));
+        }
+        for exports in &module.exports {
+            let mut words = vec![
+                "exports".to_string(),
+                self.slot(exports.exports_index.index, "Utf8"),
+            ];
+            words.extend(flag_words(&exports.flags));
+            if !exports.to_index.is_empty() {
+                words.push("to".to_string());
+                for index in &exports.to_index {
+                    words.push(self.slot(index.index, "Module"));
+                }
+            }
+            self.line(words.join(" "));
+        }
+        for opens in &module.opens {
+            let mut words = vec![
+                "opens".to_string(),
+                self.slot(opens.opens_index.index, "Utf8"),
+            ];
+            words.extend(flag_words(&opens.flags));
+            if !opens.to_index.is_empty() {
+                words.push("to".to_string());
+                for index in &opens.to_index {
+                    words.push(self.slot(index.index, "Utf8"));
+                }
+            }
+            self.line(words.join(" "));
+        }
+        for uses in &module.uses {
+            let class = self.slot(uses.uses_index.index, "Class");
+            self.line(format!("uses {}", class));
+        }
+        for provides in &module.provides {
+            let text = format!(
+                "provides {} with {}",
+                self.slot(provides.provides_index.index, "Class"),
+                self.classes(provides.with_index.iter().map(|index| index.index))
+            );
+            self.line(text);
+        }
+        self.indent -= 1;
+        self.line(".end module");
+    }
+
+    // The version of a module is optional
+    fn version(&self, words: &mut Vec<String>, index: u16) {
+        if index != 0 {
+            words.push("version".to_string());
+            words.push(self.slot(index, "Utf8"));
+        }
+    }
+}
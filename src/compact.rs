@@ -0,0 +1,483 @@
+//! Drops the constant pool entries a class no longer refers to, which linger after editing it.
+//!
+//! An entry is kept when it is reachable from the class, its fields, methods, attributes or
+//! instructions, or from another entry that is kept. Entries stay in the same order, so indices
+//! only get smaller and an `ldc` keeps fitting in its single byte. Bootstrap methods that no
+//! `Dynamic` or `InvokeDynamic` entry refers to are dropped along with their arguments, and the
+//! `BootstrapMethods` attribute with them once none are left.
+
+use crate::{
+    error::{ParseError, Result},
+    structs::{
+        attributes::{
+            Annotation, Attribute, AttributeInfo, BootstrapMethod, ElementValue, StackMapFrame,
+            VerificationTypeInfo,
+        },
+        const_pool::TryFromItem,
+        const_types::MethodHandleReference,
+        Class, ConstItem, ConstPool, Index, Instruction,
+    },
+};
+
+/// Removes the unused entries of the constant pool and renumbers every index into it. Returns
+/// the number of slots that were freed.
+///
+/// Fails for classes with attributes that were not parsed, as the indices in their bytes cannot
+/// be rewritten.
+pub fn compact(class: &mut Class) -> Result<usize> {
+    if let Some(name) = unknown_attribute(class) {
+        return Err(ParseError::Other(format!(
+            "Cannot compact the constant pool, the {} attribute is not understood",
+            name
+        )));
+    }
+    let count = class.constant_pool.entries.len();
+    let mut bootstrap_methods = class.bootstrap_methods().to_vec();
+
+    // Mark everything the class refers to, then everything the marked entries refer to
+    let mut used = vec![false; count];
+    let mut used_bootstrap_methods = vec![false; bootstrap_methods.len()];
+    let mut pending = Vec::new();
+    class.visit_indices(&mut |index| pending.push(*index));
+    while let Some(index) = pending.pop() {
+        let slot = used
+            .get_mut(index as usize)
+            .ok_or(ParseError::ConstantIndexOutOfRange { index, count })?;
+        if index == 0 || *slot {
+            continue;
+        }
+        *slot = true;
+        let mut item = class.constant_pool.entries[index as usize]
+            .clone()
+            .ok_or(ParseError::UnusableConstantIndex(index))?;
+        item.visit_indices(&mut |index| pending.push(*index));
+        if let Some(bootstrap) = bootstrap_method_attr_index(&mut item) {
+            let count = bootstrap_methods.len();
+            let method = bootstrap_methods.get_mut(*bootstrap as usize).ok_or(
+                ParseError::BootstrapMethodOutOfRange {
+                    index: *bootstrap,
+                    count,
+                },
+            )?;
+            used_bootstrap_methods[*bootstrap as usize] = true;
+            method.visit_indices(&mut |index| pending.push(*index));
+        }
+    }
+    let bootstrap_name = class
+        .attributes
+        .iter()
+        .find(|attribute| matches!(attribute.info, AttributeInfo::BootstrapMethods(_)))
+        .map(|attribute| attribute.attribute_name_index.index);
+    match bootstrap_name {
+        Some(name) if used_bootstrap_methods.contains(&true) => {
+            *used
+                .get_mut(name as usize)
+                .ok_or(ParseError::ConstantIndexOutOfRange { index: name, count })? = true;
+        }
+        // Nothing fails past this point, so the class is never left half rewritten
+        Some(_) => class
+            .attributes
+            .retain(|attribute| !matches!(attribute.info, AttributeInfo::BootstrapMethods(_))),
+        None => {}
+    }
+
+    // Number the entries that are kept, in their original order
+    let mut indices = vec![0u16; count];
+    let mut entries = vec![None];
+    for (index, entry) in class.constant_pool.entries.iter_mut().enumerate() {
+        // Marking checked that every used index holds an entry
+        let item = match entry.take() {
+            Some(item) if used[index] => item,
+            _ => continue,
+        };
+        indices[index] = entries.len() as u16;
+        let wide = matches!(item, ConstItem::Long(_) | ConstItem::Double(_));
+        entries.push(Some(item));
+        if wide {
+            entries.push(None);
+        }
+    }
+    let mut bootstrap_indices = vec![0u16; bootstrap_methods.len()];
+    let mut kept = 0;
+    for (index, used) in used_bootstrap_methods.iter().enumerate() {
+        if *used {
+            bootstrap_indices[index] = kept;
+            kept += 1;
+        }
+    }
+    let mut used = used_bootstrap_methods.into_iter();
+    bootstrap_methods.retain(|_| used.next() == Some(true));
+
+    // Rewrite every index to the new numbering
+    let renumber = &mut |index: &mut u16| *index = indices[*index as usize];
+    class.visit_indices(renumber);
+    for method in &mut bootstrap_methods {
+        method.visit_indices(renumber);
+    }
+    for item in entries.iter_mut().flatten() {
+        item.visit_indices(renumber);
+        if let Some(bootstrap) = bootstrap_method_attr_index(item) {
+            *bootstrap = bootstrap_indices[*bootstrap as usize];
+        }
+    }
+    for attribute in &mut class.attributes {
+        if let AttributeInfo::BootstrapMethods(attribute_info) = &mut attribute.info {
+            renumber(&mut attribute.attribute_name_index.index);
+            attribute_info.bootstrap_methods = std::mem::take(&mut bootstrap_methods);
+        }
+    }
+    let freed = count - entries.len();
+    class.constant_pool = ConstPool { entries };
+    Ok(freed)
+}
+
+/// The name of the first attribute that was not parsed, anywhere in the class
+fn unknown_attribute(class: &Class) -> Option<String> {
+    fn find(attributes: &[Attribute], class: &Class) -> Option<String> {
+        attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::Unknown(_) => Some(
+                    attribute
+                        .attribute_name_index
+                        .get(&class.constant_pool)
+                        .map_or_else(|_| "unnamed".to_string(), |name| name.str.clone()),
+                ),
+                AttributeInfo::Code(code) => find(&code.attributes, class),
+                AttributeInfo::Record(record) => record
+                    .components
+                    .iter()
+                    .find_map(|component| find(&component.attributes, class)),
+                _ => None,
+            })
+    }
+
+    find(&class.attributes, class)
+        .or_else(|| {
+            class
+                .fields
+                .iter()
+                .find_map(|field| find(&field.attributes, class))
+        })
+        .or_else(|| {
+            class
+                .methods
+                .iter()
+                .find_map(|method| find(&method.attributes, class))
+        })
+}
+
+fn bootstrap_method_attr_index(item: &mut ConstItem) -> Option<&mut u16> {
+    match item {
+        ConstItem::Dynamic(dynamic) => Some(&mut dynamic.bootstrap_method_attr_index),
+        ConstItem::InvokeDynamic(dynamic) => Some(&mut dynamic.bootstrap_method_attr_index),
+        _ => None,
+    }
+}
+
+/// Calls `visit` with every constant pool index held by a structure. Index 0 is visited too where
+/// it means there is no entry, so it has to be kept as 0.
+///
+/// The `BootstrapMethods` attribute is left out, including its name, and bootstrap method indices
+/// are not visited as they do not point into the constant pool.
+trait VisitIndices {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16));
+}
+
+impl<T: TryFromItem> VisitIndices for Index<T> {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        visit(&mut self.index)
+    }
+}
+
+impl<T: VisitIndices> VisitIndices for Vec<T> {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        for item in self {
+            item.visit_indices(visit);
+        }
+    }
+}
+
+impl VisitIndices for Class {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        self.this_class.visit_indices(visit);
+        self.super_class.visit_indices(visit);
+        for interface in &mut self.interfaces {
+            interface.index.visit_indices(visit);
+        }
+        for field in &mut self.fields {
+            field.name_index.visit_indices(visit);
+            field.descriptor_index.visit_indices(visit);
+            field.attributes.visit_indices(visit);
+        }
+        for method in &mut self.methods {
+            method.name_index.visit_indices(visit);
+            method.descriptor_index.visit_indices(visit);
+            method.attributes.visit_indices(visit);
+        }
+        self.attributes.visit_indices(visit);
+    }
+}
+
+impl VisitIndices for ConstItem {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        match self {
+            ConstItem::Utf8(_)
+            | ConstItem::Integer(_)
+            | ConstItem::Float(_)
+            | ConstItem::Long(_)
+            | ConstItem::Double(_) => {}
+            ConstItem::Class(class) => class.name_index.visit_indices(visit),
+            ConstItem::StringJ(string) => string.string_index.visit_indices(visit),
+            ConstItem::FieldRef(field) => {
+                field.class_index.visit_indices(visit);
+                field.name_and_type_index.visit_indices(visit);
+            }
+            ConstItem::MethodRef(method) => {
+                method.class_index.visit_indices(visit);
+                method.name_and_type_index.visit_indices(visit);
+            }
+            ConstItem::InterfaceMethodRef(method) => {
+                method.class_index.visit_indices(visit);
+                method.name_and_type_index.visit_indices(visit);
+            }
+            ConstItem::NameAndType(name_and_type) => {
+                name_and_type.name_index.visit_indices(visit);
+                name_and_type.descriptor_index.visit_indices(visit);
+            }
+            ConstItem::MethodHandle(handle) => match &mut handle.reference_index {
+                MethodHandleReference::Field(index) => index.visit_indices(visit),
+                MethodHandleReference::Method(index) => index.visit_indices(visit),
+                MethodHandleReference::InterfaceMethod(index) => index.visit_indices(visit),
+            },
+            ConstItem::MethodType(method_type) => method_type.descriptor_index.visit_indices(visit),
+            ConstItem::Dynamic(dynamic) => dynamic.name_and_type_index.visit_indices(visit),
+            ConstItem::InvokeDynamic(dynamic) => dynamic.name_and_type_index.visit_indices(visit),
+            ConstItem::Module(module) => module.name_index.visit_indices(visit),
+            ConstItem::Package(package) => package.name_index.visit_indices(visit),
+        }
+    }
+}
+
+impl VisitIndices for BootstrapMethod {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        self.bootstrap_method_ref.visit_indices(visit);
+        self.bootstrap_arguments.visit_indices(visit);
+    }
+}
+
+impl VisitIndices for Attribute {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        if let AttributeInfo::BootstrapMethods(_) = self.info {
+            return;
+        }
+        self.attribute_name_index.visit_indices(visit);
+        match &mut self.info {
+            AttributeInfo::ConstantValue(value) => value.constant_value_index.visit_indices(visit),
+            AttributeInfo::Code(code) => {
+                for instruction in &mut code.code {
+                    instruction.instruction.visit_indices(visit);
+                }
+                for entry in &mut code.exception_table {
+                    entry.catch_type.visit_indices(visit);
+                }
+                code.attributes.visit_indices(visit);
+            }
+            AttributeInfo::StackMapTable(table) => {
+                for frame in &mut table.entries {
+                    frame.visit_indices(visit);
+                }
+            }
+            AttributeInfo::Exceptions(exceptions) => {
+                exceptions.exception_index_table.visit_indices(visit)
+            }
+            AttributeInfo::InnerClasses(inner_classes) => {
+                for class in &mut inner_classes.classes {
+                    class.inner_class_info_index.visit_indices(visit);
+                    class.outer_class_info_index.visit_indices(visit);
+                    class.inner_name_index.visit_indices(visit);
+                }
+            }
+            AttributeInfo::EnclosingMethod(method) => {
+                method.class_index.visit_indices(visit);
+                method.method_index.visit_indices(visit);
+            }
+            AttributeInfo::Signature(signature) => signature.signature_index.visit_indices(visit),
+            AttributeInfo::SourceFile(file) => file.sourcefile_index.visit_indices(visit),
+            AttributeInfo::LocalVariableTable(table) => {
+                for variable in &mut table.local_variable_table {
+                    variable.name_index.visit_indices(visit);
+                    variable.descriptor_index.visit_indices(visit);
+                }
+            }
+            AttributeInfo::LocalVariableTypeTable(table) => {
+                for variable in &mut table.local_variable_type_table {
+                    variable.name_index.visit_indices(visit);
+                    variable.signature_index.visit_indices(visit);
+                }
+            }
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) => {
+                annotations.annotations.visit_indices(visit)
+            }
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => {
+                annotations.annotations.visit_indices(visit)
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations(annotations) => {
+                for parameter in &mut annotations.parameter_annotations {
+                    parameter.annotations.visit_indices(visit);
+                }
+            }
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(annotations) => {
+                for parameter in &mut annotations.parameter_annotations {
+                    parameter.annotations.visit_indices(visit);
+                }
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) => {
+                for annotation in &mut annotations.annotations {
+                    annotation.annotation.visit_indices(visit);
+                }
+            }
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => {
+                for annotation in &mut annotations.annotations {
+                    annotation.annotation.visit_indices(visit);
+                }
+            }
+            AttributeInfo::AnnotationDefault(default) => default.default_value.visit_indices(visit),
+            AttributeInfo::MethodParameters(parameters) => {
+                for parameter in &mut parameters.parameters {
+                    parameter.name_index.visit_indices(visit);
+                }
+            }
+            AttributeInfo::Module(module) => {
+                module.name_index.visit_indices(visit);
+                module.version_index.visit_indices(visit);
+                for requires in &mut module.requires {
+                    requires.requires_index.visit_indices(visit);
+                    requires.version_index.visit_indices(visit);
+                }
+                for exports in &mut module.exports {
+                    exports.exports_index.visit_indices(visit);
+                    exports.to_index.visit_indices(visit);
+                }
+                for opens in &mut module.opens {
+                    opens.opens_index.visit_indices(visit);
+                    opens.to_index.visit_indices(visit);
+                }
+                for uses in &mut module.uses {
+                    uses.uses_index.visit_indices(visit);
+                }
+                for provides in &mut module.provides {
+                    provides.provides_index.visit_indices(visit);
+                    provides.with_index.visit_indices(visit);
+                }
+            }
+            AttributeInfo::ModulePackages(packages) => packages.package_index.visit_indices(visit),
+            AttributeInfo::ModuleMainClass(main) => main.main_class_index.visit_indices(visit),
+            AttributeInfo::NestHost(host) => host.host_class_index.visit_indices(visit),
+            AttributeInfo::NestMembers(members) => members.classes.visit_indices(visit),
+            AttributeInfo::Record(record) => {
+                for component in &mut record.components {
+                    component.name_index.visit_indices(visit);
+                    component.descriptor_index.visit_indices(visit);
+                    component.attributes.visit_indices(visit);
+                }
+            }
+            AttributeInfo::PermittedSubclasses(subclasses) => {
+                subclasses.classes.visit_indices(visit)
+            }
+            AttributeInfo::Synthetic(_)
+            | AttributeInfo::Deprecated(_)
+            | AttributeInfo::SourceDebugExtension(_)
+            | AttributeInfo::LineNumberTable(_)
+            | AttributeInfo::BootstrapMethods(_)
+            | AttributeInfo::Unknown(_) => {}
+        }
+    }
+}
+
+impl VisitIndices for Instruction {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        match self {
+            Instruction::ldc(index)
+            | Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => index.visit_indices(visit),
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => index.visit_indices(visit),
+            Instruction::invokevirtual(index) => index.visit_indices(visit),
+            Instruction::invokeinterface(index, _) => index.visit_indices(visit),
+            Instruction::invokedynamic(index) => index.visit_indices(visit),
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index)
+            | Instruction::multianewarray(index, _) => index.visit_indices(visit),
+            _ => {}
+        }
+    }
+}
+
+impl VisitIndices for StackMapFrame {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        match self {
+            StackMapFrame::SameLocals1StackItem { stack, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                stack.visit_indices(visit)
+            }
+            StackMapFrame::Append { locals, .. } => locals.visit_indices(visit),
+            StackMapFrame::Full { locals, stack, .. } => {
+                locals.visit_indices(visit);
+                stack.visit_indices(visit);
+            }
+            StackMapFrame::Same { .. }
+            | StackMapFrame::SameExtended { .. }
+            | StackMapFrame::Chop { .. } => {}
+        }
+    }
+}
+
+impl VisitIndices for VerificationTypeInfo {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        if let VerificationTypeInfo::Object(class) = self {
+            class.visit_indices(visit);
+        }
+    }
+}
+
+impl VisitIndices for Annotation {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        self.type_index.visit_indices(visit);
+        for pair in &mut self.element_value_pairs {
+            pair.element_name_index.visit_indices(visit);
+            pair.value.visit_indices(visit);
+        }
+    }
+}
+
+impl VisitIndices for ElementValue {
+    fn visit_indices(&mut self, visit: &mut dyn FnMut(&mut u16)) {
+        match self {
+            ElementValue::Byte(index)
+            | ElementValue::Char(index)
+            | ElementValue::Int(index)
+            | ElementValue::Short(index)
+            | ElementValue::Boolean(index) => index.visit_indices(visit),
+            ElementValue::Double(index) => index.visit_indices(visit),
+            ElementValue::Float(index) => index.visit_indices(visit),
+            ElementValue::Long(index) => index.visit_indices(visit),
+            ElementValue::String(index) | ElementValue::Class(index) => index.visit_indices(visit),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => {
+                type_name_index.visit_indices(visit);
+                const_name_index.visit_indices(visit);
+            }
+            ElementValue::Annotation(annotation) => annotation.visit_indices(visit),
+            ElementValue::Array(values) => values.visit_indices(visit),
+        }
+    }
+}
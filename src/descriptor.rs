@@ -0,0 +1,403 @@
+//! Field and method descriptors (JVMS 4.3), e.g. `[Ljava/lang/String;` and `(IJ)V`
+
+use std::fmt::{self, Display};
+
+use crate::{error::ParseError, Result};
+
+/// Array types may have at most this many dimensions
+pub const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+}
+
+impl BaseType {
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'B' => BaseType::Byte,
+            'C' => BaseType::Char,
+            'D' => BaseType::Double,
+            'F' => BaseType::Float,
+            'I' => BaseType::Int,
+            'J' => BaseType::Long,
+            'S' => BaseType::Short,
+            'Z' => BaseType::Boolean,
+            _ => return None,
+        })
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            BaseType::Byte => 'B',
+            BaseType::Char => 'C',
+            BaseType::Double => 'D',
+            BaseType::Float => 'F',
+            BaseType::Int => 'I',
+            BaseType::Long => 'J',
+            BaseType::Short => 'S',
+            BaseType::Boolean => 'Z',
+        }
+    }
+
+    /// The keyword used in Java source
+    pub fn java_name(self) -> &'static str {
+        match self {
+            BaseType::Byte => "byte",
+            BaseType::Char => "char",
+            BaseType::Double => "double",
+            BaseType::Float => "float",
+            BaseType::Int => "int",
+            BaseType::Long => "long",
+            BaseType::Short => "short",
+            BaseType::Boolean => "boolean",
+        }
+    }
+
+    /// The number of local variable or operand stack slots a value takes
+    pub fn slot_size(self) -> u16 {
+        match self {
+            BaseType::Long | BaseType::Double => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// The type of a field, parameter or local variable
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Base(BaseType),
+    // The internal name of the class, e.g. `java/lang/String`
+    Object(String),
+    // The element type is never itself an array
+    Array {
+        dimensions: u8,
+        element: Box<FieldType>,
+    },
+}
+
+impl FieldType {
+    pub fn parse(descriptor: &str) -> Result<Self> {
+        let mut parser = Parser::new(descriptor);
+        let field_type = parser.field_type()?;
+        parser.finish()?;
+        Ok(field_type)
+    }
+
+    /// An object type for the class with the given internal name
+    pub fn object(name: &str) -> Self {
+        FieldType::Object(name.to_string())
+    }
+
+    /// An array of `dimensions` dimensions of `element`, which may already be an array.
+    /// Fails without any dimensions, or with more than `MAX_ARRAY_DIMENSIONS` in total
+    pub fn array(element: FieldType, dimensions: u8) -> Result<Self> {
+        let (inner, element) = match element {
+            FieldType::Array {
+                dimensions: inner,
+                element,
+            } => (inner, element),
+            element => (0, Box::new(element)),
+        };
+        let total = inner as usize + dimensions as usize;
+        if dimensions == 0 || total > MAX_ARRAY_DIMENSIONS {
+            return Err(ParseError::InvalidDescriptor {
+                descriptor: element.to_string(),
+                reason: format!("an array cannot have {} dimensions", total),
+            });
+        }
+        Ok(FieldType::Array {
+            dimensions: total as u8,
+            element,
+        })
+    }
+
+    /// Whether values of this type are references
+    pub fn is_reference(&self) -> bool {
+        !matches!(self, FieldType::Base(_))
+    }
+
+    /// The number of local variable or operand stack slots a value takes,
+    /// 2 for `long` and `double` and 1 for everything else
+    pub fn slot_size(&self) -> u16 {
+        match self {
+            FieldType::Base(base) => base.slot_size(),
+            _ => 1,
+        }
+    }
+
+    /// The descriptor, e.g. `[Ljava/lang/String;`
+    pub fn to_internal(&self) -> String {
+        self.to_string()
+    }
+
+    /// The type as it is written in Java source, e.g. `java.lang.String[]`
+    pub fn to_java(&self) -> String {
+        match self {
+            FieldType::Base(base) => base.java_name().to_string(),
+            FieldType::Object(name) => java_class_name(name),
+            FieldType::Array {
+                dimensions,
+                element,
+            } => format!("{}{}", element.to_java(), "[]".repeat(*dimensions as usize)),
+        }
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Base(base) => write!(f, "{}", base.as_char()),
+            FieldType::Object(name) => write!(f, "L{};", name),
+            FieldType::Array {
+                dimensions,
+                element,
+            } => write!(f, "{}{}", "[".repeat(*dimensions as usize), element),
+        }
+    }
+}
+
+/// The parameter and return types of a method
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    pub parameters: Vec<FieldType>,
+    // `None` for `void`
+    pub return_type: Option<FieldType>,
+}
+
+impl MethodDescriptor {
+    pub fn parse(descriptor: &str) -> Result<Self> {
+        let mut parser = Parser::new(descriptor);
+        parser.expect('(')?;
+        let mut parameters = Vec::new();
+        while !parser.eat(')') {
+            parameters.push(parser.field_type()?);
+        }
+        let return_type = if parser.eat('V') {
+            None
+        } else {
+            Some(parser.field_type()?)
+        };
+        parser.finish()?;
+        Ok(MethodDescriptor {
+            parameters,
+            return_type,
+        })
+    }
+
+    /// The number of local variable slots taken by the parameters, not counting `this`
+    pub fn parameter_slots(&self) -> u16 {
+        self.parameters.iter().map(FieldType::slot_size).sum()
+    }
+
+    /// The number of operand stack slots taken by the return value
+    pub fn return_slots(&self) -> u16 {
+        self.return_type.as_ref().map_or(0, FieldType::slot_size)
+    }
+
+    /// The descriptor, e.g. `([Ljava/lang/String;)V`
+    pub fn to_internal(&self) -> String {
+        self.to_string()
+    }
+
+    /// The method's declaration as it is written in Java source without modifiers,
+    /// e.g. `void main(java.lang.String[])`
+    pub fn to_java(&self, name: &str) -> String {
+        let return_type = match &self.return_type {
+            Some(return_type) => return_type.to_java(),
+            None => "void".to_string(),
+        };
+        let parameters: Vec<String> = self.parameters.iter().map(FieldType::to_java).collect();
+        format!("{} {}({})", return_type, name, parameters.join(", "))
+    }
+}
+
+impl Display for MethodDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for parameter in &self.parameters {
+            write!(f, "{}", parameter)?;
+        }
+        match &self.return_type {
+            Some(return_type) => write!(f, "){}", return_type),
+            None => write!(f, ")V"),
+        }
+    }
+}
+
+/// Converts an internal class name to a binary name, e.g. `java/util/Map$Entry` becomes
+/// `java.util.Map$Entry`. `$` is kept as javap does, since it can be part of a class name
+pub fn java_class_name(internal: &str) -> String {
+    internal.replace('/', ".")
+}
+
+/// Reads descriptors and signatures one character at a time
+pub(crate) struct Parser<'a> {
+    descriptor: &'a str,
+    position: usize,
+    // Whether errors are reported as `InvalidSignature` rather than `InvalidDescriptor`
+    signature: bool,
+    // How many levels of `nested` are open
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(descriptor: &'a str) -> Self {
+        Parser {
+            descriptor,
+            position: 0,
+            signature: false,
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn for_signature(signature: &'a str) -> Self {
+        Parser {
+            signature: true,
+            ..Parser::new(signature)
+        }
+    }
+
+    pub(crate) fn error(&self, reason: &str) -> ParseError {
+        let reason = format!("{} at position {}", reason, self.position);
+        if self.signature {
+            ParseError::InvalidSignature {
+                signature: self.descriptor.to_string(),
+                reason,
+            }
+        } else {
+            ParseError::InvalidDescriptor {
+                descriptor: self.descriptor.to_string(),
+                reason,
+            }
+        }
+    }
+
+    /// Runs `f` one level deeper, failing with `reason` instead once `limit` levels are open
+    pub(crate) fn nested<T>(
+        &mut self,
+        limit: usize,
+        reason: &str,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if self.depth >= limit {
+            return Err(self.error(reason));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.descriptor[self.position..].chars().next()
+    }
+
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consumes `c` if it is the next character
+    pub(crate) fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// Fails unless the whole descriptor has been read
+    pub(crate) fn finish(&self) -> Result<()> {
+        if self.position == self.descriptor.len() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing characters"))
+        }
+    }
+
+    /// Reads up to the first of `terminators`, which is not consumed. The result must be a
+    /// non-empty name without `.`, `;`, `[` or an empty `/`-separated segment
+    pub(crate) fn class_name(&mut self, terminators: &[char]) -> Result<&'a str> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if terminators.contains(&c) {
+                break;
+            }
+            if matches!(c, '.' | ';' | '[') {
+                return Err(self.error(&format!("unexpected '{}' in class name", c)));
+            }
+            self.position += c.len_utf8();
+        }
+        let name = &self.descriptor[start..self.position];
+        if name.split('/').any(str::is_empty) {
+            return Err(self.error("empty class name"));
+        }
+        Ok(name)
+    }
+
+    /// Reads a non-empty name up to the next `.`, `;`, `[`, `/`, `<`, `>` or `:`
+    pub(crate) fn identifier(&mut self) -> Result<&'a str> {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if matches!(c, '.' | ';' | '[' | '/' | '<' | '>' | ':') {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        if start == self.position {
+            return Err(self.error("expected an identifier"));
+        }
+        Ok(&self.descriptor[start..self.position])
+    }
+
+    pub(crate) fn field_type(&mut self) -> Result<FieldType> {
+        let mut dimensions = 0;
+        while self.eat('[') {
+            dimensions += 1;
+        }
+        if dimensions > MAX_ARRAY_DIMENSIONS {
+            return Err(self.error("too many array dimensions"));
+        }
+        let element = match self.next() {
+            Some('L') => {
+                let name = self.class_name(&[';'])?;
+                self.expect(';')?;
+                FieldType::object(name)
+            }
+            Some(c) => match BaseType::from_char(c) {
+                Some(base) => FieldType::Base(base),
+                None => {
+                    self.position -= c.len_utf8();
+                    return Err(self.error(&format!("unexpected '{}'", c)));
+                }
+            },
+            None => return Err(self.error("unexpected end")),
+        };
+        Ok(if dimensions == 0 {
+            element
+        } else {
+            FieldType::Array {
+                dimensions: dimensions as u8,
+                element: Box::new(element),
+            }
+        })
+    }
+}
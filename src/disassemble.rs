@@ -0,0 +1,592 @@
+//! Textual disassembly in the layout of `javap -c -v -p`, so the output can be diffed against the
+//! JDK tool. The `Classfile`, `Last modified` and checksum lines javap prints first are left out,
+//! as they describe the file rather than the class.
+//!
+//! Floats and doubles are written with the shortest digits that round-trip, as javap does since
+//! JDK 19; older versions sometimes print an extra digit. Attributes that are specific to the JDK,
+//! like `ModuleTarget`, are dumped as unknown attributes
+
+mod attributes;
+mod constants;
+pub mod options;
+
+use options::DisassembleOptions;
+
+use crate::{
+    descriptor::{FieldType, MethodDescriptor},
+    error::Result,
+    signature::{
+        ClassTypeSignature, MethodSignature, ReferenceTypeSignature, TypeArgument, TypeParameter,
+        TypeSignature,
+    },
+    structs::{
+        access_flags::ClassFlags,
+        attributes::{AttributeInfo, Module},
+        bootstrap::Resolver,
+        Class, ConstPool, Field, Method,
+    },
+};
+
+/// Renders the class the way `javap -c -v -p` does
+pub fn disassemble(class: &Class) -> Result<String> {
+    disassemble_with(class, &DisassembleOptions::default())
+}
+
+/// Renders the class the way `javap -c -v -p` does, with the additions the options ask for
+pub fn disassemble_with(class: &Class, options: &DisassembleOptions) -> Result<String> {
+    let mut disassembler = Disassembler {
+        class,
+        pool: &class.constant_pool,
+        options,
+        resolver: Resolver::new(class),
+        out: LineWriter::default(),
+    };
+    disassembler.class()?;
+    Ok(disassembler.out.finish())
+}
+
+const INDENT_WIDTH: usize = 2;
+// Comments after a `tab` start at this column, plus the indentation
+const TAB_COLUMN: usize = 40;
+
+/// Writes lines the way javap does: spaces are held back until the next visible character, so no
+/// line ends in whitespace, and indentation is only added to lines that have text
+#[derive(Default)]
+struct LineWriter {
+    output: String,
+    buffer: String,
+    // The length of `buffer` in characters
+    column: usize,
+    pending_spaces: usize,
+    // Set after a method, so a blank line separates it from the next one
+    pending_newline: bool,
+    indent: usize,
+}
+
+impl LineWriter {
+    fn print(&mut self, text: &str) {
+        if self.pending_newline {
+            self.pending_newline = false;
+            self.newline();
+        }
+        for c in text.chars() {
+            match c {
+                ' ' => self.pending_spaces += 1,
+                '\n' => self.newline(),
+                c => {
+                    if self.buffer.is_empty() {
+                        self.pending_spaces += self.indent * INDENT_WIDTH;
+                    }
+                    self.buffer
+                        .extend(std::iter::repeat_n(' ', self.pending_spaces));
+                    self.column += self.pending_spaces + 1;
+                    self.pending_spaces = 0;
+                    self.buffer.push(c);
+                }
+            }
+        }
+    }
+
+    fn println(&mut self, text: &str) {
+        self.print(text);
+        self.newline();
+    }
+
+    fn newline(&mut self) {
+        self.pending_spaces = 0;
+        self.output.push_str(&self.buffer);
+        self.output.push('\n');
+        self.buffer.clear();
+        self.column = 0;
+    }
+
+    /// Moves to the comment column, or by one space if the line is already past it
+    fn tab(&mut self) {
+        let column = self.indent * INDENT_WIDTH + TAB_COLUMN;
+        self.pending_spaces += if column <= self.column {
+            1
+        } else {
+            column - self.column
+        };
+    }
+
+    fn indent(&mut self) {
+        self.indent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.indent -= 1;
+    }
+
+    fn finish(mut self) -> String {
+        if !self.buffer.is_empty() {
+            self.newline();
+        }
+        self.output
+    }
+}
+
+struct Disassembler<'a> {
+    class: &'a Class,
+    pool: &'a ConstPool,
+    options: &'a DisassembleOptions,
+    // Shared by every call site, so each bootstrap argument is only resolved once
+    resolver: Resolver<'a>,
+    out: LineWriter,
+}
+
+impl Disassembler<'_> {
+    fn class(&mut self) -> Result<()> {
+        let class = self.class;
+        self.out.indent();
+        for attribute in &class.attributes {
+            if let AttributeInfo::SourceFile(source_file) = &attribute.info {
+                let name = &source_file.sourcefile_index.get(self.pool)?.str;
+                self.out.println(&format!("Compiled from \"{}\"", name));
+            }
+        }
+        self.out.outdent();
+
+        self.header()?;
+        self.out.println("{");
+        self.out.indent();
+        for field in &class.fields {
+            self.field(field)?;
+        }
+        for method in &class.methods {
+            self.method(method)?;
+        }
+        self.out.pending_newline = false;
+        self.out.outdent();
+        self.out.println("}");
+        self.attributes(&class.attributes, None)
+    }
+
+    fn header(&mut self) -> Result<()> {
+        let class = self.class;
+        let flags = class.access_flags.bits();
+        let is_interface = class.access_flags.contains(ClassFlags::INTERFACE);
+        self.modifiers(&modifiers(flags, CLASS_MODIFIERS, Kind::Class));
+        if class.access_flags.contains(ClassFlags::MODULE) {
+            self.module_name()?;
+        } else {
+            self.out
+                .print(if is_interface { "interface " } else { "class " });
+            let name = self.class_name(class.this_class.index)?;
+            self.out.print(&java_name(&name));
+        }
+
+        match class.signature()? {
+            Some(signature) => {
+                let mut text = type_parameters(&signature.type_parameters, "<", ">");
+                if is_interface {
+                    text.push_str(&type_list(&signature.interfaces, " extends ", ""));
+                } else {
+                    text.push_str(" extends ");
+                    text.push_str(&class_type(&signature.superclass));
+                    text.push_str(&type_list(&signature.interfaces, " implements ", ""));
+                }
+                self.out.print(&java_name(&text));
+            }
+            None => {
+                if !is_interface && class.super_class.index != 0 {
+                    let superclass = java_name(&self.class_name(class.super_class.index)?);
+                    if superclass != "java.lang.Object" {
+                        self.out.print(&format!(" extends {}", superclass));
+                    }
+                }
+                for (i, interface) in class.interfaces.iter().enumerate() {
+                    let separator = match (i, is_interface) {
+                        (0, false) => " implements ",
+                        (0, true) => " extends ",
+                        _ => ",",
+                    };
+                    let name = java_name(&self.class_name(interface.index.index)?);
+                    self.out.print(&format!("{}{}", separator, name));
+                }
+            }
+        }
+        self.out.println("");
+
+        self.out.indent();
+        self.out
+            .println(&format!("minor version: {}", class.version.minor));
+        self.out
+            .println(&format!("major version: {}", class.version.major as u16));
+        self.flags(flags, CLASS_FLAGS, Kind::Class);
+        for (label, index) in [
+            ("this_class", class.this_class.index),
+            ("super_class", class.super_class.index),
+        ] {
+            self.out.print(&format!("{}: #{}", label, index));
+            if index != 0 {
+                self.out.tab();
+                self.out.print(&format!("// {}", self.string_value(index)?));
+            }
+            self.out.println("");
+        }
+        self.out.println(&format!(
+            "interfaces: {}, fields: {}, methods: {}, attributes: {}",
+            class.interfaces.len(),
+            class.fields.len(),
+            class.methods.len(),
+            class.attributes.len()
+        ));
+        self.out.outdent();
+        self.constant_pool()
+    }
+
+    /// `open module name@version`
+    fn module_name(&mut self) -> Result<()> {
+        let module = self
+            .class
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::Module(module) => Some(module),
+                _ => None,
+            });
+        let module: &Module = match module {
+            Some(module) => module,
+            None => {
+                let name = self.class_name(self.class.this_class.index)?;
+                self.out.print(&format!("class {}", java_name(&name)));
+                return Ok(());
+            }
+        };
+        if module.flags.bits() & 0x0020 != 0 {
+            self.out.print("open ");
+        }
+        let name = self.name(module.name_index.index)?;
+        self.out.print(&format!("module {}", java_name(&name)));
+        if module.version_index.index != 0 {
+            let version = &module.version_index.get(self.pool)?.str;
+            self.out.print(&format!("@{}", version));
+        }
+        Ok(())
+    }
+
+    fn field(&mut self, field: &Field) -> Result<()> {
+        let flags = field.access_flags.bits();
+        self.modifiers(&modifiers(flags, FIELD_MODIFIERS, Kind::Field));
+        let field_type = match field.signature(self.pool)? {
+            Some(signature) => reference_type(&signature),
+            None => field_type(&field.descriptor(self.pool)?),
+        };
+        let name = &field.name_index.get(self.pool)?.str;
+        self.out
+            .println(&format!("{} {};", java_name(&field_type), name));
+
+        self.out.indent();
+        let descriptor = &field.descriptor_index.get(self.pool)?.str;
+        self.out.println(&format!("descriptor: {}", descriptor));
+        self.flags(flags, FIELD_FLAGS, Kind::Field);
+        self.attributes(&field.attributes, None)?;
+        self.out.outdent();
+        self.out.println("");
+        Ok(())
+    }
+
+    fn method(&mut self, method: &Method) -> Result<()> {
+        let class = self.class;
+        let flags = method.access_flags.bits();
+        let name = &method.name_index.get(self.pool)?.str;
+        let signature = method.signature(self.pool)?;
+
+        let mut modifiers = modifiers(flags, METHOD_MODIFIERS, Kind::Method);
+        // Non-abstract instance methods of interfaces are default methods since Java 8
+        if class.access_flags.contains(ClassFlags::INTERFACE)
+            && flags & ACC_ABSTRACT == 0
+            && name != "<clinit>"
+            && class.version.major as u16 >= 52
+            && flags & (ACC_STATIC | ACC_PRIVATE) == 0
+        {
+            modifiers.push("default");
+        }
+        self.modifiers(&modifiers);
+
+        let descriptor = method.descriptor(self.pool)?;
+        let (parameters, return_type) = match &signature {
+            Some(signature) => {
+                self.out.print(&java_name(&type_parameters(
+                    &signature.type_parameters,
+                    "<",
+                    "> ",
+                )));
+                signature_types(signature)
+            }
+            None => descriptor_types(&descriptor),
+        };
+        let mut parameters = java_name(&format!("({})", parameters.join(", ")));
+        if flags & ACC_VARARGS != 0 {
+            if let Some(i) = parameters.rfind("[]").filter(|&i| i > 0) {
+                parameters.replace_range(i..i + 2, "...");
+            }
+        }
+        match name.as_str() {
+            "<init>" => {
+                let class_name = self.class_name(class.this_class.index)?;
+                self.out
+                    .print(&format!("{}{}", java_name(&class_name), parameters));
+            }
+            "<clinit>" => self.out.print("{}"),
+            _ => self.out.print(&format!(
+                "{} {}{}",
+                java_name(&return_type),
+                name,
+                parameters
+            )),
+        }
+
+        let exceptions = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::Exceptions(exceptions) => Some(exceptions),
+                _ => None,
+            });
+        if let Some(exceptions) = exceptions {
+            self.out.print(" throws ");
+            // The generic throws clause is printed without converting the names
+            match signature.filter(|signature| !signature.throws.is_empty()) {
+                Some(signature) => {
+                    let throws: Vec<String> = signature.throws.iter().map(reference_type).collect();
+                    self.out.print(&throws.join(", "));
+                }
+                None => {
+                    let mut names = Vec::new();
+                    for exception in &exceptions.exception_index_table {
+                        names.push(java_name(&self.class_name(exception.index)?));
+                    }
+                    self.out.print(&names.join(", "));
+                }
+            }
+        }
+        self.out.println(";");
+
+        self.out.indent();
+        let descriptor = &method.descriptor_index.get(self.pool)?.str;
+        self.out.println(&format!("descriptor: {}", descriptor));
+        self.flags(flags, METHOD_FLAGS, Kind::Method);
+        self.attributes(&method.attributes, Some(method))?;
+        self.out.outdent();
+        self.out.pending_newline = true;
+        Ok(())
+    }
+
+    fn modifiers(&mut self, modifiers: &[&str]) {
+        for modifier in modifiers {
+            self.out.print(&format!("{} ", modifier));
+        }
+    }
+
+    /// `flags: (0x0021) ACC_PUBLIC, ACC_SUPER`
+    fn flags(&mut self, flags: u16, masks: &[u16], kind: Kind) {
+        let names: Vec<&str> = masks
+            .iter()
+            .filter(|&&mask| flags & mask != 0)
+            .map(|&mask| flag_name(mask, kind))
+            .collect();
+        self.out
+            .println(&format!("flags: (0x{:04x}) {}", flags, names.join(", ")));
+    }
+}
+
+const ACC_PRIVATE: u16 = 0x0002;
+const ACC_STATIC: u16 = 0x0008;
+const ACC_VARARGS: u16 = 0x0080;
+const ACC_ABSTRACT: u16 = 0x0400;
+const ACC_INTERFACE: u16 = 0x0200;
+
+/// What the flags belong to, as some bits have a different meaning for each
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Class,
+    InnerClass,
+    Field,
+    Method,
+}
+
+// The flags printed as modifiers and as `ACC_` names, in javap's order
+const CLASS_MODIFIERS: &[u16] = &[0x0001, 0x0010, 0x0400];
+const CLASS_FLAGS: &[u16] = &[
+    0x0001, 0x0010, 0x0020, 0x0200, 0x0400, 0x1000, 0x2000, 0x4000, 0x8000,
+];
+const INNER_CLASS_MODIFIERS: &[u16] = &[0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0400];
+const FIELD_MODIFIERS: &[u16] = &[0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0040, 0x0080];
+const FIELD_FLAGS: &[u16] = &[
+    0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0040, 0x0080, 0x1000, 0x4000,
+];
+const METHOD_MODIFIERS: &[u16] = &[
+    0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0020, 0x0100, 0x0400, 0x0800,
+];
+const METHOD_FLAGS: &[u16] = &[
+    0x0001, 0x0002, 0x0004, 0x0008, 0x0010, 0x0020, 0x0040, 0x0080, 0x0100, 0x0400, 0x0800, 0x1000,
+];
+
+fn modifiers(flags: u16, masks: &[u16], kind: Kind) -> Vec<&'static str> {
+    // `abstract` is implied for interfaces
+    let flags = if matches!(kind, Kind::Class | Kind::InnerClass) && flags & ACC_INTERFACE != 0 {
+        flags & !ACC_ABSTRACT
+    } else {
+        flags
+    };
+    masks
+        .iter()
+        .filter(|&&mask| flags & mask != 0)
+        .filter_map(|&mask| match mask {
+            0x0001 => Some("public"),
+            0x0002 => Some("private"),
+            0x0004 => Some("protected"),
+            0x0008 => Some("static"),
+            0x0010 => Some("final"),
+            0x0020 => Some("synchronized"),
+            0x0040 => Some("volatile"),
+            0x0080 if kind == Kind::Field => Some("transient"),
+            0x0100 => Some("native"),
+            0x0400 => Some("abstract"),
+            0x0800 => Some("strictfp"),
+            _ => None,
+        })
+        .collect()
+}
+
+fn flag_name(mask: u16, kind: Kind) -> &'static str {
+    match mask {
+        0x0001 => "ACC_PUBLIC",
+        0x0002 => "ACC_PRIVATE",
+        0x0004 => "ACC_PROTECTED",
+        0x0008 => "ACC_STATIC",
+        0x0010 => "ACC_FINAL",
+        0x0020 if kind == Kind::Class => "ACC_SUPER",
+        0x0020 => "ACC_SYNCHRONIZED",
+        0x0040 if kind == Kind::Field => "ACC_VOLATILE",
+        0x0040 => "ACC_BRIDGE",
+        0x0080 if kind == Kind::Field => "ACC_TRANSIENT",
+        0x0080 => "ACC_VARARGS",
+        0x0100 => "ACC_NATIVE",
+        0x0200 => "ACC_INTERFACE",
+        0x0400 => "ACC_ABSTRACT",
+        0x0800 => "ACC_STRICT",
+        0x1000 => "ACC_SYNTHETIC",
+        0x2000 => "ACC_ANNOTATION",
+        0x4000 => "ACC_ENUM",
+        0x8000 if kind == Kind::Class => "ACC_MODULE",
+        _ => "ACC_MANDATED",
+    }
+}
+
+/// javap's name for a class: `/` becomes `.` but nested classes keep their `$`
+fn java_name(internal: &str) -> String {
+    internal.replace('/', ".")
+}
+
+// The renderers below follow javap's, which write types with internal class names that are
+// converted by `java_name` where needed
+
+fn field_type(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Base(base) => base.java_name().to_string(),
+        FieldType::Object(name) => name.clone(),
+        FieldType::Array {
+            dimensions,
+            element,
+        } => format!(
+            "{}{}",
+            self::field_type(element),
+            "[]".repeat(*dimensions as usize)
+        ),
+    }
+}
+
+fn descriptor_types(descriptor: &MethodDescriptor) -> (Vec<String>, String) {
+    let parameters = descriptor.parameters.iter().map(field_type).collect();
+    let return_type = match &descriptor.return_type {
+        Some(return_type) => field_type(return_type),
+        None => "void".to_string(),
+    };
+    (parameters, return_type)
+}
+
+fn signature_types(signature: &MethodSignature) -> (Vec<String>, String) {
+    let parameters = signature.parameters.iter().map(type_signature).collect();
+    let return_type = match &signature.return_type {
+        Some(return_type) => type_signature(return_type),
+        None => "void".to_string(),
+    };
+    (parameters, return_type)
+}
+
+fn type_signature(signature: &TypeSignature) -> String {
+    match signature {
+        TypeSignature::Base(base) => base.java_name().to_string(),
+        TypeSignature::Reference(reference) => reference_type(reference),
+    }
+}
+
+fn reference_type(signature: &ReferenceTypeSignature) -> String {
+    match signature {
+        ReferenceTypeSignature::Class(class) => class_type(class),
+        ReferenceTypeSignature::TypeVariable(name) => name.clone(),
+        ReferenceTypeSignature::Array(element) => format!("{}[]", type_signature(element)),
+    }
+}
+
+/// `java/util/Map<K, V>`, inner classes are joined with `.` after the outer class
+fn class_type(signature: &ClassTypeSignature) -> String {
+    let mut result = String::new();
+    for (i, class) in signature.classes.iter().enumerate() {
+        if i > 0 {
+            result.push('.');
+        } else if !signature.package.is_empty() {
+            result.push_str(&signature.package);
+            result.push('/');
+        }
+        result.push_str(&class.name);
+        if !class.type_arguments.is_empty() {
+            let arguments: Vec<String> = class.type_arguments.iter().map(type_argument).collect();
+            result.push_str(&format!("<{}>", arguments.join(", ")));
+        }
+    }
+    result
+}
+
+fn type_argument(argument: &TypeArgument) -> String {
+    match argument {
+        TypeArgument::Any => "?".to_string(),
+        TypeArgument::Exact(bound) => reference_type(bound),
+        TypeArgument::Extends(bound) => format!("? extends {}", reference_type(bound)),
+        TypeArgument::Super(bound) => format!("? super {}", reference_type(bound)),
+    }
+}
+
+/// Unlike the `to_java` renderers, a bound of `Object` is written out
+fn type_parameter(parameter: &TypeParameter) -> String {
+    let bounds: Vec<String> = parameter
+        .class_bound
+        .iter()
+        .chain(&parameter.interface_bounds)
+        .map(reference_type)
+        .collect();
+    if bounds.is_empty() {
+        parameter.name.clone()
+    } else {
+        format!("{} extends {}", parameter.name, bounds.join(" & "))
+    }
+}
+
+fn type_parameters(parameters: &[TypeParameter], prefix: &str, suffix: &str) -> String {
+    if parameters.is_empty() {
+        return String::new();
+    }
+    let parameters: Vec<String> = parameters.iter().map(type_parameter).collect();
+    format!("{}{}{}", prefix, parameters.join(", "), suffix)
+}
+
+fn type_list(types: &[ClassTypeSignature], prefix: &str, suffix: &str) -> String {
+    if types.is_empty() {
+        return String::new();
+    }
+    let types: Vec<String> = types.iter().map(class_type).collect();
+    format!("{}{}{}", prefix, types.join(", "), suffix)
+}
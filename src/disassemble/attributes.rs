@@ -0,0 +1,1018 @@
+//! Attributes, including the code of methods and annotations, in javap's layout
+
+use crate::{
+    analysis::concat::Concatenation,
+    error::Result,
+    structs::{
+        attributes::{
+            Annotation, Attribute, AttributeInfo, Code, ElementValue, Module, ParameterAnnotations,
+            RecordComponent, StackMapFrame, TargetInfo, TargetType, TypeAnnotation, TypePathKind,
+            VerificationTypeInfo,
+        },
+        instructions::{Instruction, Wide},
+        Method,
+    },
+};
+
+use super::{
+    constants::{java_double, java_float},
+    field_type, java_name, modifiers, reference_type, Disassembler, Kind, INNER_CLASS_MODIFIERS,
+};
+
+// The bodies of switch instructions are indented past the `%4d: ` of the offset
+const SWITCH_INDENT: usize = 6_usize.div_ceil(super::INDENT_WIDTH);
+
+impl Disassembler<'_> {
+    /// `method` is the method owning the attributes, needed for its `Code`
+    pub(super) fn attributes(
+        &mut self,
+        attributes: &[Attribute],
+        method: Option<&Method>,
+    ) -> Result<()> {
+        for attribute in attributes {
+            self.attribute(attribute, method)?;
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self, attribute: &Attribute, method: Option<&Method>) -> Result<()> {
+        match &attribute.info {
+            AttributeInfo::ConstantValue(constant) => {
+                let value = self.constant(constant.constant_value_index.index)?;
+                self.out.println(&format!("ConstantValue: {}", value));
+            }
+            AttributeInfo::Code(code) => self.code(code, method)?,
+            AttributeInfo::StackMapTable(table) => {
+                self.out.println(&format!(
+                    "StackMapTable: number_of_entries = {}",
+                    table.entries.len()
+                ));
+                self.out.indent();
+                for frame in &table.entries {
+                    self.stack_map_frame(frame)?;
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::Exceptions(exceptions) => {
+                self.out.println("Exceptions:");
+                self.out.indent();
+                let mut names = Vec::new();
+                for exception in &exceptions.exception_index_table {
+                    names.push(java_name(&self.class_name(exception.index)?));
+                }
+                self.out.println(&format!("throws {}", names.join(", ")));
+                self.out.outdent();
+            }
+            AttributeInfo::InnerClasses(inner_classes) => {
+                if !inner_classes.classes.is_empty() {
+                    self.out.println("InnerClasses:");
+                    self.out.indent();
+                }
+                for class in &inner_classes.classes {
+                    let flags = class.inner_class_access_flags.bits();
+                    self.modifiers(&modifiers(flags, INNER_CLASS_MODIFIERS, Kind::InnerClass));
+                    let inner_name = class.inner_name_index.index;
+                    let outer = class.outer_class_info_index.index;
+                    if inner_name != 0 {
+                        self.out.print(&format!("#{}= ", inner_name));
+                    }
+                    self.out
+                        .print(&format!("#{}", class.inner_class_info_index.index));
+                    if outer != 0 {
+                        self.out.print(&format!(" of #{}", outer));
+                    }
+                    self.out.print(";");
+                    self.out.tab();
+                    self.out.print("// ");
+                    if inner_name != 0 {
+                        self.out.print(&format!("{}=", self.utf8(inner_name)?));
+                    }
+                    let inner = self.constant(class.inner_class_info_index.index)?;
+                    self.out.print(&inner);
+                    if outer != 0 {
+                        self.out.print(&format!(" of {}", self.constant(outer)?));
+                    }
+                    self.out.println("");
+                }
+                if !inner_classes.classes.is_empty() {
+                    self.out.outdent();
+                }
+            }
+            AttributeInfo::EnclosingMethod(enclosing) => {
+                let method_index = enclosing.method_index.index;
+                self.out.print(&format!(
+                    "EnclosingMethod: #{}.#{}",
+                    enclosing.class_index.index, method_index
+                ));
+                self.out.tab();
+                let class_name = self.class_name(enclosing.class_index.index)?;
+                self.out.print(&format!("// {}", java_name(&class_name)));
+                if method_index != 0 {
+                    let method = enclosing.method_index.get(self.pool)?;
+                    self.out
+                        .print(&format!(".{}", self.utf8(method.name_index.index)?));
+                }
+                self.out.println("");
+            }
+            AttributeInfo::Synthetic(_) => self.out.println("Synthetic: true"),
+            AttributeInfo::Signature(signature) => {
+                let index = signature.signature_index.index;
+                self.out.print(&format!("Signature: #{}", index));
+                self.out.tab();
+                self.out.println(&format!("// {}", self.utf8(index)?));
+            }
+            AttributeInfo::SourceFile(source_file) => {
+                let name = self.utf8(source_file.sourcefile_index.index)?;
+                self.out.println(&format!("SourceFile: \"{}\"", name));
+            }
+            AttributeInfo::SourceDebugExtension(extension) => {
+                self.out.println("SourceDebugExtension:");
+                self.out.indent();
+                let lines = extension.debug_extension.str.split(['\r', '\n']);
+                for line in lines.filter(|line| !line.is_empty()) {
+                    self.out.println(line);
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::LineNumberTable(table) => {
+                self.out.println("LineNumberTable:");
+                self.out.indent();
+                for line in &table.line_number_table {
+                    self.out
+                        .println(&format!("line {}: {}", line.line_number, line.start_pc));
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::LocalVariableTable(table) => {
+                let variables = table.local_variable_table.iter().map(|variable| {
+                    (
+                        variable.start_pc,
+                        variable.length,
+                        variable.index,
+                        variable.name_index.index,
+                        variable.descriptor_index.index,
+                    )
+                });
+                self.local_variables("LocalVariableTable:", variables.collect())?;
+            }
+            AttributeInfo::LocalVariableTypeTable(table) => {
+                let variables = table.local_variable_type_table.iter().map(|variable| {
+                    (
+                        variable.start_pc,
+                        variable.length,
+                        variable.index,
+                        variable.name_index.index,
+                        variable.signature_index.index,
+                    )
+                });
+                self.local_variables("LocalVariableTypeTable:", variables.collect())?;
+            }
+            AttributeInfo::Deprecated(_) => self.out.println("Deprecated: true"),
+            AttributeInfo::RuntimeVisibleAnnotations(annotations) => {
+                self.annotations("RuntimeVisibleAnnotations:", &annotations.annotations)?
+            }
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations) => {
+                self.annotations("RuntimeInvisibleAnnotations:", &annotations.annotations)?
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations(annotations) => self
+                .parameter_annotations(
+                    "RuntimeVisibleParameterAnnotations:",
+                    &annotations.parameter_annotations,
+                )?,
+            AttributeInfo::RuntimeInvisibleParameterAnnotations(annotations) => self
+                .parameter_annotations(
+                    "RuntimeInvisibleParameterAnnotations:",
+                    &annotations.parameter_annotations,
+                )?,
+            AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) => {
+                self.type_annotations("RuntimeVisibleTypeAnnotations:", &annotations.annotations)?
+            }
+            AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations) => {
+                self.type_annotations("RuntimeInvisibleTypeAnnotations:", &annotations.annotations)?
+            }
+            AttributeInfo::AnnotationDefault(default) => {
+                self.out.println("AnnotationDefault:");
+                self.out.indent();
+                let value = self.element_value(&default.default_value);
+                self.out.println(&format!("default_value: {}", value));
+                self.out.indent();
+                self.resolved_element_value(&default.default_value)?;
+                self.out.println("");
+                self.out.outdent();
+                self.out.outdent();
+            }
+            AttributeInfo::BootstrapMethods(methods) => {
+                self.out.println("BootstrapMethods:");
+                for (i, method) in methods.bootstrap_methods.iter().enumerate() {
+                    let index = method.bootstrap_method_ref.index;
+                    self.out.indent();
+                    self.out
+                        .println(&format!("{}: #{} {}", i, index, self.string_value(index)?));
+                    self.out.indent();
+                    self.out.println("Method arguments:");
+                    self.out.indent();
+                    for argument in &method.bootstrap_arguments {
+                        let value = self.string_value(argument.index)?;
+                        self.out.println(&format!("#{} {}", argument.index, value));
+                    }
+                    self.out.outdent();
+                    self.out.outdent();
+                    self.out.outdent();
+                }
+            }
+            AttributeInfo::MethodParameters(parameters) => {
+                self.out.println("MethodParameters:");
+                self.out.indent();
+                self.out.println(&format!("{:<31}{}", "Name", "Flags"));
+                for parameter in &parameters.parameters {
+                    let name = match parameter.name_index.index {
+                        0 => "<no name>".to_string(),
+                        index => self.string_value(index)?,
+                    };
+                    let flags = parameter.access_flags.bits();
+                    let mut names = String::new();
+                    for (mask, name) in [
+                        (0x0010, "final "),
+                        (0x8000, "mandated "),
+                        (0x1000, "synthetic"),
+                    ] {
+                        if flags & mask != 0 {
+                            names.push_str(name);
+                        }
+                    }
+                    self.out.println(&format!("{:<31}{}", name, names));
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::Module(module) => self.module(module)?,
+            AttributeInfo::ModulePackages(packages) => {
+                self.out.println("ModulePackages: ");
+                self.out.indent();
+                for package in &packages.package_index {
+                    self.out.print(&format!("#{}", package.index));
+                    self.out.tab();
+                    self.out
+                        .println(&format!("// {}", java_name(&self.name(package.index)?)));
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::ModuleMainClass(main_class) => {
+                let index = main_class.main_class_index.index;
+                self.out.print(&format!("ModuleMainClass: #{}", index));
+                self.out.tab();
+                self.out
+                    .println(&format!("// {}", java_name(&self.class_name(index)?)));
+            }
+            AttributeInfo::NestHost(host) => {
+                let host = self.constant(host.host_class_index.index)?;
+                self.out.println(&format!("NestHost: {}", host));
+            }
+            AttributeInfo::NestMembers(members) => {
+                self.class_list("NestMembers:", &members.classes)?
+            }
+            AttributeInfo::Record(record) => {
+                self.out.println("Record:");
+                self.out.indent();
+                for component in &record.components {
+                    self.record_component(component)?;
+                }
+                self.out.outdent();
+            }
+            AttributeInfo::PermittedSubclasses(subclasses) => {
+                self.class_list("PermittedSubclasses:", &subclasses.classes)?
+            }
+            AttributeInfo::Unknown(unknown) => {
+                let name = self.utf8(attribute.attribute_name_index.index)?;
+                self.out
+                    .println(&format!("  {}: length = 0x{:X}", name, unknown.bytes.len()));
+                self.out.print("   ");
+                for (i, byte) in unknown.bytes.iter().enumerate() {
+                    self.out.print(&format!("{:02X}", byte));
+                    if i % 16 == 15 {
+                        self.out.println("");
+                        self.out.print("   ");
+                    } else {
+                        self.out.print(" ");
+                    }
+                }
+                self.out.println("");
+            }
+        }
+        Ok(())
+    }
+
+    fn class_list(
+        &mut self,
+        title: &str,
+        classes: &[crate::structs::Index<crate::structs::const_types::Class>],
+    ) -> Result<()> {
+        self.out.println(title);
+        self.out.indent();
+        for class in classes {
+            let name = self.string_value(class.index)?;
+            self.out.println(&name);
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    /// `(start_pc, length, slot, name_index, descriptor_index)` of each variable
+    fn local_variables(
+        &mut self,
+        title: &str,
+        variables: Vec<(u16, u16, u16, u16, u16)>,
+    ) -> Result<()> {
+        self.out.println(title);
+        self.out.indent();
+        self.out.println("Start  Length  Slot  Name   Signature");
+        for (start, length, slot, name, descriptor) in variables {
+            self.out.println(&format!(
+                "{:>5} {:>7} {:>5} {:>5}   {}",
+                start,
+                length,
+                slot,
+                self.string_value(name)?,
+                self.string_value(descriptor)?
+            ));
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn record_component(&mut self, component: &RecordComponent) -> Result<()> {
+        let component_type = match component.signature(self.pool)? {
+            Some(signature) => reference_type(&signature),
+            None => {
+                let descriptor = self.utf8(component.descriptor_index.index)?;
+                field_type(&crate::descriptor::FieldType::parse(descriptor)?)
+            }
+        };
+        let name = self.utf8(component.name_index.index)?;
+        self.out
+            .println(&format!("{} {};", java_name(&component_type), name));
+        self.out.indent();
+        let descriptor = self.utf8(component.descriptor_index.index)?;
+        self.out.println(&format!("descriptor: {}", descriptor));
+        self.attributes(&component.attributes, None)?;
+        self.out.println("");
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn module(&mut self, module: &Module) -> Result<()> {
+        self.out.println("Module:");
+        self.out.indent();
+        let flags = module.flags.bits();
+        self.out
+            .print(&format!("#{},{:x}", module.name_index.index, flags));
+        self.out.tab();
+        self.out.print(&format!(
+            "// {}",
+            self.string_value(module.name_index.index)?
+        ));
+        for (mask, name) in [
+            (0x0020, "ACC_OPEN"),
+            (0x8000, "ACC_MANDATED"),
+            (0x1000, "ACC_SYNTHETIC"),
+        ] {
+            if flags & mask != 0 {
+                self.out.print(&format!(" {}", name));
+            }
+        }
+        self.out.println("");
+        self.version(module.version_index.index)?;
+
+        self.out.print(&module.requires.len().to_string());
+        self.out.tab();
+        self.out.println("// requires");
+        self.out.indent();
+        for requires in &module.requires {
+            let index = requires.requires_index.index;
+            let flags = requires.flags.bits();
+            self.out.print(&format!("#{},{:x}", index, flags));
+            self.out.tab();
+            self.out.print(&format!("// {}", self.string_value(index)?));
+            for (mask, name) in [
+                (0x0020, "ACC_TRANSITIVE"),
+                (0x0040, "ACC_STATIC_PHASE"),
+                (0x1000, "ACC_SYNTHETIC"),
+                (0x8000, "ACC_MANDATED"),
+            ] {
+                if flags & mask != 0 {
+                    self.out.print(&format!(" {}", name));
+                }
+            }
+            self.out.println("");
+            self.version(requires.version_index.index)?;
+        }
+        self.out.outdent();
+
+        self.out.print(&module.exports.len().to_string());
+        self.out.tab();
+        self.out.println("// exports");
+        self.out.indent();
+        for exports in &module.exports {
+            let to: Vec<u16> = exports.to_index.iter().map(|index| index.index).collect();
+            self.exports(exports.exports_index.index, exports.flags.bits(), &to)?;
+        }
+        self.out.outdent();
+
+        self.out.print(&module.opens.len().to_string());
+        self.out.tab();
+        self.out.println("// opens");
+        self.out.indent();
+        for opens in &module.opens {
+            let to: Vec<u16> = opens.to_index.iter().map(|index| index.index).collect();
+            self.exports(opens.opens_index.index, opens.flags.bits(), &to)?;
+        }
+        self.out.outdent();
+
+        self.out.print(&module.uses.len().to_string());
+        self.out.tab();
+        self.out.println("// uses");
+        self.out.indent();
+        for uses in &module.uses {
+            let index = uses.uses_index.index;
+            self.out.print(&format!("#{}", index));
+            self.out.tab();
+            self.out
+                .println(&format!("// {}", self.string_value(index)?));
+        }
+        self.out.outdent();
+
+        self.out.print(&module.provides.len().to_string());
+        self.out.tab();
+        self.out.println("// provides");
+        self.out.indent();
+        for provides in &module.provides {
+            let index = provides.provides_index.index;
+            self.out.print(&format!("#{}", index));
+            self.out.tab();
+            self.out.println(&format!(
+                "// {} with ... {}",
+                self.string_value(index)?,
+                provides.with_index.len()
+            ));
+            self.out.indent();
+            for with in &provides.with_index {
+                self.out.print(&format!("#{}", with.index));
+                self.out.tab();
+                self.out
+                    .println(&format!("// ... with {}", self.string_value(with.index)?));
+            }
+            self.out.outdent();
+        }
+        self.out.outdent();
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn version(&mut self, index: u16) -> Result<()> {
+        self.out.print(&format!("#{}", index));
+        if index != 0 {
+            self.out.tab();
+            self.out.print(&format!("// {}", self.string_value(index)?));
+        }
+        self.out.println("");
+        Ok(())
+    }
+
+    /// An `exports` or `opens` directive
+    fn exports(&mut self, index: u16, flags: u16, to: &[u16]) -> Result<()> {
+        self.out.print(&format!("#{},{:x}", index, flags));
+        self.out.tab();
+        self.out.print(&format!("// {}", self.string_value(index)?));
+        for (mask, name) in [(0x8000, "ACC_MANDATED"), (0x1000, "ACC_SYNTHETIC")] {
+            if flags & mask != 0 {
+                self.out.print(&format!(" {}", name));
+            }
+        }
+        if to.is_empty() {
+            self.out.println("");
+            return Ok(());
+        }
+        self.out.println(&format!(" to ... {}", to.len()));
+        self.out.indent();
+        for &to in to {
+            self.out.print(&format!("#{}", to));
+            self.out.tab();
+            self.out
+                .println(&format!("// ... to {}", self.string_value(to)?));
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn code(&mut self, code: &Code, method: Option<&Method>) -> Result<()> {
+        self.out.println("Code:");
+        self.out.indent();
+        let args_size = match method {
+            Some(method) => {
+                let parameters = method.descriptor(self.pool)?.parameters.len();
+                let this = method.access_flags.bits() & super::ACC_STATIC == 0;
+                (parameters + this as usize).to_string()
+            }
+            None => "?".to_string(),
+        };
+        self.out.println(&format!(
+            "stack={}, locals={}, args_size={}",
+            code.max_stack, code.max_locals, args_size
+        ));
+        for instruction in &code.code {
+            self.instruction(instruction.offset, &instruction.instruction)?;
+        }
+        if !code.exception_table.is_empty() {
+            self.out.println("Exception table:");
+            self.out.indent();
+            self.out.println(" from    to  target type");
+            for entry in &code.exception_table {
+                self.out.print(&format!(
+                    " {:>5} {:>5} {:>5}   ",
+                    entry.start_pc, entry.end_pc, entry.handler_pc
+                ));
+                match entry.catch_type.index {
+                    0 => self.out.println("any"),
+                    index => {
+                        let name = self.string_value(index)?;
+                        self.out.println(&format!("Class {}", name));
+                    }
+                }
+            }
+            self.out.outdent();
+        }
+        self.attributes(&code.attributes, method)?;
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn instruction(&mut self, offset: u32, instruction: &Instruction) -> Result<()> {
+        let mnemonic = match instruction {
+            Instruction::wide(wide) => format!("{:?}_w", wide.opcode()),
+            instruction => format!("{:?}", instruction.opcode())
+                .trim_end_matches('_')
+                .to_string(),
+        };
+        self.out.print(&format!("{:>4}: {:<13} ", offset, mnemonic));
+        let target = |relative: i32| offset as i64 + relative as i64;
+        match instruction {
+            Instruction::bipush(value) => self.out.print(&value.to_string()),
+            Instruction::sipush(value) => self.out.print(&value.to_string()),
+            Instruction::ldc(index)
+            | Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => self.constant_operand(index.index, None)?,
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => self.constant_operand(index.index, None)?,
+            Instruction::invokevirtual(index) => self.constant_operand(index.index, None)?,
+            Instruction::invokeinterface(index, count) => {
+                self.constant_operand(index.index, Some(*count))?
+            }
+            // The two bytes after the index are always zero
+            Instruction::invokedynamic(index) => self.invokedynamic(index.index)?,
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index) => self.constant_operand(index.index, None)?,
+            Instruction::multianewarray(index, dimensions) => {
+                self.constant_operand(index.index, Some(*dimensions))?
+            }
+            Instruction::iload(slot)
+            | Instruction::lload(slot)
+            | Instruction::fload(slot)
+            | Instruction::dload(slot)
+            | Instruction::aload(slot)
+            | Instruction::istore(slot)
+            | Instruction::lstore(slot)
+            | Instruction::fstore(slot)
+            | Instruction::dstore(slot)
+            | Instruction::astore(slot)
+            | Instruction::ret(slot) => self.out.print(&slot.to_string()),
+            Instruction::iinc(slot, value) => self.out.print(&format!("{}, {}", slot, value)),
+            Instruction::wide(Wide::iinc(slot, value)) => {
+                self.out.print(&format!("{}, {}", slot, value))
+            }
+            Instruction::wide(wide) => self.out.print(&wide.index().to_string()),
+            Instruction::ifeq(relative)
+            | Instruction::ifne(relative)
+            | Instruction::iflt(relative)
+            | Instruction::ifge(relative)
+            | Instruction::ifgt(relative)
+            | Instruction::ifle(relative)
+            | Instruction::if_icmpeq(relative)
+            | Instruction::if_icmpne(relative)
+            | Instruction::if_icmplt(relative)
+            | Instruction::if_icmpge(relative)
+            | Instruction::if_icmpgt(relative)
+            | Instruction::if_icmple(relative)
+            | Instruction::if_acmpeq(relative)
+            | Instruction::if_acmpne(relative)
+            | Instruction::goto(relative)
+            | Instruction::jsr(relative)
+            | Instruction::ifnull(relative)
+            | Instruction::ifnonnull(relative) => {
+                self.out.print(&target(*relative as i32).to_string())
+            }
+            Instruction::goto_w(relative) | Instruction::jsr_w(relative) => {
+                self.out.print(&target(*relative).to_string())
+            }
+            Instruction::newarray(array_type) => {
+                let name = format!("{:?}", array_type);
+                let name = name.trim_start_matches("T_").to_lowercase();
+                self.out.print(&format!(" {}", name));
+            }
+            Instruction::tableswitch(switch) => {
+                self.out
+                    .print(&format!("{{ // {} to {}", switch.low, switch.high));
+                self.out.indent += SWITCH_INDENT;
+                for (key, relative) in (switch.low..).zip(&switch.offsets) {
+                    self.out
+                        .print(&format!("\n{:>12}: {}", key, target(*relative)));
+                }
+                self.out
+                    .print(&format!("\n     default: {}\n}}", target(switch.default)));
+                self.out.indent -= SWITCH_INDENT;
+            }
+            Instruction::lookupswitch(switch) => {
+                self.out.print(&format!("{{ // {}", switch.pairs.len()));
+                self.out.indent += SWITCH_INDENT;
+                for pair in &switch.pairs {
+                    self.out
+                        .print(&format!("\n{:>12}: {}", pair.key, target(pair.offset)));
+                }
+                self.out
+                    .print(&format!("\n     default: {}\n}}", target(switch.default)));
+                self.out.indent -= SWITCH_INDENT;
+            }
+            _ => {}
+        }
+        self.out.println("");
+        Ok(())
+    }
+
+    /// `#7  // Field java/lang/System.out:Ljava/io/PrintStream;`, with a second operand for
+    /// `invokeinterface`, `invokedynamic` and `multianewarray`
+    fn constant_operand(&mut self, index: u16, value: Option<u8>) -> Result<()> {
+        match value {
+            Some(value) => self.out.print(&format!("#{},  {}", index, value)),
+            None => self.out.print(&format!("#{}", index)),
+        }
+        self.out.tab();
+        let constant = self.constant(index)?;
+        self.out.print(&format!("// {}", constant));
+        Ok(())
+    }
+
+    /// Call sites that cannot be decoded as a string concatenation keep the comment javap gives
+    /// them, as the option only adds to the output
+    fn invokedynamic(&mut self, index: u16) -> Result<()> {
+        if self.options.decode_concat {
+            let decoded = self
+                .resolver
+                .dynamic(index)
+                .and_then(|site| Concatenation::decode(index, &site));
+            if let Ok(Some(concatenation)) = decoded {
+                self.out.print(&format!("#{},  0", index));
+                self.out.tab();
+                self.out
+                    .print(&format!("// String concatenation {}", concatenation));
+                return Ok(());
+            }
+        }
+        self.constant_operand(index, Some(0))
+    }
+
+    fn stack_map_frame(&mut self, frame: &StackMapFrame) -> Result<()> {
+        let name = match frame {
+            StackMapFrame::Same { .. } => "same",
+            StackMapFrame::SameLocals1StackItem { .. } => "same_locals_1_stack_item",
+            StackMapFrame::SameLocals1StackItemExtended { .. } => {
+                "same_locals_1_stack_item_frame_extended"
+            }
+            StackMapFrame::Chop { .. } => "chop",
+            StackMapFrame::SameExtended { .. } => "same_frame_extended",
+            StackMapFrame::Append { .. } => "append",
+            StackMapFrame::Full { .. } => "full_frame",
+        };
+        self.out.println(&format!(
+            "frame_type = {} /* {} */",
+            frame.frame_type()?,
+            name
+        ));
+        self.out.indent();
+        if !matches!(
+            frame,
+            StackMapFrame::Same { .. } | StackMapFrame::SameLocals1StackItem { .. }
+        ) {
+            self.out
+                .println(&format!("offset_delta = {}", frame.offset_delta()));
+        }
+        match frame {
+            StackMapFrame::SameLocals1StackItem { stack, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                self.verification_types("stack", std::slice::from_ref(stack))?
+            }
+            StackMapFrame::Append { locals, .. } => self.verification_types("locals", locals)?,
+            StackMapFrame::Full { locals, stack, .. } => {
+                self.verification_types("locals", locals)?;
+                self.verification_types("stack", stack)?;
+            }
+            _ => {}
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    /// `locals = [ class Annotated, int ]`
+    fn verification_types(&mut self, name: &str, types: &[VerificationTypeInfo]) -> Result<()> {
+        self.out.print(&format!("{} = [", name));
+        for (i, info) in types.iter().enumerate() {
+            let text = match info {
+                VerificationTypeInfo::Top => "top".to_string(),
+                VerificationTypeInfo::Integer => "int".to_string(),
+                VerificationTypeInfo::Float => "float".to_string(),
+                VerificationTypeInfo::Long => "long".to_string(),
+                VerificationTypeInfo::Double => "double".to_string(),
+                VerificationTypeInfo::Null => "null".to_string(),
+                VerificationTypeInfo::UninitializedThis => "this".to_string(),
+                VerificationTypeInfo::Object(class) => self.constant(class.index)?,
+                VerificationTypeInfo::Uninitialized(offset) => {
+                    format!("uninitialized {}", offset)
+                }
+            };
+            self.out.print(&format!(" {}", text));
+            self.out.print(if i == types.len() - 1 { " " } else { "," });
+        }
+        self.out.println("]");
+        Ok(())
+    }
+
+    fn annotations(&mut self, title: &str, annotations: &[Annotation]) -> Result<()> {
+        self.out.println(title);
+        self.out.indent();
+        for (i, annotation) in annotations.iter().enumerate() {
+            self.out
+                .println(&format!("{}: {}", i, self.annotation(annotation)));
+            self.out.indent();
+            self.resolved_annotation(annotation)?;
+            self.out.outdent();
+            self.out.println("");
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn parameter_annotations(
+        &mut self,
+        title: &str,
+        parameters: &[ParameterAnnotations],
+    ) -> Result<()> {
+        self.out.println(title);
+        self.out.indent();
+        for (parameter, annotations) in parameters.iter().enumerate() {
+            self.out.println(&format!("parameter {}: ", parameter));
+            self.out.indent();
+            for (i, annotation) in annotations.annotations.iter().enumerate() {
+                self.out
+                    .println(&format!("{}: {}", i, self.annotation(annotation)));
+                self.out.indent();
+                self.resolved_annotation(annotation)?;
+                self.out.outdent();
+                self.out.println("");
+            }
+            self.out.outdent();
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    fn type_annotations(&mut self, title: &str, annotations: &[TypeAnnotation]) -> Result<()> {
+        self.out.println(title);
+        self.out.indent();
+        for (i, annotation) in annotations.iter().enumerate() {
+            self.out.println(&format!(
+                "{}: {}: {}",
+                i,
+                self.annotation(&annotation.annotation),
+                type_annotation_position(annotation)
+            ));
+            self.out.indent();
+            self.resolved_annotation(&annotation.annotation)?;
+            self.out.outdent();
+            self.out.println("");
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    /// The annotation with constant pool indices, e.g. `#26(#27=I#28)`
+    fn annotation(&self, annotation: &Annotation) -> String {
+        let pairs: Vec<String> = annotation
+            .element_value_pairs
+            .iter()
+            .map(|pair| {
+                format!(
+                    "#{}={}",
+                    pair.element_name_index.index,
+                    self.element_value(&pair.value)
+                )
+            })
+            .collect();
+        format!("#{}({})", annotation.type_index.index, pairs.join(","))
+    }
+
+    fn element_value(&self, value: &ElementValue) -> String {
+        let tag = value.tag() as char;
+        match value {
+            ElementValue::Byte(index)
+            | ElementValue::Char(index)
+            | ElementValue::Int(index)
+            | ElementValue::Short(index)
+            | ElementValue::Boolean(index) => format!("{}#{}", tag, index.index),
+            ElementValue::Double(index) => format!("{}#{}", tag, index.index),
+            ElementValue::Float(index) => format!("{}#{}", tag, index.index),
+            ElementValue::Long(index) => format!("{}#{}", tag, index.index),
+            ElementValue::String(index) | ElementValue::Class(index) => {
+                format!("{}#{}", tag, index.index)
+            }
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "{}#{}.#{}",
+                tag, type_name_index.index, const_name_index.index
+            ),
+            ElementValue::Annotation(annotation) => format!("@{}", self.annotation(annotation)),
+            ElementValue::Array(values) => {
+                let values: Vec<String> = values.iter().map(|v| self.element_value(v)).collect();
+                format!("[{}]", values.join(","))
+            }
+        }
+    }
+
+    /// The annotation with its constants looked up, one element per line
+    fn resolved_annotation(&mut self, annotation: &Annotation) -> Result<()> {
+        let type_name = crate::descriptor::FieldType::parse(annotation.type_name(self.pool)?)?;
+        self.out.print(&java_name(&field_type(&type_name)));
+        if annotation.element_value_pairs.is_empty() {
+            return Ok(());
+        }
+        self.out.println("(");
+        self.out.indent();
+        for pair in &annotation.element_value_pairs {
+            let name = self.string_value(pair.element_name_index.index)?;
+            self.out.print(&format!("{}=", name));
+            self.resolved_element_value(&pair.value)?;
+            self.out.println("");
+        }
+        self.out.outdent();
+        self.out.print(")");
+        Ok(())
+    }
+
+    fn resolved_element_value(&mut self, value: &ElementValue) -> Result<()> {
+        let text = match value {
+            ElementValue::Byte(index) => format!("(byte) {}", index.get(self.pool)?.value),
+            ElementValue::Char(index) => {
+                let value = index.get(self.pool)?.value as u16;
+                format!("'{}'", String::from_utf16_lossy(&[value]))
+            }
+            ElementValue::Double(index) => {
+                format!("{}d", java_double(index.get(self.pool)?.value))
+            }
+            ElementValue::Float(index) => format!("{}f", java_float(index.get(self.pool)?.value)),
+            ElementValue::Int(index) => index.get(self.pool)?.value.to_string(),
+            ElementValue::Long(index) => format!("{}l", index.get(self.pool)?.value),
+            ElementValue::Short(index) => format!("(short) {}", index.get(self.pool)?.value),
+            ElementValue::Boolean(index) => (index.get(self.pool)?.value != 0).to_string(),
+            ElementValue::String(index) => format!("\"{}\"", self.string_value(index.index)?),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "{}.{}",
+                self.string_value(type_name_index.index)?,
+                self.string_value(const_name_index.index)?
+            ),
+            ElementValue::Class(index) => format!("class {}", self.string_value(index.index)?),
+            ElementValue::Annotation(annotation) => {
+                self.out.print("@");
+                return self.resolved_annotation(annotation);
+            }
+            ElementValue::Array(values) => {
+                self.out.print("[");
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        self.out.print(",");
+                    }
+                    self.resolved_element_value(value)?;
+                }
+                self.out.print("]");
+                return Ok(());
+            }
+        };
+        self.out.print(&text);
+        Ok(())
+    }
+}
+
+/// e.g. `LOCAL_VARIABLE, {start_pc=2, length=23, index=3}, location=[TYPE_ARGUMENT(0)]`
+fn type_annotation_position(annotation: &TypeAnnotation) -> String {
+    let mut result = target_type_name(annotation.target_type).to_string();
+    match &annotation.target_info {
+        TargetInfo::TypeParameter {
+            type_parameter_index,
+        } => result.push_str(&format!(", param_index={}", type_parameter_index)),
+        // The superclass is stored as 65535
+        TargetInfo::Supertype { supertype_index } => {
+            let index = match *supertype_index {
+                u16::MAX => -1,
+                index => index as i32,
+            };
+            result.push_str(&format!(", type_index={}", index));
+        }
+        TargetInfo::TypeParameterBound {
+            type_parameter_index,
+            bound_index,
+        } => result.push_str(&format!(
+            ", param_index={}, bound_index={}",
+            type_parameter_index, bound_index
+        )),
+        TargetInfo::Empty => {}
+        TargetInfo::FormalParameter {
+            formal_parameter_index,
+        } => result.push_str(&format!(", param_index={}", formal_parameter_index)),
+        TargetInfo::Throws { throws_type_index } => {
+            result.push_str(&format!(", type_index={}", throws_type_index))
+        }
+        TargetInfo::Localvar { table } => {
+            let entries: Vec<String> = table
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "start_pc={}, length={}, index={}",
+                        entry.start_pc, entry.length, entry.index
+                    )
+                })
+                .collect();
+            result.push_str(&format!(", {{{}}}", entries.join("; ")));
+        }
+        TargetInfo::Catch {
+            exception_table_index,
+        } => result.push_str(&format!(", exception_index={}", exception_table_index)),
+        TargetInfo::Offset { offset } => result.push_str(&format!(", offset={}", offset)),
+        TargetInfo::TypeArgument {
+            offset,
+            type_argument_index,
+        } => result.push_str(&format!(
+            ", offset={}, type_index={}",
+            offset, type_argument_index
+        )),
+    }
+    if !annotation.target_path.path.is_empty() {
+        let path: Vec<String> = annotation
+            .target_path
+            .path
+            .iter()
+            .map(|entry| match entry.type_path_kind {
+                TypePathKind::Array => "ARRAY".to_string(),
+                TypePathKind::Nested => "INNER_TYPE".to_string(),
+                TypePathKind::WildcardBound => "WILDCARD".to_string(),
+                TypePathKind::TypeArgument => {
+                    format!("TYPE_ARGUMENT({})", entry.type_argument_index)
+                }
+            })
+            .collect();
+        result.push_str(&format!(", location=[{}]", path.join(", ")));
+    }
+    result
+}
+
+fn target_type_name(target_type: TargetType) -> &'static str {
+    match target_type {
+        TargetType::ClassTypeParameter => "CLASS_TYPE_PARAMETER",
+        TargetType::MethodTypeParameter => "METHOD_TYPE_PARAMETER",
+        TargetType::Supertype => "CLASS_EXTENDS",
+        TargetType::ClassTypeParameterBound => "CLASS_TYPE_PARAMETER_BOUND",
+        TargetType::MethodTypeParameterBound => "METHOD_TYPE_PARAMETER_BOUND",
+        TargetType::Field => "FIELD",
+        TargetType::MethodReturn => "METHOD_RETURN",
+        TargetType::MethodReceiver => "METHOD_RECEIVER",
+        TargetType::MethodFormalParameter => "METHOD_FORMAL_PARAMETER",
+        TargetType::Throws => "THROWS",
+        TargetType::LocalVariable => "LOCAL_VARIABLE",
+        TargetType::ResourceVariable => "RESOURCE_VARIABLE",
+        TargetType::ExceptionParameter => "EXCEPTION_PARAMETER",
+        TargetType::Instanceof => "INSTANCEOF",
+        TargetType::New => "NEW",
+        TargetType::ConstructorReference => "CONSTRUCTOR_REFERENCE",
+        TargetType::MethodReference => "METHOD_REFERENCE",
+        TargetType::Cast => "CAST",
+        TargetType::ConstructorInvocationTypeArgument => "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT",
+        TargetType::MethodInvocationTypeArgument => "METHOD_INVOCATION_TYPE_ARGUMENT",
+        TargetType::ConstructorReferenceTypeArgument => "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT",
+        TargetType::MethodReferenceTypeArgument => "METHOD_REFERENCE_TYPE_ARGUMENT",
+    }
+}
@@ -0,0 +1,359 @@
+//! The constant pool listing, and the way javap renders constants in comments
+
+use crate::{
+    error::Result,
+    structs::{
+        const_types::{MethodHandleReferenceKind, Utf8},
+        ConstItem, Index,
+    },
+};
+
+use super::Disassembler;
+
+impl Disassembler<'_> {
+    pub(super) fn item(&self, index: u16) -> Result<&ConstItem> {
+        self.pool.get(&Index::<ConstItem>::new(index))
+    }
+
+    pub(super) fn utf8(&self, index: u16) -> Result<&str> {
+        Ok(&self.pool.get(&Index::<Utf8>::new(index))?.str)
+    }
+
+    /// The internal name of a `Class` constant
+    pub(super) fn class_name(&self, index: u16) -> Result<String> {
+        let class = self
+            .pool
+            .get(&Index::<crate::structs::const_types::Class>::new(index))?;
+        Ok(self.utf8(class.name_index.index)?.to_string())
+    }
+
+    /// The name of a `Module` or `Package` constant, or a `Utf8` constant itself
+    pub(super) fn name(&self, index: u16) -> Result<String> {
+        let name_index = match self.item(index)? {
+            ConstItem::Module(module) => module.name_index.index,
+            ConstItem::Package(package) => package.name_index.index,
+            _ => index,
+        };
+        Ok(self.utf8(name_index)?.to_string())
+    }
+
+    pub(super) fn constant_pool(&mut self) -> Result<()> {
+        self.out.println("Constant pool:");
+        self.out.indent();
+        let width = self.pool.entries.len().to_string().len() + 1;
+        for (index, entry) in self.pool.entries.iter().enumerate() {
+            // Index 0 and the slots after a Long or Double
+            let entry = match entry {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let index = format!("#{}", index);
+            self.out.print(&format!(
+                "{:>width$} = {:<18} ",
+                index,
+                tag_name(entry),
+                width = width
+            ));
+            let operands = match entry {
+                ConstItem::Utf8(_)
+                | ConstItem::Integer(_)
+                | ConstItem::Float(_)
+                | ConstItem::Long(_)
+                | ConstItem::Double(_) => {
+                    let value = self.item_value(entry)?;
+                    self.out.println(&value);
+                    continue;
+                }
+                ConstItem::Class(class) => format!("#{}", class.name_index.index),
+                ConstItem::StringJ(string) => format!("#{}", string.string_index.index),
+                ConstItem::FieldRef(field) => format!(
+                    "#{}.#{}",
+                    field.class_index.index, field.name_and_type_index.index
+                ),
+                ConstItem::MethodRef(method) => format!(
+                    "#{}.#{}",
+                    method.class_index.index, method.name_and_type_index.index
+                ),
+                ConstItem::InterfaceMethodRef(method) => format!(
+                    "#{}.#{}",
+                    method.class_index.index, method.name_and_type_index.index
+                ),
+                ConstItem::NameAndType(name_and_type) => format!(
+                    "#{}:#{}",
+                    name_and_type.name_index.index, name_and_type.descriptor_index.index
+                ),
+                ConstItem::MethodHandle(handle) => format!(
+                    "{}:#{}",
+                    handle.reference_kind.clone() as u8,
+                    handle.reference_index.index()
+                ),
+                ConstItem::MethodType(method_type) => {
+                    format!("#{}", method_type.descriptor_index.index)
+                }
+                ConstItem::Dynamic(dynamic) => format!(
+                    "#{}:#{}",
+                    dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index.index
+                ),
+                ConstItem::InvokeDynamic(dynamic) => format!(
+                    "#{}:#{}",
+                    dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index.index
+                ),
+                ConstItem::Module(module) => format!("#{}", module.name_index.index),
+                ConstItem::Package(package) => format!("#{}", package.name_index.index),
+            };
+            self.out.print(&operands);
+            self.out.tab();
+            // javap leaves an extra space before method types
+            let separator = if matches!(entry, ConstItem::MethodType(_)) {
+                "//  "
+            } else {
+                "// "
+            };
+            let value = self.item_value(entry)?;
+            self.out.println(&format!("{}{}", separator, value));
+        }
+        self.out.outdent();
+        Ok(())
+    }
+
+    /// The constant as javap shows it in comments, e.g. `java/lang/Object."<init>":()V`
+    pub(super) fn string_value(&self, index: u16) -> Result<String> {
+        self.item_value(self.item(index)?)
+    }
+
+    fn item_value(&self, item: &ConstItem) -> Result<String> {
+        Ok(match item {
+            ConstItem::Utf8(utf8) => escape(&utf8.str),
+            ConstItem::Integer(integer) => integer.value.to_string(),
+            ConstItem::Float(float) => format!("{}f", java_float(float.value)),
+            ConstItem::Long(long) => format!("{}l", long.value),
+            ConstItem::Double(double) => format!("{}d", java_double(double.value)),
+            ConstItem::Class(class) => check_name(self.utf8(class.name_index.index)?),
+            ConstItem::StringJ(string) => self.string_value(string.string_index.index)?,
+            ConstItem::FieldRef(field) => {
+                self.member_value(field.class_index.index, field.name_and_type_index.index)?
+            }
+            ConstItem::MethodRef(method) => {
+                self.member_value(method.class_index.index, method.name_and_type_index.index)?
+            }
+            ConstItem::InterfaceMethodRef(method) => {
+                self.member_value(method.class_index.index, method.name_and_type_index.index)?
+            }
+            ConstItem::NameAndType(name_and_type) => format!(
+                "{}:{}",
+                check_name(self.utf8(name_and_type.name_index.index)?),
+                self.utf8(name_and_type.descriptor_index.index)?
+            ),
+            ConstItem::MethodHandle(handle) => format!(
+                "{} {}",
+                reference_kind_name(&handle.reference_kind),
+                self.string_value(handle.reference_index.index())?
+            ),
+            ConstItem::MethodType(method_type) => {
+                self.utf8(method_type.descriptor_index.index)?.to_string()
+            }
+            ConstItem::Dynamic(dynamic) => format!(
+                "#{}:{}",
+                dynamic.bootstrap_method_attr_index,
+                self.string_value(dynamic.name_and_type_index.index)?
+            ),
+            ConstItem::InvokeDynamic(dynamic) => format!(
+                "#{}:{}",
+                dynamic.bootstrap_method_attr_index,
+                self.string_value(dynamic.name_and_type_index.index)?
+            ),
+            ConstItem::Module(module) => check_name(self.utf8(module.name_index.index)?),
+            ConstItem::Package(package) => check_name(self.utf8(package.name_index.index)?),
+        })
+    }
+
+    fn member_value(&self, class_index: u16, name_and_type_index: u16) -> Result<String> {
+        Ok(format!(
+            "{}.{}",
+            check_name(&self.class_name(class_index)?),
+            self.string_value(name_and_type_index)?
+        ))
+    }
+
+    /// The constant with its kind, e.g. `Method java/io/PrintStream.println:(Ljava/lang/String;)V`.
+    /// Members of the class itself are shown without the class name
+    pub(super) fn constant(&self, index: u16) -> Result<String> {
+        if index == 0 {
+            return Ok("#0".to_string());
+        }
+        let item = self.item(index)?;
+        let this_class = self.class.this_class.index;
+        let value = match item {
+            ConstItem::FieldRef(field) if field.class_index.index == this_class => {
+                self.string_value(field.name_and_type_index.index)?
+            }
+            ConstItem::MethodRef(method) if method.class_index.index == this_class => {
+                self.string_value(method.name_and_type_index.index)?
+            }
+            ConstItem::InterfaceMethodRef(method) if method.class_index.index == this_class => {
+                self.string_value(method.name_and_type_index.index)?
+            }
+            item => self.item_value(item)?,
+        };
+        let kind = match item {
+            ConstItem::Utf8(_) => "Utf8",
+            ConstItem::Integer(_) => "int",
+            ConstItem::Float(_) => "float",
+            ConstItem::Long(_) => "long",
+            ConstItem::Double(_) => "double",
+            ConstItem::Class(_) => "class",
+            ConstItem::StringJ(_) => "String",
+            ConstItem::FieldRef(_) => "Field",
+            ConstItem::MethodRef(_) => "Method",
+            ConstItem::InterfaceMethodRef(_) => "InterfaceMethod",
+            ConstItem::NameAndType(_) => "NameAndType",
+            ConstItem::MethodHandle(_) => "MethodHandle",
+            ConstItem::MethodType(_) => "MethodType",
+            ConstItem::Dynamic(_) => "Dynamic",
+            ConstItem::InvokeDynamic(_) => "InvokeDynamic",
+            ConstItem::Module(_) => "Module",
+            ConstItem::Package(_) => "Package",
+        };
+        Ok(format!("{} {}", kind, value))
+    }
+}
+
+/// The tag name in the constant pool listing
+fn tag_name(item: &ConstItem) -> &'static str {
+    match item {
+        ConstItem::StringJ(_) => "String",
+        ConstItem::FieldRef(_) => "Fieldref",
+        ConstItem::MethodRef(_) => "Methodref",
+        ConstItem::InterfaceMethodRef(_) => "InterfaceMethodref",
+        item => item.name(),
+    }
+}
+
+pub(super) fn reference_kind_name(kind: &MethodHandleReferenceKind) -> &'static str {
+    match kind {
+        MethodHandleReferenceKind::GetField => "REF_getField",
+        MethodHandleReferenceKind::GetStatic => "REF_getStatic",
+        MethodHandleReferenceKind::PutField => "REF_putField",
+        MethodHandleReferenceKind::PutStatic => "REF_putStatic",
+        MethodHandleReferenceKind::InvokeVirtual => "REF_invokeVirtual",
+        MethodHandleReferenceKind::InvokeStatic => "REF_invokeStatic",
+        MethodHandleReferenceKind::InvokeSpecial => "REF_invokeSpecial",
+        MethodHandleReferenceKind::NewInvokeSpecial => "REF_newInvokeSpecial",
+        MethodHandleReferenceKind::InvokeInterface => "REF_invokeInterface",
+    }
+}
+
+/// Escapes a string constant the way javap does, e.g. a tab becomes `\t`
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{c}' => result.push_str("\\f"),
+            '"' => result.push_str("\\\""),
+            '\'' => result.push_str("\\'"),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Quotes names that are not `/`-separated Java identifiers, such as `"<init>"` and `"[I"`
+fn check_name(name: &str) -> String {
+    if name.is_empty() {
+        return "\"\"".to_string();
+    }
+    let mut previous = '/';
+    for c in name.chars() {
+        let valid = if previous == '/' {
+            is_identifier_start(c)
+        } else {
+            c == '/' || is_identifier_part(c)
+        };
+        if !valid {
+            let mut quoted = String::from("\"");
+            for c in name.chars() {
+                match c {
+                    '\\' | '"' => {
+                        quoted.push('\\');
+                        quoted.push(c);
+                    }
+                    '\n' => quoted.push_str("\\n"),
+                    '\t' => quoted.push_str("\\t"),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            return quoted;
+        }
+        previous = c;
+    }
+    name.to_string()
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    // Java also allows the ignorable control characters in identifiers
+    is_identifier_start(c)
+        || c.is_numeric()
+        || matches!(c, '\u{0}'..='\u{8}' | '\u{e}'..='\u{1b}' | '\u{7f}'..='\u{9f}')
+}
+
+/// Formats like Java's `Float.toString`, e.g. `1.0`, `0.001` and `1.0E10`
+pub(super) fn java_float(value: f32) -> String {
+    if value.is_nan() || value.is_infinite() || value == 0.0 {
+        return java_double(value as f64);
+    }
+    java_decimal(value.is_sign_negative(), &format!("{:e}", value.abs()))
+}
+
+/// Formats like Java's `Double.toString`
+pub(super) fn java_double(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        return format!("{}Infinity", sign);
+    }
+    if value == 0.0 {
+        let sign = if value.is_sign_negative() { "-" } else { "" };
+        return format!("{}0.0", sign);
+    }
+    java_decimal(value.is_sign_negative(), &format!("{:e}", value.abs()))
+}
+
+/// Lays out the shortest digits of a value, given in Rust's `1.5e-7` notation, the way Java does:
+/// plain between 10^-3 and 10^7, otherwise in scientific notation with an `E`
+fn java_decimal(negative: bool, scientific: &str) -> String {
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let digits = mantissa.replace('.', "");
+    let mut result = String::from(if negative { "-" } else { "" });
+    if (-3..7).contains(&exponent) {
+        if exponent < 0 {
+            result.push_str("0.");
+            result.push_str(&"0".repeat((-exponent - 1) as usize));
+            result.push_str(&digits);
+        } else {
+            let point = exponent as usize + 1;
+            if digits.len() > point {
+                result.push_str(&format!("{}.{}", &digits[..point], &digits[point..]));
+            } else {
+                result.push_str(&format!("{}{}.0", digits, "0".repeat(point - digits.len())));
+            }
+        }
+    } else {
+        let fraction = if digits.len() > 1 { &digits[1..] } else { "0" };
+        result.push_str(&format!("{}.{}E{}", &digits[..1], fraction, exponent));
+    }
+    result
+}
@@ -0,0 +1,7 @@
+/// What the disassembler adds to the output of javap. The default matches javap exactly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisassembleOptions {
+    /// Comments string concatenations with the expression they were compiled from, like
+    /// `"Hello " + arg0 + "!"`, instead of the `InvokeDynamic` constant
+    pub decode_concat: bool,
+}
@@ -1,5 +1,8 @@
-use std::{io, string::FromUtf8Error};
+use std::{fmt::Display, io, string::FromUtf8Error};
 use thiserror::Error;
+use zip::result::ZipError;
+
+use crate::structs::MajorVersion;
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -18,8 +21,182 @@ pub enum ParseError {
     #[error("Invalid access flags: {0:#X}")]
     InvalidAccessFlags(u16),
 
+    #[error("Illegal access flags {flags:#X}: {reason}")]
+    IllegalAccessFlags { flags: u16, reason: String },
+
+    #[error("Invalid constant pool tag {tag} at index {index}")]
+    InvalidConstantTag { tag: u8, index: u16 },
+
+    #[error("Constant pool index {index} is out of range for a pool of {count} entries")]
+    ConstantIndexOutOfRange { index: u16, count: usize },
+
+    #[error("Constant pool index {0} does not refer to a usable entry")]
+    UnusableConstantIndex(u16),
+
+    #[error("Constant pool index {index} refers to {found}, expected {expected}")]
+    WrongConstantType {
+        index: u16,
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    #[error("{0} bytes left over after reading the attribute")]
+    TrailingBytes(usize),
+
+    #[error("Zip error: {0}")]
+    Zip(ZipError),
+
+    #[error("Invalid manifest at line {line}: {reason}")]
+    InvalidManifest { line: usize, reason: String },
+
+    #[error("Main-Class {0} is not present in the archive")]
+    MissingMainClass(String),
+
+    #[error("{entry} targets {major:?} which is newer than its release directory {release}")]
+    VersionedEntryTooNew {
+        entry: String,
+        release: u16,
+        major: MajorVersion,
+    },
+
+    #[error("Invalid descriptor {descriptor}: {reason}")]
+    InvalidDescriptor { descriptor: String, reason: String },
+
+    #[error("Invalid signature {signature}: {reason}")]
+    InvalidSignature { signature: String, reason: String },
+
+    #[error("Bootstrap method {index} is out of range for {count} bootstrap methods")]
+    BootstrapMethodOutOfRange { index: u16, count: usize },
+
+    #[error("Invalid method handle: {0}")]
+    InvalidMethodHandle(String),
+
+    #[error("The constant pool is full, a {0} entry does not fit")]
+    ConstantPoolFull(&'static str),
+
+    #[error("A Utf8 constant of {0} bytes does not fit in the 65535 bytes a class file allows")]
+    Utf8TooLong(usize),
+
+    #[error("{what} are nested more than {limit} levels deep")]
+    TooDeeplyNested { what: &'static str, limit: usize },
+
+    #[error("Invalid assembly at line {line}: {reason}")]
+    InvalidAssembly { line: usize, reason: String },
+
     #[error("{0}")]
     Other(String),
+
+    /// Where in the class file another error happened
+    #[error("{source} at offset {offset:#X}{}", format_path(.path))]
+    Context {
+        offset: usize,
+        path: String,
+        source: Box<ParseError>,
+    },
+}
+
+fn format_path(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", path)
+    }
+}
+
+impl ParseError {
+    /// The offset in the class file at which reading failed, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseError::Context { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The logical path of the item that failed, e.g. `methods[12].attributes[0](Code)`
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ParseError::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The name of the variant, e.g. `InvalidConstantTag`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParseError::IO(_) => "IO",
+            ParseError::InvalidMagicNumber(_) => "InvalidMagicNumber",
+            ParseError::Unrecognized(..) => "Unrecognized",
+            ParseError::Utf8Error(_) => "Utf8Error",
+            ParseError::InvalidAccessFlags(_) => "InvalidAccessFlags",
+            ParseError::IllegalAccessFlags { .. } => "IllegalAccessFlags",
+            ParseError::InvalidConstantTag { .. } => "InvalidConstantTag",
+            ParseError::ConstantIndexOutOfRange { .. } => "ConstantIndexOutOfRange",
+            ParseError::UnusableConstantIndex(_) => "UnusableConstantIndex",
+            ParseError::WrongConstantType { .. } => "WrongConstantType",
+            ParseError::TrailingBytes(_) => "TrailingBytes",
+            ParseError::Zip(_) => "Zip",
+            ParseError::InvalidManifest { .. } => "InvalidManifest",
+            ParseError::MissingMainClass(_) => "MissingMainClass",
+            ParseError::VersionedEntryTooNew { .. } => "VersionedEntryTooNew",
+            ParseError::InvalidDescriptor { .. } => "InvalidDescriptor",
+            ParseError::InvalidSignature { .. } => "InvalidSignature",
+            ParseError::BootstrapMethodOutOfRange { .. } => "BootstrapMethodOutOfRange",
+            ParseError::InvalidMethodHandle(_) => "InvalidMethodHandle",
+            ParseError::ConstantPoolFull(_) => "ConstantPoolFull",
+            ParseError::Utf8TooLong(_) => "Utf8TooLong",
+            ParseError::TooDeeplyNested { .. } => "TooDeeplyNested",
+            ParseError::InvalidAssembly { .. } => "InvalidAssembly",
+            ParseError::Other(_) => "Other",
+            ParseError::Context { .. } => "Context",
+        }
+    }
+
+    /// The error without any context
+    pub fn root(&self) -> &ParseError {
+        match self {
+            ParseError::Context { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// Prefixes the path with `segment`, the offset is only used if there is no context yet
+    pub(crate) fn in_path(self, segment: impl Display, offset: usize) -> Self {
+        match self {
+            ParseError::Context {
+                offset,
+                path,
+                source,
+            } => {
+                let path = if path.is_empty() {
+                    segment.to_string()
+                } else {
+                    format!("{}.{}", segment, path)
+                };
+                ParseError::Context {
+                    offset,
+                    path,
+                    source,
+                }
+            }
+            err => ParseError::Context {
+                offset,
+                path: segment.to_string(),
+                source: Box::new(err),
+            },
+        }
+    }
+
+    /// Records the offset without adding to the path, unless there is context already
+    pub(crate) fn at_offset(self, offset: usize) -> Self {
+        match self {
+            err @ ParseError::Context { .. } => err,
+            err => ParseError::Context {
+                offset,
+                path: String::new(),
+                source: Box::new(err),
+            },
+        }
+    }
 }
 
 impl From<io::Error> for ParseError {
@@ -34,4 +211,10 @@ impl From<FromUtf8Error> for ParseError {
     }
 }
 
+impl From<ZipError> for ParseError {
+    fn from(err: ZipError) -> Self {
+        ParseError::Zip(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ParseError>;
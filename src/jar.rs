@@ -0,0 +1,308 @@
+use std::{
+    cell::OnceCell,
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+use crate::{
+    error::ParseError,
+    reader::options::ParseOptions,
+    structs::{Class, MajorVersion},
+    Result,
+};
+
+pub mod manifest;
+
+pub use manifest::Manifest;
+use manifest::MANIFEST_PATH;
+
+const CLASS_SUFFIX: &str = ".class";
+const VERSIONS_PREFIX: &str = "META-INF/versions/";
+/// The first release that looks at `META-INF/versions`
+const FIRST_VERSIONED_RELEASE: u16 = 9;
+/// Buffers for entries are allocated up front up to this many bytes, and grow as read beyond it
+const MAX_SIZE_HINT: u64 = 1 << 20;
+
+/// A jar (or any zip) archive whose class entries are parsed on demand
+pub struct Jar<R: Read + Seek> {
+    archive: ZipArchive<R>,
+    options: ParseOptions,
+    // Parsed on first use, `Some(None)` when the archive has no manifest
+    manifest: Option<Option<Manifest>>,
+    // Every class by internal name, listed on first use
+    class_index: OnceCell<HashMap<String, ClassVariants>>,
+}
+
+/// Where the variants of a class are stored
+#[derive(Debug, Default)]
+struct ClassVariants {
+    // Whether there is an entry outside of `META-INF/versions`
+    base: bool,
+    // The releases with an overlay, in ascending order
+    releases: Vec<u16>,
+}
+
+impl Jar<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> Jar<R> {
+    pub fn new(reader: R) -> Result<Self> {
+        let archive = ZipArchive::new(reader)?;
+        Ok(Self {
+            archive,
+            options: ParseOptions::default(),
+            manifest: None,
+            class_index: OnceCell::new(),
+        })
+    }
+
+    /// Sets the options used to parse every class, which are strict unless set
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The names of all entries, including directories
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.archive.file_names()
+    }
+
+    /// The entry names of all class files, e.g. `com/example/Main.class`. Overlays under
+    /// `META-INF/versions/N/` are left out, see `class_releases`
+    pub fn class_entries(&self) -> Vec<String> {
+        self.class_names()
+            .into_iter()
+            .map(|name| format!("{}{}", name, CLASS_SUFFIX))
+            .collect()
+    }
+
+    /// The internal names of all classes, e.g. `com/example/Main`, without overlays
+    pub fn class_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .class_index()
+            .iter()
+            .filter(|(_, variants)| variants.base)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Lists the classes and their overlays the first time it is called
+    fn class_index(&self) -> &HashMap<String, ClassVariants> {
+        self.class_index.get_or_init(|| {
+            let mut classes: HashMap<String, ClassVariants> = HashMap::new();
+            for entry in self.entries() {
+                let name = match entry.strip_suffix(CLASS_SUFFIX) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let overlay = match name.strip_prefix(VERSIONS_PREFIX) {
+                    Some(rest) => rest.split_once('/'),
+                    None => {
+                        classes.entry(name.to_string()).or_default().base = true;
+                        continue;
+                    }
+                };
+                let overlay = overlay
+                    .and_then(|(release, name)| Some((release.parse::<u16>().ok()?, name)))
+                    .filter(|(release, _)| *release >= FIRST_VERSIONED_RELEASE);
+                if let Some((release, name)) = overlay {
+                    let variants = classes.entry(name.to_string()).or_default();
+                    variants.releases.push(release);
+                }
+            }
+            for variants in classes.values_mut() {
+                variants.releases.sort_unstable();
+            }
+            classes
+        })
+    }
+
+    /// The names of all entries that are neither classes nor directories
+    pub fn resource_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .entries()
+            .filter(|name| !name.ends_with(CLASS_SUFFIX) && !name.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Parses the class with the given internal name, e.g. `com/example/Main`
+    pub fn class(&mut self, name: &str) -> Result<Option<Class>> {
+        self.class_entry(&format!("{}{}", name, CLASS_SUFFIX))
+    }
+
+    /// Parses the class stored in the given entry
+    pub fn class_entry(&mut self, entry: &str) -> Result<Option<Class>> {
+        match self.archive.by_name(entry) {
+            Ok(file) => Ok(Some(Class::read_with(
+                &mut BufReader::new(file),
+                &self.options,
+            )?)),
+            Err(ZipError::FileNotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Reads the raw bytes of an entry
+    pub fn resource(&mut self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self.archive.by_name(name) {
+            Ok(mut file) => {
+                // The size in the archive is not checked until the entry is read, so it only
+                // serves as a hint for entries of a plausible size
+                let capacity = file.size().min(MAX_SIZE_HINT) as usize;
+                let mut bytes = Vec::with_capacity(capacity);
+                file.read_to_end(&mut bytes)?;
+                Ok(Some(bytes))
+            }
+            Err(ZipError::FileNotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Parses `META-INF/MANIFEST.MF`, if the archive has one
+    pub fn manifest(&mut self) -> Result<Option<Manifest>> {
+        Ok(self.cached_manifest()?.cloned())
+    }
+
+    /// The manifest, parsed the first time it is needed
+    fn cached_manifest(&mut self) -> Result<Option<&Manifest>> {
+        if self.manifest.is_none() {
+            let manifest = match self.resource(MANIFEST_PATH)? {
+                Some(bytes) => Some(Manifest::parse(&bytes)?),
+                None => None,
+            };
+            self.manifest = Some(manifest);
+        }
+        Ok(self.manifest.as_ref().and_then(Option::as_ref))
+    }
+
+    /// Parses the class named by the manifest's `Main-Class`,
+    /// failing if it is declared but not present in the archive
+    pub fn main_class(&mut self) -> Result<Option<Class>> {
+        let main_class = match self.cached_manifest()? {
+            Some(manifest) => match manifest.main_class() {
+                Some(main_class) => main_class.trim().to_string(),
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        match self.class(&main_class.replace('.', "/"))? {
+            Some(class) => Ok(Some(class)),
+            None => Err(ParseError::MissingMainClass(main_class)),
+        }
+    }
+
+    /// Whether the manifest declares `Multi-Release: true`
+    pub fn is_multi_release(&mut self) -> Result<bool> {
+        Ok(self
+            .cached_manifest()?
+            .is_some_and(|manifest| manifest.multi_release()))
+    }
+
+    /// The releases with an overlay of the given class under
+    /// `META-INF/versions/N/`, in ascending order
+    pub fn class_releases(&self, name: &str) -> Vec<u16> {
+        self.class_index()
+            .get(name)
+            .map(|variants| variants.releases.clone())
+            .unwrap_or_default()
+    }
+
+    /// The entry a JVM running the given release would load for a class.
+    /// Overlays are only considered in multi-release jars
+    pub fn resolve_class_entry(
+        &mut self,
+        name: &str,
+        release: MajorVersion,
+    ) -> Result<Option<String>> {
+        if self.is_multi_release()? {
+            let overlay = self
+                .class_releases(name)
+                .into_iter()
+                .rev()
+                .find(|overlay| *overlay <= release.release());
+            if let Some(overlay) = overlay {
+                return Ok(Some(format!(
+                    "{}{}/{}{}",
+                    VERSIONS_PREFIX, overlay, name, CLASS_SUFFIX
+                )));
+            }
+        }
+        let base = self
+            .class_index()
+            .get(name)
+            .is_some_and(|variants| variants.base);
+        Ok(base.then(|| format!("{}{}", name, CLASS_SUFFIX)))
+    }
+
+    /// Parses the variant of a class a JVM running the given release would load,
+    /// failing if an overlay is compiled for a newer release than its directory
+    pub fn class_for_release(
+        &mut self,
+        name: &str,
+        release: MajorVersion,
+    ) -> Result<Option<Class>> {
+        let entry = match self.resolve_class_entry(name, release)? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let class = match self.class_entry(&entry)? {
+            Some(class) => class,
+            None => return Ok(None),
+        };
+
+        let overlay = entry
+            .strip_prefix(VERSIONS_PREFIX)
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(overlay, _)| overlay.parse::<u16>().ok());
+        if let Some(overlay) = overlay {
+            let limit = MajorVersion::from_release(overlay)?;
+            if class.version.major as u16 > limit as u16 {
+                return Err(ParseError::VersionedEntryTooNew {
+                    entry,
+                    release: overlay,
+                    major: class.version.major,
+                });
+            }
+        }
+        Ok(Some(class))
+    }
+
+    /// Iterates over all classes, each one is only parsed when it is reached
+    pub fn classes(&mut self) -> Classes<'_, R> {
+        let entries = self.class_entries().into_iter();
+        Classes { jar: self, entries }
+    }
+}
+
+pub struct Classes<'a, R: Read + Seek> {
+    jar: &'a mut Jar<R>,
+    entries: std::vec::IntoIter<String>,
+}
+
+impl<'a, R: Read + Seek> Iterator for Classes<'a, R> {
+    /// The entry name and the parsed class
+    type Item = Result<(String, Class)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+        let class = self.jar.class_entry(&entry).transpose()?;
+        Some(class.map(|class| (entry, class)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
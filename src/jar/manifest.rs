@@ -0,0 +1,236 @@
+use crate::{error::ParseError, Result};
+
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// Lines of a manifest may not be longer than this many bytes,
+/// longer values are continued on the next line after a single space
+const MAX_LINE_LENGTH: usize = 72;
+
+/// An ordered list of `name: value` pairs, names are case-insensitive
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct ManifestAttributes {
+    pub entries: Vec<(String, String)>,
+}
+
+impl ManifestAttributes {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.entries.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A per-entry section, started by a `Name:` header
+#[derive(Debug, PartialEq, Clone)]
+pub struct ManifestSection {
+    pub name: String,
+    pub attributes: ManifestAttributes,
+}
+
+/// The parsed contents of `META-INF/MANIFEST.MF`
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Manifest {
+    pub main: ManifestAttributes,
+    pub sections: Vec<ManifestSection>,
+}
+
+impl Manifest {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut manifest = Manifest::default();
+        // The section currently being read, `None` while in the main section
+        let mut section: Option<ManifestSection> = None;
+        let mut in_main = true;
+
+        for (number, line) in logical_lines(bytes)? {
+            let invalid = |reason: &str| ParseError::InvalidManifest {
+                line: number,
+                reason: reason.to_string(),
+            };
+
+            if line.is_empty() {
+                // A blank line ends the current section
+                if let Some(section) = section.take() {
+                    manifest.sections.push(section);
+                }
+                in_main = false;
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid("expected `name: value`"))?;
+            let value = value
+                .strip_prefix(' ')
+                .ok_or_else(|| invalid("expected a space after the colon"))?;
+            if name.is_empty() {
+                return Err(invalid("empty attribute name"));
+            }
+
+            if !in_main && section.is_none() {
+                if !name.eq_ignore_ascii_case("Name") {
+                    return Err(invalid("section does not start with a `Name` attribute"));
+                }
+                section = Some(ManifestSection {
+                    name: String::new(),
+                    attributes: ManifestAttributes::default(),
+                });
+            }
+            let attributes = match section.as_mut() {
+                Some(section) => &mut section.attributes,
+                None => &mut manifest.main,
+            };
+            attributes.insert(name, value);
+        }
+
+        if let Some(section) = section.take() {
+            manifest.sections.push(section);
+        }
+        for section in manifest.sections.iter_mut() {
+            section.name = section.attributes.get("Name").unwrap_or("").to_string();
+        }
+
+        Ok(manifest)
+    }
+
+    /// Serializes the manifest, wrapping lines at 72 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_attributes(&mut out, &self.main);
+        out.extend_from_slice(b"\r\n");
+        for section in &self.sections {
+            write_attributes(&mut out, &section.attributes);
+            out.extend_from_slice(b"\r\n");
+        }
+        out
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.main.get(name)
+    }
+
+    pub fn section(&self, name: &str) -> Option<&ManifestSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    pub fn main_class(&self) -> Option<&str> {
+        self.get("Main-Class")
+    }
+
+    pub fn class_path(&self) -> Vec<&str> {
+        split_list(self.get("Class-Path"))
+    }
+
+    pub fn automatic_module_name(&self) -> Option<&str> {
+        self.get("Automatic-Module-Name")
+    }
+
+    pub fn multi_release(&self) -> bool {
+        self.get("Multi-Release")
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+    }
+
+    /// The `module/package` pairs listed in `Add-Opens`
+    pub fn add_opens(&self) -> Vec<&str> {
+        split_list(self.get("Add-Opens"))
+    }
+}
+
+/// Joins continuation lines to the line they continue and decodes the result, with the number of
+/// the line each one starts on. Lines are wrapped at a byte count, which can split a multi-byte
+/// character, so they are only decoded once joined.
+fn logical_lines(bytes: &[u8]) -> Result<Vec<(usize, String)>> {
+    let mut lines: Vec<(usize, Vec<u8>)> = Vec::new();
+    for (i, line) in physical_lines(bytes).into_iter().enumerate() {
+        match (line.strip_prefix(b" "), lines.last_mut()) {
+            (Some(rest), Some((_, previous))) if !previous.is_empty() => {
+                previous.extend_from_slice(rest)
+            }
+            (Some(_), _) => {
+                return Err(ParseError::InvalidManifest {
+                    line: i + 1,
+                    reason: "continuation line without an attribute".to_string(),
+                })
+            }
+            (None, _) => lines.push((i + 1, line.to_vec())),
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(number, line)| {
+            String::from_utf8(line)
+                .map(|line| (number, line))
+                .map_err(|_| ParseError::InvalidManifest {
+                    line: number,
+                    reason: "invalid UTF-8".to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Splits at each line ending, which is CR LF, LF or a lone CR
+fn physical_lines(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => lines.push(&bytes[start..i]),
+            b'\r' => {
+                lines.push(&bytes[start..i]);
+                if bytes.get(i + 1) == Some(&b'\n') {
+                    i += 1;
+                }
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 1;
+        start = i;
+    }
+    lines.push(&bytes[start..]);
+    lines
+}
+
+fn split_list(value: Option<&str>) -> Vec<&str> {
+    value.map_or_else(Vec::new, |value| value.split_whitespace().collect())
+}
+
+fn write_attributes(out: &mut Vec<u8>, attributes: &ManifestAttributes) {
+    for (name, value) in &attributes.entries {
+        let line = format!("{}: {}", name, value);
+        let mut rest = line.as_str();
+        let mut limit = MAX_LINE_LENGTH;
+        while rest.len() > limit {
+            // Never split inside a multi-byte character
+            let mut split = limit;
+            while !rest.is_char_boundary(split) {
+                split -= 1;
+            }
+            out.extend_from_slice(&rest.as_bytes()[..split]);
+            out.extend_from_slice(b"\r\n ");
+            rest = &rest[split..];
+            // The leading space counts towards the line length
+            limit = MAX_LINE_LENGTH - 1;
+        }
+        out.extend_from_slice(rest.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+}
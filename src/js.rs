@@ -0,0 +1,609 @@
+//! Converts the parsed structures into plain JavaScript values and exports the parser to Node.
+//! Constant pool references are resolved, so an `Index<Utf8>` becomes the string it points to.
+//! Enums become `{ type: "Variant", ... }` objects, and unit enums become the variant's name.
+
+pub mod tasks;
+
+use neon::{
+    context::{Context, FunctionContext},
+    handle::Handle,
+    object::Object,
+    result::{JsResult, NeonResult},
+    types::{buffer::TypedArray, JsArray, JsBuffer, JsObject, JsString, JsUndefined, JsValue},
+};
+
+use crate::{
+    error::ParseError,
+    jar::{manifest::ManifestAttributes, Jar, Manifest},
+    reader::options::{ParseMode, ParseOptions},
+    structs::{
+        attributes::{ElementValue, StackMapFrame, TargetInfo, VerificationTypeInfo},
+        const_pool::TryFromItem,
+        const_types::{MethodHandleReference, Utf8},
+        instructions::{Instruction, OffsetInstruction, Wide},
+        Class, ConstPool, Index,
+    },
+};
+
+pub trait ToJs {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue>;
+}
+
+macro_rules! impl_to_js_number {
+    ($($name:ident),*) => {
+        $(
+            impl ToJs for $name {
+                fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, _pool: &ConstPool) -> JsResult<'a, JsValue> {
+                    Ok(cx.number(*self as f64).upcast())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_js_number! {
+    u8, u16, u32, usize,
+    i8, i16, i32, isize,
+    f32, f64
+}
+
+// 64-bit integers do not fit in a JS number, so they are passed as decimal strings
+macro_rules! impl_to_js_string {
+    ($($name:ident),*) => {
+        $(
+            impl ToJs for $name {
+                fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, _pool: &ConstPool) -> JsResult<'a, JsValue> {
+                    Ok(cx.string(self.to_string()).upcast())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_js_string! {
+    u64, u128, i64, i128,
+    char,
+    String
+}
+
+impl ToJs for bool {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, _pool: &ConstPool) -> JsResult<'a, JsValue> {
+        Ok(cx.boolean(*self).upcast())
+    }
+}
+
+impl<T: ToJs> ToJs for Vec<T> {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let array = cx.empty_array();
+        for (i, item) in self.iter().enumerate() {
+            let value = item.to_js(cx, pool)?;
+            array.set(cx, i as u32, value)?;
+        }
+        Ok(array.upcast())
+    }
+}
+
+impl<T: ToJs> ToJs for Option<T> {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        match self {
+            Some(x) => x.to_js(cx, pool),
+            None => Ok(cx.null().upcast()),
+        }
+    }
+}
+
+impl<T: TryFromItem + ToJs> ToJs for Index<T> {
+    /// The entry the index points to, or `null` for an optional index of 0 or a broken reference
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        match self.get(pool) {
+            Ok(item) => item.to_js(cx, pool),
+            Err(_) => Ok(cx.null().upcast()),
+        }
+    }
+}
+
+impl ToJs for MethodHandleReference {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let (kind, value) = match self {
+            MethodHandleReference::Field(index) => ("Field", index.to_js(cx, pool)?),
+            MethodHandleReference::Method(index) => ("Method", index.to_js(cx, pool)?),
+            MethodHandleReference::InterfaceMethod(index) => {
+                ("InterfaceMethod", index.to_js(cx, pool)?)
+            }
+        };
+        tagged(cx, kind, &[("value", value)])
+    }
+}
+
+impl ToJs for Utf8 {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, _pool: &ConstPool) -> JsResult<'a, JsValue> {
+        Ok(cx.string(&self.str).upcast())
+    }
+}
+
+/// Builds `{ type: kind, ...fields }`
+pub fn tagged<'a, C: Context<'a>>(
+    cx: &mut C,
+    kind: &str,
+    fields: &[(&str, Handle<'a, JsValue>)],
+) -> JsResult<'a, JsValue> {
+    let object = cx.empty_object();
+    let kind = cx.string(kind);
+    object.set(cx, "type", kind)?;
+    for (name, value) in fields {
+        object.set(cx, *name, *value)?;
+    }
+    Ok(object.upcast())
+}
+
+impl ToJs for StackMapFrame {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let offset_delta = self.offset_delta().to_js(cx, pool)?;
+        match self {
+            StackMapFrame::Same { .. } => tagged(cx, "Same", &[("offset_delta", offset_delta)]),
+            StackMapFrame::SameLocals1StackItem { stack, .. } => {
+                let stack = stack.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "SameLocals1StackItem",
+                    &[("offset_delta", offset_delta), ("stack", stack)],
+                )
+            }
+            StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                let stack = stack.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "SameLocals1StackItemExtended",
+                    &[("offset_delta", offset_delta), ("stack", stack)],
+                )
+            }
+            StackMapFrame::Chop { k, .. } => {
+                let k = k.to_js(cx, pool)?;
+                tagged(cx, "Chop", &[("k", k), ("offset_delta", offset_delta)])
+            }
+            StackMapFrame::SameExtended { .. } => {
+                tagged(cx, "SameExtended", &[("offset_delta", offset_delta)])
+            }
+            StackMapFrame::Append { locals, .. } => {
+                let locals = locals.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "Append",
+                    &[("offset_delta", offset_delta), ("locals", locals)],
+                )
+            }
+            StackMapFrame::Full { locals, stack, .. } => {
+                let locals = locals.to_js(cx, pool)?;
+                let stack = stack.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "Full",
+                    &[
+                        ("offset_delta", offset_delta),
+                        ("locals", locals),
+                        ("stack", stack),
+                    ],
+                )
+            }
+        }
+    }
+}
+
+impl ToJs for VerificationTypeInfo {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        match self {
+            VerificationTypeInfo::Top => tagged(cx, "Top", &[]),
+            VerificationTypeInfo::Integer => tagged(cx, "Integer", &[]),
+            VerificationTypeInfo::Float => tagged(cx, "Float", &[]),
+            VerificationTypeInfo::Double => tagged(cx, "Double", &[]),
+            VerificationTypeInfo::Long => tagged(cx, "Long", &[]),
+            VerificationTypeInfo::Null => tagged(cx, "Null", &[]),
+            VerificationTypeInfo::UninitializedThis => tagged(cx, "UninitializedThis", &[]),
+            VerificationTypeInfo::Object(class) => {
+                let class = class.to_js(cx, pool)?;
+                tagged(cx, "Object", &[("value", class)])
+            }
+            VerificationTypeInfo::Uninitialized(offset) => {
+                let offset = offset.to_js(cx, pool)?;
+                tagged(cx, "Uninitialized", &[("value", offset)])
+            }
+        }
+    }
+}
+
+impl ToJs for ElementValue {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let (kind, value) = match self {
+            ElementValue::Byte(index) => ("Byte", index.to_js(cx, pool)?),
+            ElementValue::Char(index) => ("Char", index.to_js(cx, pool)?),
+            ElementValue::Double(index) => ("Double", index.to_js(cx, pool)?),
+            ElementValue::Float(index) => ("Float", index.to_js(cx, pool)?),
+            ElementValue::Int(index) => ("Int", index.to_js(cx, pool)?),
+            ElementValue::Long(index) => ("Long", index.to_js(cx, pool)?),
+            ElementValue::Short(index) => ("Short", index.to_js(cx, pool)?),
+            ElementValue::Boolean(index) => ("Boolean", index.to_js(cx, pool)?),
+            ElementValue::String(index) => ("String", index.to_js(cx, pool)?),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => {
+                let type_name_index = type_name_index.to_js(cx, pool)?;
+                let const_name_index = const_name_index.to_js(cx, pool)?;
+                return tagged(
+                    cx,
+                    "Enum",
+                    &[
+                        ("type_name_index", type_name_index),
+                        ("const_name_index", const_name_index),
+                    ],
+                );
+            }
+            ElementValue::Class(index) => ("Class", index.to_js(cx, pool)?),
+            ElementValue::Annotation(annotation) => ("Annotation", annotation.to_js(cx, pool)?),
+            ElementValue::Array(values) => ("Array", values.to_js(cx, pool)?),
+        };
+        tagged(cx, kind, &[("value", value)])
+    }
+}
+
+impl ToJs for TargetInfo {
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        match self {
+            TargetInfo::TypeParameter {
+                type_parameter_index,
+            } => {
+                let index = type_parameter_index.to_js(cx, pool)?;
+                tagged(cx, "TypeParameter", &[("type_parameter_index", index)])
+            }
+            TargetInfo::Supertype { supertype_index } => {
+                let index = supertype_index.to_js(cx, pool)?;
+                tagged(cx, "Supertype", &[("supertype_index", index)])
+            }
+            TargetInfo::TypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => {
+                let type_parameter_index = type_parameter_index.to_js(cx, pool)?;
+                let bound_index = bound_index.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "TypeParameterBound",
+                    &[
+                        ("type_parameter_index", type_parameter_index),
+                        ("bound_index", bound_index),
+                    ],
+                )
+            }
+            TargetInfo::Empty => tagged(cx, "Empty", &[]),
+            TargetInfo::FormalParameter {
+                formal_parameter_index,
+            } => {
+                let index = formal_parameter_index.to_js(cx, pool)?;
+                tagged(cx, "FormalParameter", &[("formal_parameter_index", index)])
+            }
+            TargetInfo::Throws { throws_type_index } => {
+                let index = throws_type_index.to_js(cx, pool)?;
+                tagged(cx, "Throws", &[("throws_type_index", index)])
+            }
+            TargetInfo::Localvar { table } => {
+                let table = table.to_js(cx, pool)?;
+                tagged(cx, "Localvar", &[("table", table)])
+            }
+            TargetInfo::Catch {
+                exception_table_index,
+            } => {
+                let index = exception_table_index.to_js(cx, pool)?;
+                tagged(cx, "Catch", &[("exception_table_index", index)])
+            }
+            TargetInfo::Offset { offset } => {
+                let offset = offset.to_js(cx, pool)?;
+                tagged(cx, "Offset", &[("offset", offset)])
+            }
+            TargetInfo::TypeArgument {
+                offset,
+                type_argument_index,
+            } => {
+                let offset = offset.to_js(cx, pool)?;
+                let index = type_argument_index.to_js(cx, pool)?;
+                tagged(
+                    cx,
+                    "TypeArgument",
+                    &[("offset", offset), ("type_argument_index", index)],
+                )
+            }
+        }
+    }
+}
+
+/// Builds `{ opcode, operands }`
+fn instruction<'a, C: Context<'a>>(
+    cx: &mut C,
+    opcode: &str,
+    operands: &[Handle<'a, JsValue>],
+) -> JsResult<'a, JsValue> {
+    let object = cx.empty_object();
+    let opcode = cx.string(opcode);
+    object.set(cx, "opcode", opcode)?;
+    let array = cx.empty_array();
+    for (i, operand) in operands.iter().enumerate() {
+        array.set(cx, i as u32, *operand)?;
+    }
+    object.set(cx, "operands", array)?;
+    Ok(object.upcast())
+}
+
+impl ToJs for Instruction {
+    /// `{ opcode, operands }`, switches have a single `{ default, low, high, offsets }` or
+    /// `{ default, pairs }` operand and `wide` has the widened instruction as its operand
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let opcode = format!("{:?}", self.opcode());
+        // Opcodes that are Rust keywords have a trailing underscore
+        let opcode = opcode.trim_end_matches('_');
+        let operands = match self {
+            Instruction::bipush(value) => vec![value.to_js(cx, pool)?],
+            Instruction::sipush(value) => vec![value.to_js(cx, pool)?],
+            Instruction::ldc(index)
+            | Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => vec![index.to_js(cx, pool)?],
+            Instruction::iload(slot)
+            | Instruction::lload(slot)
+            | Instruction::fload(slot)
+            | Instruction::dload(slot)
+            | Instruction::aload(slot)
+            | Instruction::istore(slot)
+            | Instruction::lstore(slot)
+            | Instruction::fstore(slot)
+            | Instruction::dstore(slot)
+            | Instruction::astore(slot)
+            | Instruction::ret(slot) => vec![slot.to_js(cx, pool)?],
+            Instruction::iinc(slot, value) => {
+                vec![slot.to_js(cx, pool)?, value.to_js(cx, pool)?]
+            }
+            Instruction::ifeq(relative)
+            | Instruction::ifne(relative)
+            | Instruction::iflt(relative)
+            | Instruction::ifge(relative)
+            | Instruction::ifgt(relative)
+            | Instruction::ifle(relative)
+            | Instruction::if_icmpeq(relative)
+            | Instruction::if_icmpne(relative)
+            | Instruction::if_icmplt(relative)
+            | Instruction::if_icmpge(relative)
+            | Instruction::if_icmpgt(relative)
+            | Instruction::if_icmple(relative)
+            | Instruction::if_acmpeq(relative)
+            | Instruction::if_acmpne(relative)
+            | Instruction::goto(relative)
+            | Instruction::jsr(relative)
+            | Instruction::ifnull(relative)
+            | Instruction::ifnonnull(relative) => vec![relative.to_js(cx, pool)?],
+            Instruction::goto_w(relative) | Instruction::jsr_w(relative) => {
+                vec![relative.to_js(cx, pool)?]
+            }
+            Instruction::tableswitch(switch) => {
+                let object = cx.empty_object();
+                let default = switch.default.to_js(cx, pool)?;
+                object.set(cx, "default", default)?;
+                let low = switch.low.to_js(cx, pool)?;
+                object.set(cx, "low", low)?;
+                let high = switch.high.to_js(cx, pool)?;
+                object.set(cx, "high", high)?;
+                let offsets = switch.offsets.to_js(cx, pool)?;
+                object.set(cx, "offsets", offsets)?;
+                vec![object.upcast()]
+            }
+            Instruction::lookupswitch(switch) => {
+                let object = cx.empty_object();
+                let default = switch.default.to_js(cx, pool)?;
+                object.set(cx, "default", default)?;
+                let pairs = cx.empty_array();
+                for (i, pair) in switch.pairs.iter().enumerate() {
+                    let entry = cx.empty_object();
+                    let key = pair.key.to_js(cx, pool)?;
+                    entry.set(cx, "key", key)?;
+                    let offset = pair.offset.to_js(cx, pool)?;
+                    entry.set(cx, "offset", offset)?;
+                    pairs.set(cx, i as u32, entry)?;
+                }
+                object.set(cx, "pairs", pairs)?;
+                vec![object.upcast()]
+            }
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => vec![index.to_js(cx, pool)?],
+            Instruction::invokevirtual(index) => vec![index.to_js(cx, pool)?],
+            Instruction::invokeinterface(index, count) => {
+                vec![index.to_js(cx, pool)?, count.to_js(cx, pool)?]
+            }
+            Instruction::invokedynamic(index) => vec![index.to_js(cx, pool)?],
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index) => vec![index.to_js(cx, pool)?],
+            Instruction::newarray(atype) => vec![atype.to_js(cx, pool)?],
+            Instruction::wide(wide) => {
+                let opcode = format!("{:?}", wide.opcode());
+                let operands = match wide {
+                    Wide::iinc(slot, value) => {
+                        vec![slot.to_js(cx, pool)?, value.to_js(cx, pool)?]
+                    }
+                    _ => vec![wide.index().to_js(cx, pool)?],
+                };
+                vec![instruction(cx, &opcode, &operands)?]
+            }
+            Instruction::multianewarray(index, dimensions) => {
+                vec![index.to_js(cx, pool)?, dimensions.to_js(cx, pool)?]
+            }
+            _ => Vec::new(),
+        };
+        instruction(cx, opcode, &operands)
+    }
+}
+
+impl ToJs for OffsetInstruction {
+    /// The instruction with an extra `offset` property
+    fn to_js<'a, C: Context<'a>>(&self, cx: &mut C, pool: &ConstPool) -> JsResult<'a, JsValue> {
+        let object = self
+            .instruction
+            .to_js(cx, pool)?
+            .downcast_or_throw::<JsObject, _>(cx)?;
+        let offset = self.offset.to_js(cx, pool)?;
+        object.set(cx, "offset", offset)?;
+        Ok(object.upcast())
+    }
+}
+
+fn attributes_to_js<'a, C: Context<'a>>(
+    cx: &mut C,
+    attributes: &ManifestAttributes,
+) -> JsResult<'a, JsObject> {
+    let object = cx.empty_object();
+    for (name, value) in &attributes.entries {
+        let value = cx.string(value);
+        object.set(cx, name.as_str(), value)?;
+    }
+    Ok(object)
+}
+
+/// `{ main: { [name]: value }, sections: [{ name, attributes }] }`
+fn manifest_to_js<'a, C: Context<'a>>(cx: &mut C, manifest: &Manifest) -> JsResult<'a, JsValue> {
+    let object = cx.empty_object();
+    let main = attributes_to_js(cx, &manifest.main)?;
+    object.set(cx, "main", main)?;
+    let sections = cx.empty_array();
+    for (i, section) in manifest.sections.iter().enumerate() {
+        let entry = cx.empty_object();
+        let name = cx.string(&section.name);
+        entry.set(cx, "name", name)?;
+        let attributes = attributes_to_js(cx, &section.attributes)?;
+        entry.set(cx, "attributes", attributes)?;
+        sections.set(cx, i as u32, entry)?;
+    }
+    object.set(cx, "sections", sections)?;
+    Ok(object.upcast())
+}
+
+/// Throws a JS `Error` with the message of `err`, and its `kind`, `offset` and `path`
+pub fn throw_parse_error<'a, C: Context<'a>, T>(cx: &mut C, err: ParseError) -> NeonResult<T> {
+    let error = cx.error(err.to_string())?;
+    let kind = cx.string(err.root().kind());
+    error.set(cx, "kind", kind)?;
+    if let Some(offset) = err.offset() {
+        let offset = cx.number(offset as f64);
+        error.set(cx, "offset", offset)?;
+    }
+    if let Some(path) = err.path() {
+        let path = cx.string(path);
+        error.set(cx, "path", path)?;
+    }
+    cx.throw(error)
+}
+
+/// The options object passed as the argument at `i`, if any
+pub(crate) fn options_argument<'a>(
+    cx: &mut FunctionContext<'a>,
+    i: i32,
+) -> NeonResult<Option<Handle<'a, JsObject>>> {
+    match cx.argument_opt(i) {
+        Some(options) if !options.is_a::<JsUndefined, _>(cx) => {
+            Ok(Some(options.downcast_or_throw::<JsObject, _>(cx)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Reads `{ mode: "strict" | "lenient" }`, lenient unless told otherwise so that slightly broken
+/// classes can still be inspected
+pub(crate) fn parse_options<'a, C: Context<'a>>(
+    cx: &mut C,
+    options: Option<Handle<JsObject>>,
+) -> NeonResult<ParseOptions> {
+    let options = match options {
+        Some(options) => options,
+        None => return Ok(ParseOptions::lenient()),
+    };
+    let mode: Handle<JsValue> = options.get(cx, "mode")?;
+    if mode.is_a::<JsUndefined, _>(cx) {
+        return Ok(ParseOptions::lenient());
+    }
+    let mode = mode.downcast_or_throw::<JsString, _>(cx)?.value(cx);
+    let mode = match mode.as_str() {
+        "strict" => ParseMode::Strict,
+        "lenient" => ParseMode::Lenient,
+        _ => return cx.throw_type_error(format!("Unknown parse mode: {}", mode)),
+    };
+    Ok(ParseOptions { mode })
+}
+
+pub(crate) fn class_to_js<'a, C: Context<'a>>(cx: &mut C, class: &Class) -> JsResult<'a, JsValue> {
+    class.to_js(cx, &class.constant_pool)
+}
+
+/// `{ entry, class }`
+pub(crate) fn jar_class_to_js<'a, C: Context<'a>>(
+    cx: &mut C,
+    entry: &str,
+    class: &Class,
+) -> JsResult<'a, JsObject> {
+    let object = cx.empty_object();
+    let entry = cx.string(entry);
+    object.set(cx, "entry", entry)?;
+    let class = class_to_js(cx, class)?;
+    object.set(cx, "class", class)?;
+    Ok(object)
+}
+
+/// `{ manifest, classes }`
+pub(crate) fn jar_to_js<'a, C: Context<'a>>(
+    cx: &mut C,
+    manifest: Option<&Manifest>,
+    classes: Handle<JsArray>,
+) -> JsResult<'a, JsValue> {
+    let object = cx.empty_object();
+    let manifest = match manifest {
+        Some(manifest) => manifest_to_js(cx, manifest)?,
+        None => cx.null().upcast(),
+    };
+    object.set(cx, "manifest", manifest)?;
+    object.set(cx, "classes", classes)?;
+    Ok(object.upcast())
+}
+
+/// `parseClass(buffer, options?)`
+pub fn parse_class(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let buffer = cx.argument::<JsBuffer>(0)?;
+    let bytes = buffer.as_slice(&cx).to_vec();
+    let options = options_argument(&mut cx, 1)?;
+    let options = parse_options(&mut cx, options)?;
+    match Class::read_with(&mut bytes.as_slice(), &options) {
+        Ok(class) => class_to_js(&mut cx, &class),
+        Err(err) => throw_parse_error(&mut cx, err),
+    }
+}
+
+/// `parseJar(path, options?)`, returns `{ manifest, classes: [{ entry, class }] }`
+pub fn parse_jar(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let options = options_argument(&mut cx, 1)?;
+    let options = parse_options(&mut cx, options)?;
+    let read = || -> crate::Result<_> {
+        let mut jar = Jar::open(&path)?.with_options(options);
+        let manifest = jar.manifest()?;
+        let classes = jar.classes().collect::<crate::Result<Vec<_>>>()?;
+        Ok((manifest, classes))
+    };
+    let (manifest, classes) = match read() {
+        Ok(result) => result,
+        Err(err) => return throw_parse_error(&mut cx, err),
+    };
+
+    let array = cx.empty_array();
+    for (i, (entry, class)) in classes.iter().enumerate() {
+        let item = jar_class_to_js(&mut cx, entry, class)?;
+        array.set(&mut cx, i as u32, item)?;
+    }
+    jar_to_js(&mut cx, manifest.as_ref(), array)
+}
@@ -0,0 +1,316 @@
+//! Promise-returning variants of the parser that do the reading off the main thread.
+//! Classes are still converted to JS values on the main thread, but one jar entry at a time, so
+//! the event loop keeps running while a large jar is scanned.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Condvar, Mutex, OnceLock,
+    },
+};
+
+use neon::{
+    context::{Context, FunctionContext, TaskContext},
+    event::Channel,
+    handle::{Handle, Root},
+    object::Object,
+    result::{JsResult, NeonResult},
+    types::{
+        buffer::TypedArray, JsArray, JsBoolean, JsBuffer, JsFunction, JsObject, JsPromise,
+        JsString, JsUndefined, JsValue,
+    },
+};
+
+use crate::{
+    error::ParseError,
+    jar::{Jar, Manifest},
+    js::{
+        class_to_js, jar_class_to_js, jar_to_js, options_argument, parse_options, throw_parse_error,
+    },
+    reader::options::ParseOptions,
+    structs::Class,
+};
+
+/// `parseClassAsync(buffer, options?)`, reads the class on the Node worker pool
+pub fn parse_class_async(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let buffer = cx.argument::<JsBuffer>(0)?;
+    let bytes = buffer.as_slice(&cx).to_vec();
+    let options = options_argument(&mut cx, 1)?;
+    let options = parse_options(&mut cx, options)?;
+    let promise = cx
+        .task(move || Class::read_with(&mut bytes.as_slice(), &options))
+        .promise(|mut cx, class| match class {
+            Ok(class) => class_to_js(&mut cx, &class),
+            Err(err) => throw_parse_error(&mut cx, err),
+        });
+    Ok(promise)
+}
+
+/// How many jars are read at the same time, further calls wait for a free thread
+const SCAN_THREADS: usize = 2;
+
+/// How many classes a scan reads ahead of the main thread converting them
+const MAX_PENDING_CLASSES: usize = 16;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs `job` on one of the `SCAN_THREADS` threads shared by every scan, in the order of the calls
+fn spawn_scan(job: Job) {
+    static QUEUE: OnceLock<mpsc::Sender<Job>> = OnceLock::new();
+    let queue = QUEUE.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..SCAN_THREADS {
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                let job = match receiver.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+                // A panicking scan must not take the thread with it
+                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+            });
+        }
+        sender
+    });
+    // The receiver lives as long as the threads, which never stop
+    queue.send(job).expect("jar scan threads have stopped");
+}
+
+/// State shared between the thread reading a jar and the callbacks run on the main thread
+struct Scan {
+    cancelled: AtomicBool,
+    // Classes sent to the main thread that it has not converted yet
+    pending: Mutex<usize>,
+    converted: Condvar,
+    classes: Root<JsArray>,
+    on_progress: Option<Root<JsFunction>>,
+    // An exception thrown by `on_progress`, the promise is rejected with it. Only objects can be
+    // rooted, so it is kept as the single element of an array
+    exception: Mutex<Option<Root<JsArray>>>,
+}
+
+impl Scan {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Waits until fewer than `MAX_PENDING_CLASSES` classes are pending, then counts one more
+    /// until the returned guard is dropped
+    fn reserve(self: &Arc<Self>) -> Pending {
+        let mut pending = self.pending.lock().unwrap();
+        while *pending >= MAX_PENDING_CLASSES {
+            pending = self.converted.wait(pending).unwrap();
+        }
+        *pending += 1;
+        Pending(Arc::clone(self))
+    }
+
+    /// Appends a class to the result and reports it to `on_progress`
+    fn add<'a>(
+        &self,
+        cx: &mut TaskContext<'a>,
+        entry: &str,
+        index: usize,
+        total: usize,
+        class: &Class,
+    ) -> NeonResult<()> {
+        if self.is_cancelled() {
+            return Ok(());
+        }
+        let item = jar_class_to_js(cx, entry, class)?;
+        let classes = self.classes.to_inner(cx);
+        let len = classes.len(cx);
+        classes.set(cx, len, item)?;
+
+        let on_progress = match &self.on_progress {
+            Some(on_progress) => on_progress.to_inner(cx),
+            None => return Ok(()),
+        };
+        let progress = cx.empty_object();
+        let entry = cx.string(entry);
+        progress.set(cx, "entry", entry)?;
+        let done = cx.number((index + 1) as f64);
+        progress.set(cx, "done", done)?;
+        let total = cx.number(total as f64);
+        progress.set(cx, "total", total)?;
+        let this = cx.undefined();
+        let result = cx.try_catch(|cx| on_progress.call(cx, this, [progress.upcast()]));
+        if let Err(exception) = result {
+            self.cancel();
+            let holder = cx.empty_array();
+            holder.set(cx, 0, exception)?;
+            *self.exception.lock().unwrap() = Some(holder.root(cx));
+        }
+        Ok(())
+    }
+}
+
+/// A class on its way to the main thread. Dropped once it has been converted, or when the
+/// callback carrying it is dropped without running
+struct Pending(Arc<Scan>);
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        *self.0.pending.lock().unwrap() -= 1;
+        self.0.converted.notify_one();
+    }
+}
+
+enum Outcome {
+    Done(Option<Manifest>),
+    Failed(ParseError),
+    Cancelled,
+}
+
+/// Reads every class in the jar, handing each one to the main thread as soon as it is read.
+/// Reading waits while the main thread is `MAX_PENDING_CLASSES` classes behind
+fn scan(path: &str, options: ParseOptions, scan: &Arc<Scan>, channel: &Channel) -> Outcome {
+    let read = || -> crate::Result<Outcome> {
+        let mut jar = Jar::open(path)?.with_options(options);
+        let manifest = jar.manifest()?;
+        let entries = jar.class_entries();
+        let total = entries.len();
+        for (index, entry) in entries.into_iter().enumerate() {
+            if scan.is_cancelled() {
+                return Ok(Outcome::Cancelled);
+            }
+            let class = match jar.class_entry(&entry)? {
+                Some(class) => class,
+                None => continue,
+            };
+            let pending = scan.reserve();
+            channel.send(move |mut cx| pending.0.add(&mut cx, &entry, index, total, &class));
+        }
+        Ok(Outcome::Done(manifest))
+    };
+    read().unwrap_or_else(Outcome::Failed)
+}
+
+/// The `abort` listener a scan added to its `AbortSignal`
+struct Watch {
+    signal: Root<JsObject>,
+    listener: Root<JsFunction>,
+}
+
+impl Watch {
+    /// Removes the listener, so that a signal outliving the scan does not keep it alive
+    fn stop<'a, C: Context<'a>>(self, cx: &mut C) -> NeonResult<()> {
+        let signal = self.signal.into_inner(cx);
+        let listener = self.listener.into_inner(cx);
+        let remove_event_listener = signal.get::<JsFunction, _, _>(cx, "removeEventListener")?;
+        let event = cx.string("abort");
+        remove_event_listener.call(cx, signal, [event.upcast(), listener.upcast()])?;
+        Ok(())
+    }
+}
+
+/// Sets `scan.cancelled` once `signal` is aborted, returning the listener that does so unless
+/// the signal was already aborted
+fn watch_signal<'a>(
+    cx: &mut FunctionContext<'a>,
+    signal: Handle<'a, JsObject>,
+    scan: &Arc<Scan>,
+) -> NeonResult<Option<Watch>> {
+    let aborted = signal
+        .get::<JsValue, _, _>(cx, "aborted")?
+        .downcast::<JsBoolean, _>(cx)
+        .map(|aborted| aborted.value(cx))
+        .unwrap_or(false);
+    if aborted {
+        scan.cancel();
+        return Ok(None);
+    }
+    let listener = {
+        let scan = Arc::clone(scan);
+        JsFunction::new(cx, move |mut cx| {
+            scan.cancel();
+            Ok(cx.undefined())
+        })?
+    };
+    let add_event_listener = signal.get::<JsFunction, _, _>(cx, "addEventListener")?;
+    let event = cx.string("abort");
+    add_event_listener.call(cx, signal, [event.upcast(), listener.upcast()])?;
+    Ok(Some(Watch {
+        signal: signal.root(cx),
+        listener: listener.root(cx),
+    }))
+}
+
+/// `parseJarAsync(path, options?)`, reads the jar on one of the threads shared by every scan,
+/// so a jar waits for earlier ones when they are all busy. Besides the parse mode, the
+/// options can have an `onProgress({ entry, done, total })` callback that is called after each
+/// class, and an `AbortSignal` as `signal` that cancels the scan before the next entry
+pub fn parse_jar_async(mut cx: FunctionContext) -> JsResult<JsPromise> {
+    let path = cx.argument::<JsString>(0)?.value(&mut cx);
+    let options = options_argument(&mut cx, 1)?;
+    let parse_options = parse_options(&mut cx, options)?;
+
+    let mut on_progress = None;
+    let mut signal = None;
+    if let Some(options) = options {
+        let callback: Handle<JsValue> = options.get(&mut cx, "onProgress")?;
+        if !callback.is_a::<JsUndefined, _>(&mut cx) {
+            let callback = callback.downcast_or_throw::<JsFunction, _>(&mut cx)?;
+            on_progress = Some(callback.root(&mut cx));
+        }
+        let value: Handle<JsValue> = options.get(&mut cx, "signal")?;
+        if !value.is_a::<JsUndefined, _>(&mut cx) {
+            signal = Some(value.downcast_or_throw::<JsObject, _>(&mut cx)?);
+        }
+    }
+    let classes = cx.empty_array().root(&mut cx);
+    let state = Arc::new(Scan {
+        cancelled: AtomicBool::new(false),
+        pending: Mutex::new(0),
+        converted: Condvar::new(),
+        classes,
+        on_progress,
+        exception: Mutex::new(None),
+    });
+    let watch = match signal {
+        Some(signal) => watch_signal(&mut cx, signal, &state)?,
+        None => None,
+    };
+
+    let channel = cx.channel();
+    let (deferred, promise) = cx.promise();
+    spawn_scan(Box::new(move || {
+        let outcome = scan(&path, parse_options, &state, &channel);
+        // Settled after every class sent by `scan`, as the channel runs callbacks in order
+        deferred.settle_with(&channel, move |mut cx| {
+            if let Some(watch) = watch {
+                watch.stop(&mut cx)?;
+            }
+            if let Some(holder) = state.exception.lock().unwrap().take() {
+                let exception: Handle<JsValue> = holder.into_inner(&mut cx).get(&mut cx, 0)?;
+                return cx.throw(exception);
+            }
+            // The scan may have finished before a cancellation from the main thread was seen
+            if state.is_cancelled() {
+                return throw_cancelled(&mut cx);
+            }
+            let manifest = match outcome {
+                Outcome::Done(manifest) => manifest,
+                Outcome::Failed(err) => return throw_parse_error(&mut cx, err),
+                Outcome::Cancelled => return throw_cancelled(&mut cx),
+            };
+            let classes = state.classes.to_inner(&mut cx);
+            jar_to_js(&mut cx, manifest.as_ref(), classes)
+        });
+    }));
+    Ok(promise)
+}
+
+/// Throws an `Error` with the kind `Cancelled`
+fn throw_cancelled<'a, C: Context<'a>, T>(cx: &mut C) -> NeonResult<T> {
+    let error = cx.error("Parsing was cancelled")?;
+    let kind = cx.string("Cancelled");
+    error.set(cx, "kind", kind)?;
+    cx.throw(error)
+}
@@ -2,10 +2,19 @@
 // pub mod class_version;
 // pub mod const_pool;
 // pub mod const_type;
+pub mod analysis;
+pub mod assemble;
+pub mod compact;
+pub mod descriptor;
+pub mod disassemble;
 pub mod error;
+pub mod jar;
+pub mod js;
 pub mod pretty_print;
 pub mod reader;
+pub mod signature;
 pub mod structs;
+pub mod writer;
 
 // use crate::const_type::ConstType;
 // use enum_dispatch::enum_dispatch;
@@ -44,7 +53,7 @@ macro_rules! gen_try_from {
             $($variant:ident = $value:expr,)*
         }
     ) => {
-        crate::impl_get_pretty! {
+        $crate::impl_get_pretty! {
             $(#[$attr])*
             pub enum $Name {
                 $($variant = $value,)*
@@ -52,11 +61,11 @@ macro_rules! gen_try_from {
         }
 
         impl std::convert::TryFrom<u16> for $Name {
-            type Error = crate::error::ParseError;
-            fn try_from(value: u16) -> Result<Self, Self::Error> {
+            type Error = $crate::error::ParseError;
+            fn try_from(value: u16) -> std::result::Result<Self, Self::Error> {
                 match value {
                     $($value => Ok($Name::$variant),)*
-                    _ => Err(crate::error::ParseError::Unrecognized(stringify!($Name), value.to_string())),
+                    _ => Err($crate::error::ParseError::Unrecognized(stringify!($Name), value.to_string())),
                 }
             }
         }
@@ -67,6 +76,10 @@ macro_rules! gen_try_from {
 #[neon::main]
 fn main(mut cx: ModuleContext) -> NeonResult<()> {
     cx.export_function("hello", hello)?;
+    cx.export_function("parseClass", js::parse_class)?;
+    cx.export_function("parseJar", js::parse_jar)?;
+    cx.export_function("parseClassAsync", js::tasks::parse_class_async)?;
+    cx.export_function("parseJarAsync", js::tasks::parse_jar_async)?;
     Ok(())
 }
 
@@ -75,7 +88,9 @@ mod tests {
     use crate::pretty_print::GetPretty;
 
     use super::*;
+    use std::io::{Cursor, Write};
     use std::process::Command;
+    use zip::{write::FileOptions, ZipWriter};
 
     #[test]
     fn test_read_class() {
@@ -120,4 +135,1918 @@ mod tests {
         //         });
         // });
     }
+
+    #[test]
+    fn test_write_class_round_trip() {
+        let bytes = std::fs::read("testdata/HelloWorld.class").expect("Failed to read file");
+        let class = structs::Class::read(&mut bytes.as_slice()).expect("Failed to read class file");
+
+        let mut written = Vec::new();
+        class
+            .write(&mut written)
+            .expect("Failed to write class file");
+        assert_eq!(bytes, written);
+
+        // Editing the string of a constant writes the new string
+        let mut class = class;
+        for entry in class.constant_pool.entries.iter_mut().flatten() {
+            if let structs::ConstItem::Utf8(utf8) = entry {
+                if utf8.str == "Hello World!" {
+                    utf8.str = "Hallo \0Welt!".to_string();
+                }
+            }
+        }
+        let mut written = Vec::new();
+        class.write(&mut written).unwrap();
+        let class = structs::Class::read(&mut written.as_slice()).unwrap();
+        assert!(class
+            .constant_pool
+            .entries
+            .iter()
+            .flatten()
+            .any(|entry| matches!(
+                entry,
+                structs::ConstItem::Utf8(utf8)
+                    if utf8.str == "Hallo \0Welt!" && utf8.bytes == b"Hallo \xC0\x80Welt!"
+            )));
+    }
+
+    fn read_test_class(path: &str) -> (Vec<u8>, structs::Class) {
+        let bytes = std::fs::read(path).expect("Failed to read file");
+        let class = structs::Class::read(&mut bytes.as_slice()).expect("Failed to read class file");
+        (bytes, class)
+    }
+
+    fn method_code<'a>(class: &'a structs::Class, name: &str) -> &'a structs::attributes::Code {
+        let method = class
+            .methods
+            .iter()
+            .find(|method| method.name_index.get(&class.constant_pool).unwrap().str == name)
+            .expect("Method not found");
+        method
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                structs::attributes::AttributeInfo::Code(code) => Some(code),
+                _ => None,
+            })
+            .expect("Code attribute not found")
+    }
+
+    #[test]
+    fn test_read_instructions() {
+        use structs::instructions::{Instruction, Wide};
+
+        let (_, class) = read_test_class("testdata/HelloWorld.class");
+        let code = method_code(&class, "main");
+        let instructions: Vec<_> = code.code.iter().map(|i| i.instruction.opcode()).collect();
+        assert_eq!(
+            instructions,
+            vec![
+                structs::opcodes::Opcode::getstatic,
+                structs::opcodes::Opcode::ldc,
+                structs::opcodes::Opcode::invokevirtual,
+                structs::opcodes::Opcode::return_,
+            ]
+        );
+        let offsets: Vec<_> = code.code.iter().map(|i| i.offset).collect();
+        assert_eq!(offsets, vec![0, 3, 5, 8]);
+
+        let (bytes, class) = read_test_class("testdata/Instructions.class");
+        let mut written = Vec::new();
+        class
+            .write(&mut written)
+            .expect("Failed to write class file");
+        assert_eq!(bytes, written);
+
+        let code = method_code(&class, "tableSwitch");
+        match &code.code[1].instruction {
+            Instruction::tableswitch(switch) => {
+                assert_eq!((switch.low, switch.high), (1, 3));
+                assert_eq!(switch.offsets.len(), 3);
+            }
+            other => panic!("Expected tableswitch, got {:?}", other),
+        }
+
+        let code = method_code(&class, "lookupSwitch");
+        match &code.code[1].instruction {
+            Instruction::lookupswitch(switch) => {
+                let keys: Vec<_> = switch.pairs.iter().map(|pair| pair.key).collect();
+                assert_eq!(keys, vec![-1000, 0, 1000000]);
+            }
+            other => panic!("Expected lookupswitch, got {:?}", other),
+        }
+
+        let code = method_code(&class, "wide");
+        assert!(code
+            .code
+            .iter()
+            .any(|i| i.instruction == Instruction::wide(Wide::iinc(299, 1000))));
+
+        let code = method_code(&class, "arrays");
+        assert!(code
+            .code
+            .iter()
+            .any(|i| matches!(i.instruction, Instruction::multianewarray(_, 3))));
+
+        // Switches claiming billions of entries fail at the end of the code instead of
+        // allocating for all of them
+        let mut tableswitch = vec![0xAA, 0, 0, 0, 0, 0, 0, 0];
+        tableswitch.extend_from_slice(&i32::MIN.to_be_bytes());
+        tableswitch.extend_from_slice(&i32::MAX.to_be_bytes());
+        let mut lookupswitch = vec![0xAB, 0, 0, 0, 0, 0, 0, 0];
+        lookupswitch.extend_from_slice(&i32::MAX.to_be_bytes());
+        for code in [tableswitch, lookupswitch] {
+            let err = reader::instructions::read_code(&code).unwrap_err();
+            assert!(matches!(err.root(), error::ParseError::IO(_)));
+        }
+        // The reserved opcodes are not allowed in a class file (JVMS 4.9.1)
+        for opcode in [0xCA, 0xFE, 0xFF] {
+            let err = reader::instructions::read_code(&[opcode]).unwrap_err();
+            assert!(matches!(
+                err.root(),
+                error::ParseError::Unrecognized("Instruction", _)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_stack_map_frames() {
+        use structs::attributes::{AttributeInfo, StackMapFrame, VerificationTypeInfo};
+
+        let (_, class) = read_test_class("testdata/Instructions.class");
+        let code = method_code(&class, "wide");
+        let table = code
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::StackMapTable(table) => Some(table),
+                _ => None,
+            })
+            .expect("StackMapTable not found");
+        assert!(matches!(
+            table.entries[1],
+            StackMapFrame::Chop {
+                k: 1,
+                offset_delta: 1142
+            }
+        ));
+
+        let parameters = vec![VerificationTypeInfo::Long; 8];
+        let frames = table.frames(&parameters).expect("Failed to expand frames");
+        assert_eq!(frames[0].offset, 11);
+        assert_eq!(frames[0].locals.len(), 11);
+        match &frames[0].locals[8] {
+            VerificationTypeInfo::Object(index) => {
+                let class_name = &index.get(&class.constant_pool).unwrap().name_index;
+                assert_eq!(class_name.get(&class.constant_pool).unwrap().str, "[J");
+            }
+            other => panic!("Expected an object, got {:?}", other),
+        }
+        assert_eq!(frames[1].offset, 1154);
+        assert_eq!(&frames[1].locals[..], &frames[0].locals[..10]);
+        assert!(frames[1].stack.is_empty());
+
+        assert_eq!(table.entries[1].frame_type().unwrap(), 250);
+        let chop = StackMapFrame::Chop {
+            k: 252,
+            offset_delta: 0,
+        };
+        let append = StackMapFrame::Append {
+            offset_delta: 0,
+            locals: vec![VerificationTypeInfo::Integer; 5],
+        };
+        assert!(chop.frame_type().is_err());
+        assert!(append.frame_type().is_err());
+    }
+
+    #[test]
+    fn test_annotations() {
+        use structs::attributes::{AttributeInfo, ResolvedElementValue};
+
+        let (bytes, class) = read_test_class("testdata/Annotated.class");
+        let pool = &class.constant_pool;
+        let mut written = Vec::new();
+        class
+            .write(&mut written)
+            .expect("Failed to write class file");
+        assert_eq!(bytes, written);
+
+        let visible = class
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::RuntimeVisibleAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .expect("RuntimeVisibleAnnotations not found");
+        let entity = visible.annotations[0].resolve(pool).unwrap();
+        assert_eq!(entity.type_name, "LAnnotated$Entity;");
+        assert_eq!(
+            entity.get("name"),
+            Some(&ResolvedElementValue::String("annotated".to_string()))
+        );
+        assert_eq!(
+            entity.get("tags"),
+            Some(&ResolvedElementValue::Array(vec![
+                ResolvedElementValue::String("a".to_string()),
+                ResolvedElementValue::String("b".to_string()),
+            ]))
+        );
+        assert_eq!(
+            entity.get("kind"),
+            Some(&ResolvedElementValue::Enum {
+                type_name: "Ljava/lang/annotation/ElementType;".to_string(),
+                const_name: "TYPE".to_string(),
+            })
+        );
+        assert_eq!(
+            entity.get("type"),
+            Some(&ResolvedElementValue::Class(
+                "Ljava/lang/String;".to_string()
+            ))
+        );
+        match entity.get("nested") {
+            Some(ResolvedElementValue::Annotation(nested)) => {
+                assert_eq!(nested.type_name, "LAnnotated$Marker;");
+                assert!(nested.elements.is_empty());
+            }
+            other => panic!("Expected a nested annotation, got {:?}", other),
+        }
+
+        assert!(class.attributes.iter().any(|attribute| matches!(
+            &attribute.info,
+            AttributeInfo::RuntimeInvisibleAnnotations(annotations)
+                if annotations.annotations[0].type_name(pool).unwrap() == "LAnnotated$Invisible;"
+        )));
+
+        let handle = class
+            .methods
+            .iter()
+            .find(|method| method.name_index.get(pool).unwrap().str == "handle")
+            .unwrap();
+        let parameters = handle
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::RuntimeVisibleParameterAnnotations(parameters) => Some(parameters),
+                _ => None,
+            })
+            .expect("RuntimeVisibleParameterAnnotations not found");
+        assert_eq!(parameters.parameter_annotations.len(), 2);
+        let marker = parameters.parameter_annotations[0].annotations[0]
+            .resolve(pool)
+            .unwrap();
+        assert_eq!(marker.get("priority"), Some(&ResolvedElementValue::Int(2)));
+        assert!(parameters.parameter_annotations[1].annotations.is_empty());
+
+        // Arrays of a single array, around a boolean
+        let nested = |depth: usize| {
+            let mut bytes = b"[\0\x01".repeat(depth);
+            bytes.extend_from_slice(b"Z\0\x01");
+            bytes
+        };
+        let max = structs::attributes::MAX_ELEMENT_VALUE_DEPTH;
+        let bytes = nested(max - 1);
+        assert!(structs::attributes::ElementValue::read(&mut bytes.as_slice()).is_ok());
+        let bytes = nested(100_000);
+        let err = structs::attributes::ElementValue::read(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            error::ParseError::TooDeeplyNested { limit, .. } if *limit == max
+        ));
+    }
+
+    #[test]
+    fn test_type_annotations() {
+        use structs::attributes::{
+            AttributeInfo, LocalVarTarget, TargetInfo, TargetType, TypePathKind,
+        };
+
+        let (_, class) = read_test_class("testdata/Annotated.class");
+        let pool = &class.constant_pool;
+
+        let field = &class.fields[0];
+        let annotations = field
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .expect("RuntimeVisibleTypeAnnotations not found on field");
+        let annotation = &annotations.annotations[0];
+        assert_eq!(annotation.target_type, TargetType::Field);
+        assert_eq!(annotation.target_info, TargetInfo::Empty);
+        assert_eq!(
+            annotation.target_path.path[0].type_path_kind,
+            TypePathKind::TypeArgument
+        );
+        assert_eq!(
+            annotation.annotation.type_name(pool).unwrap(),
+            "LAnnotated$NotNull;"
+        );
+
+        let code = method_code(&class, "handle");
+        let annotations = code
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::RuntimeVisibleTypeAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .expect("RuntimeVisibleTypeAnnotations not found on Code");
+        let targets: Vec<_> = annotations
+            .annotations
+            .iter()
+            .map(|annotation| (annotation.target_type, annotation.target_info.clone()))
+            .collect();
+        assert!(targets.contains(&(
+            TargetType::Cast,
+            TargetInfo::TypeArgument {
+                offset: 7,
+                type_argument_index: 0
+            }
+        )));
+        assert!(targets.contains(&(
+            TargetType::LocalVariable,
+            TargetInfo::Localvar {
+                table: vec![LocalVarTarget {
+                    start_pc: 2,
+                    length: 23,
+                    index: 3
+                }]
+            }
+        )));
+        assert!(targets.contains(&(
+            TargetType::ExceptionParameter,
+            TargetInfo::Catch {
+                exception_table_index: 0
+            }
+        )));
+    }
+
+    #[test]
+    fn test_annotation_default() {
+        let (bytes, class) = read_test_class("testdata/Annotated$Entity.class");
+        let pool = &class.constant_pool;
+        let mut written = Vec::new();
+        class
+            .write(&mut written)
+            .expect("Failed to write class file");
+        assert_eq!(bytes, written);
+
+        let defaults: Vec<_> = class
+            .methods
+            .iter()
+            .map(|method| {
+                (
+                    method.name_index.get(pool).unwrap().str.as_str(),
+                    method.annotation_default(pool).unwrap().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                ("name", "\"\"".to_string()),
+                ("tags", "{}".to_string()),
+                ("kind", "java.lang.annotation.ElementType.FIELD".to_string()),
+                ("type", "java.lang.Object.class".to_string()),
+                ("nested", "@Annotated$Marker(priority = 5)".to_string()),
+                ("id", "42L".to_string()),
+                ("letter", "'x'".to_string()),
+                ("ratio", "0.5".to_string()),
+                ("scale", "1.5f".to_string()),
+                ("enabled", "true".to_string()),
+                ("small", "-1".to_string()),
+                ("medium", "300".to_string()),
+            ]
+        );
+    }
+
+    fn build_jar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            if name.ends_with('/') {
+                writer.add_directory(*name, FileOptions::default()).unwrap();
+            } else {
+                writer.start_file(*name, FileOptions::default()).unwrap();
+                writer.write_all(bytes).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_jar() {
+        let hello = std::fs::read("testdata/HelloWorld.class").unwrap();
+        let instructions = std::fs::read("testdata/Instructions.class").unwrap();
+        let bytes = build_jar(&[
+            ("META-INF/", b""),
+            ("HelloWorld.class", &hello),
+            ("pkg/Instructions.class", &instructions),
+            ("pkg/config.properties", b"key=value"),
+        ]);
+        let mut jar = jar::Jar::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(jar.class_names(), vec!["HelloWorld", "pkg/Instructions"]);
+        assert_eq!(jar.resource_names(), vec!["pkg/config.properties"]);
+        assert_eq!(
+            jar.resource("pkg/config.properties").unwrap(),
+            Some(b"key=value".to_vec())
+        );
+        assert!(jar.class("Missing").unwrap().is_none());
+
+        let class = jar.class("HelloWorld").unwrap().unwrap();
+        let this_class = class.this_class.get(&class.constant_pool).unwrap();
+        assert_eq!(
+            this_class.name_index.get(&class.constant_pool).unwrap().str,
+            "HelloWorld"
+        );
+
+        let entries: Vec<_> = jar
+            .classes()
+            .map(|class| class.map(|(entry, _)| entry))
+            .collect::<error::Result<_>>()
+            .unwrap();
+        assert_eq!(entries, vec!["HelloWorld.class", "pkg/Instructions.class"]);
+    }
+
+    #[test]
+    fn test_manifest() {
+        let text = "Manifest-Version: 1.0\r\n\
+Main-Class: HelloWorld\r\n\
+Class-Path: lib/first.jar lib/sec\r\n ond.jar\r\n\
+Automatic-Module-Name: com.example\r\n\
+Multi-Release: true\r\n\
+Add-Opens: java.base/java.lang java.base/java.util\r\n\
+\r\n\
+Name: pkg/Instructions.class\r\n\
+Sealed: true\r\n\
+\r\n";
+        let manifest = jar::Manifest::parse(text.as_bytes()).unwrap();
+        assert_eq!(manifest.main_class(), Some("HelloWorld"));
+        assert_eq!(
+            manifest.class_path(),
+            vec!["lib/first.jar", "lib/second.jar"]
+        );
+        assert_eq!(manifest.automatic_module_name(), Some("com.example"));
+        assert!(manifest.multi_release());
+        assert_eq!(
+            manifest.add_opens(),
+            vec!["java.base/java.lang", "java.base/java.util"]
+        );
+        let section = manifest.section("pkg/Instructions.class").unwrap();
+        assert_eq!(section.attributes.get("sealed"), Some("true"));
+
+        // A lone CR ends a line as well, and LF alone
+        for newline in ["\r", "\n"] {
+            let parsed = jar::Manifest::parse(text.replace("\r\n", newline).as_bytes()).unwrap();
+            assert_eq!(parsed, manifest);
+        }
+
+        // Long values are wrapped at 72 bytes and read back unchanged
+        let mut long = manifest.clone();
+        long.main
+            .insert("Class-Path", &"lib/dependency.jar ".repeat(10));
+        let bytes = long.to_bytes();
+        assert!(bytes.split(|&b| b == b'\n').all(|line| line.len() <= 73));
+        assert_eq!(jar::Manifest::parse(&bytes).unwrap(), long);
+
+        // The JDK wraps at 72 bytes even inside a multi-byte character
+        let title = format!("{}\u{e9}t\u{e9} \u{20ac}", "x".repeat(49));
+        let line = format!("Implementation-Title: {}", title);
+        for split in [72, 78] {
+            assert!(!line.is_char_boundary(split));
+            let mut bytes = line.as_bytes()[..split].to_vec();
+            bytes.extend_from_slice(b"\r\n ");
+            bytes.extend_from_slice(&line.as_bytes()[split..]);
+            bytes.extend_from_slice(b"\r\n");
+            let parsed = jar::Manifest::parse(&bytes).unwrap();
+            assert_eq!(parsed.get("Implementation-Title"), Some(title.as_str()));
+        }
+        assert!(matches!(
+            jar::Manifest::parse(b"Main-Class: Hello\r\n\r\n more\r\n"),
+            Err(error::ParseError::InvalidManifest { line: 3, .. })
+        ));
+        assert!(matches!(
+            jar::Manifest::parse(b"Main-Class: Hello\r\nName: \xC3\r\n"),
+            Err(error::ParseError::InvalidManifest { line: 2, .. })
+        ));
+
+        assert!(matches!(
+            jar::Manifest::parse(b"Main-Class HelloWorld\n"),
+            Err(error::ParseError::InvalidManifest { line: 1, .. })
+        ));
+
+        let hello = std::fs::read("testdata/HelloWorld.class").unwrap();
+        let bytes = build_jar(&[
+            ("META-INF/MANIFEST.MF", text.as_bytes()),
+            ("HelloWorld.class", &hello),
+        ]);
+        let mut jar = jar::Jar::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(jar.manifest().unwrap(), Some(manifest));
+        assert!(jar.main_class().unwrap().is_some());
+
+        let missing = "Manifest-Version: 1.0\nMain-Class: com.example.Missing\n";
+        let bytes = build_jar(&[
+            ("META-INF/MANIFEST.MF", missing.as_bytes()),
+            ("HelloWorld.class", &hello),
+        ]);
+        let mut jar = jar::Jar::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            jar.main_class(),
+            Err(error::ParseError::MissingMainClass(name)) if name == "com.example.Missing"
+        ));
+    }
+
+    #[test]
+    fn test_multi_release_jar() {
+        use structs::MajorVersion;
+
+        assert_eq!(MajorVersion::JDK_17.release(), 17);
+        assert_eq!(MajorVersion::JDK_1_4.release(), 1);
+        assert_eq!(
+            MajorVersion::from_release(11).unwrap(),
+            MajorVersion::JDK_11
+        );
+        assert!(MajorVersion::from_release(4).is_err());
+        assert!(MajorVersion::from_release(u16::MAX).is_err());
+
+        // testdata/HelloWorld.class targets 17, patch a copy down to 8 for the base entry
+        let hello = std::fs::read("testdata/HelloWorld.class").unwrap();
+        let mut hello_8 = hello.clone();
+        hello_8[6..8].copy_from_slice(&u16::from(MajorVersion::JDK_8).to_be_bytes());
+
+        let manifest = b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n";
+        let bytes = build_jar(&[
+            ("META-INF/MANIFEST.MF", manifest),
+            ("HelloWorld.class", &hello_8),
+            ("META-INF/versions/17/HelloWorld.class", &hello),
+            ("META-INF/versions/11/HelloWorld.class", &hello),
+        ]);
+        let mut jar = jar::Jar::new(Cursor::new(bytes)).unwrap();
+        assert!(jar.is_multi_release().unwrap());
+        assert_eq!(jar.class_releases("HelloWorld"), vec![11, 17]);
+        // Overlays are not classes of their own
+        assert_eq!(jar.class_names(), vec!["HelloWorld"]);
+        assert_eq!(jar.classes().count(), 1);
+
+        let resolve = |jar: &mut jar::Jar<_>, release| {
+            jar.resolve_class_entry("HelloWorld", release).unwrap()
+        };
+        assert_eq!(
+            resolve(&mut jar, MajorVersion::JDK_8).as_deref(),
+            Some("HelloWorld.class")
+        );
+        assert_eq!(
+            resolve(&mut jar, MajorVersion::JDK_11).as_deref(),
+            Some("META-INF/versions/11/HelloWorld.class")
+        );
+        assert_eq!(
+            resolve(&mut jar, MajorVersion::JDK_21).as_deref(),
+            Some("META-INF/versions/17/HelloWorld.class")
+        );
+
+        let class = jar
+            .class_for_release("HelloWorld", MajorVersion::JDK_8)
+            .unwrap()
+            .unwrap();
+        assert_eq!(class.version.major, MajorVersion::JDK_8);
+        let class = jar
+            .class_for_release("HelloWorld", MajorVersion::JDK_17)
+            .unwrap()
+            .unwrap();
+        assert_eq!(class.version.major, MajorVersion::JDK_17);
+
+        // The versions/11 overlay is compiled for 17
+        assert!(matches!(
+            jar.class_for_release("HelloWorld", MajorVersion::JDK_11),
+            Err(error::ParseError::VersionedEntryTooNew { release: 11, .. })
+        ));
+
+        // Overlays are ignored unless the manifest opts in
+        let bytes = build_jar(&[
+            ("HelloWorld.class", &hello_8),
+            ("META-INF/versions/17/HelloWorld.class", &hello),
+        ]);
+        let mut jar = jar::Jar::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            resolve(&mut jar, MajorVersion::JDK_17).as_deref(),
+            Some("HelloWorld.class")
+        );
+    }
+
+    #[test]
+    fn test_malformed_const_pool() {
+        use error::ParseError;
+        use structs::const_types::{Class, Long, Utf8};
+        use structs::{ConstPool, Index};
+
+        // magic, version, then a pool of two entries whose first tag is invalid
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 2, 2];
+        bytes.extend_from_slice(&[0; 8]);
+        let err = structs::Class::read(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            ParseError::InvalidConstantTag { tag: 2, index: 1 }
+        ));
+        // The kind reported to JavaScript is the variant under the context
+        assert_eq!(err.kind(), "Context");
+        assert_eq!(err.root().kind(), "InvalidConstantTag");
+
+        // A MethodHandle with reference kind 0
+        let bytes = [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 2, 15, 0, 0, 1];
+        let err = structs::Class::read(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            ParseError::Unrecognized("MethodHandleReferenceKind", _)
+        ));
+
+        // Truncated input is an IO error rather than a panic
+        let bytes = [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 3, 1, 0, 5, b'a'];
+        let err = structs::Class::read(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(err.root(), ParseError::IO(_)));
+
+        // A Long takes its own index and leaves the next one unusable
+        let mut bytes = vec![0, 4, 5, 0, 0, 0, 0, 0, 0, 0, 42];
+        bytes.extend_from_slice(&[1, 0, 1, b'a']);
+        let pool = ConstPool::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(Index::<Long>::new(1).get(&pool).unwrap().value, 42);
+        assert!(matches!(
+            Index::<Utf8>::new(2).get(&pool),
+            Err(ParseError::UnusableConstantIndex(2))
+        ));
+        assert_eq!(Index::<Utf8>::new(3).get(&pool).unwrap().str, "a");
+
+        let (_, class) = read_test_class("testdata/HelloWorld.class");
+        let pool: &ConstPool = &class.constant_pool;
+        assert!(matches!(
+            Index::<Utf8>::new(u16::MAX).get(pool),
+            Err(ParseError::ConstantIndexOutOfRange {
+                index: u16::MAX,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Index::<Utf8>::new(0).get(pool),
+            Err(ParseError::UnusableConstantIndex(0))
+        ));
+        let class_index = Index::<Utf8>::new(class.this_class.index);
+        assert!(matches!(
+            class_index.get(pool),
+            Err(ParseError::WrongConstantType {
+                expected: "Utf8",
+                found: "Class",
+                ..
+            })
+        ));
+        assert!(Index::<Class>::new(class.this_class.index)
+            .get(pool)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_error_context() {
+        use error::ParseError;
+
+        let (bytes, _) = read_test_class("testdata/HelloWorld.class");
+
+        // The tag of the first constant is the 11th byte
+        let mut corrupt = bytes.clone();
+        corrupt[10] = 2;
+        let err = structs::Class::read(&mut corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.offset(), Some(10));
+        assert_eq!(err.path(), Some("constant_pool[1]"));
+        assert!(err
+            .to_string()
+            .ends_with("at offset 0xA (constant_pool[1])"));
+
+        // Truncated input points at the start of the last attribute, the 8 byte SourceFile
+        let err = structs::Class::read(&mut &bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.offset(), Some(bytes.len() - 8));
+        assert_eq!(err.path(), Some("attributes[0]"));
+        assert!(matches!(err.root(), ParseError::IO(_)));
+
+        // Replace the getstatic at the start of main with an internal opcode
+        let code_start = bytes
+            .windows(5)
+            .position(|window| window == [0, 0, 0, 9, 0xB2])
+            .expect("main's code not found")
+            + 4;
+        let mut corrupt = bytes.clone();
+        corrupt[code_start] = 0xCB;
+        let class = structs::Class::read_with(
+            &mut corrupt.as_slice(),
+            &reader::options::ParseOptions::lenient(),
+        )
+        .unwrap();
+        let main = class
+            .methods
+            .iter()
+            .find(|method| method.name_index.get(&class.constant_pool).unwrap().str == "main")
+            .unwrap();
+        let mut code = main.attributes[0].clone();
+        let err = code.classify(&class.constant_pool).unwrap_err();
+        assert_eq!(err.offset(), Some(code_start));
+        assert_eq!(err.path(), Some("code@0"));
+        assert!(matches!(
+            err.root(),
+            ParseError::Unrecognized("Instruction", _)
+        ));
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        use error::ParseError;
+        use reader::options::ParseOptions;
+        use structs::{attributes::AttributeInfo, Severity};
+
+        let (bytes, class) = read_test_class("testdata/HelloWorld.class");
+        assert!(class.diagnostics.is_empty());
+
+        // Replace the getstatic at the start of main with an internal opcode
+        let code_start = bytes
+            .windows(5)
+            .position(|window| window == [0, 0, 0, 9, 0xB2])
+            .expect("main's code not found")
+            + 4;
+        let mut corrupt = bytes.clone();
+        corrupt[code_start] = 0xCB;
+
+        let err = structs::Class::read_with(&mut corrupt.as_slice(), &ParseOptions::strict())
+            .unwrap_err();
+        assert_eq!(err.path(), Some("methods[1].attributes[0](Code).code@0"));
+        assert_eq!(err.offset(), Some(code_start));
+
+        let class =
+            structs::Class::read_with(&mut corrupt.as_slice(), &ParseOptions::lenient()).unwrap();
+        assert_eq!(class.diagnostics.len(), 1);
+        let diagnostic = &class.diagnostics[0];
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.path, "methods[1].attributes[0](Code).code@0");
+        assert_eq!(diagnostic.offset, code_start);
+        assert!(matches!(
+            class.methods[1].attributes[0].info,
+            AttributeInfo::Unknown(_)
+        ));
+        let mut written = Vec::new();
+        class.write(&mut written).unwrap();
+        assert_eq!(written, corrupt);
+
+        // An undefined access flag right after the constant pool
+        let mut pool = Vec::new();
+        class.constant_pool.write(&mut pool).unwrap();
+        let flags_offset = 8 + pool.len();
+        let mut corrupt = bytes.clone();
+        corrupt[flags_offset] |= 0x01;
+        let err = structs::Class::read_with(&mut corrupt.as_slice(), &ParseOptions::strict())
+            .unwrap_err();
+        assert!(matches!(err.root(), ParseError::InvalidAccessFlags(0x0121)));
+        // Reading without options is strict as well
+        assert!(structs::Class::read(&mut corrupt.as_slice()).is_err());
+        let class =
+            structs::Class::read_with(&mut corrupt.as_slice(), &ParseOptions::lenient()).unwrap();
+        assert_eq!(class.access_flags.bits(), 0x0121);
+        assert_eq!(class.diagnostics[0].offset, flags_offset);
+
+        // Unknown attributes only produce a warning, even when strict
+        let mut renamed = bytes.clone();
+        let name_at = renamed
+            .windows(10)
+            .position(|window| window == b"SourceFile")
+            .unwrap();
+        renamed[name_at..name_at + 10].copy_from_slice(b"SourceFail");
+        let class =
+            structs::Class::read_with(&mut renamed.as_slice(), &ParseOptions::strict()).unwrap();
+        assert_eq!(class.diagnostics.len(), 1);
+        assert_eq!(class.diagnostics[0].severity, Severity::Warning);
+        assert_eq!(class.diagnostics[0].path, "attributes[0](SourceFail)");
+    }
+
+    #[test]
+    fn test_descriptors() {
+        use descriptor::{BaseType, FieldType, MethodDescriptor};
+
+        let strings = FieldType::parse("[[Ljava/lang/String;").unwrap();
+        assert_eq!(
+            strings,
+            FieldType::Array {
+                dimensions: 2,
+                element: Box::new(FieldType::object("java/lang/String")),
+            }
+        );
+        assert_eq!(strings.to_internal(), "[[Ljava/lang/String;");
+        assert_eq!(strings.to_java(), "java.lang.String[][]");
+        assert_eq!(FieldType::parse("J").unwrap().slot_size(), 2);
+        assert_eq!(strings.slot_size(), 1);
+
+        let descriptor = MethodDescriptor::parse("(IJ[DLjava/util/Map$Entry;)D").unwrap();
+        assert_eq!(descriptor.parameters[0], FieldType::Base(BaseType::Int));
+        assert_eq!(descriptor.parameter_slots(), 5);
+        assert_eq!(descriptor.return_slots(), 2);
+        assert_eq!(descriptor.to_internal(), "(IJ[DLjava/util/Map$Entry;)D");
+        assert_eq!(
+            descriptor.to_java("apply"),
+            "double apply(int, long, double[], java.util.Map$Entry)"
+        );
+
+        for invalid in [
+            "",
+            "V",
+            "Q",
+            "Ljava/lang/String",
+            "L;",
+            "Ljava//String;",
+            "II",
+        ] {
+            assert!(matches!(
+                FieldType::parse(invalid),
+                Err(error::ParseError::InvalidDescriptor { .. })
+            ));
+        }
+        for invalid in ["()", "(V)V", "I)V", "()VV", "(Ljava.lang.String;)V"] {
+            assert!(MethodDescriptor::parse(invalid).is_err(), "{}", invalid);
+        }
+        assert!(FieldType::parse(&format!("{}I", "[".repeat(256))).is_err());
+        assert_eq!(
+            FieldType::array(strings.clone(), 1).unwrap(),
+            FieldType::parse("[[[Ljava/lang/String;").unwrap()
+        );
+        assert!(FieldType::array(strings.clone(), 0).is_err());
+        assert!(FieldType::array(strings, 254).is_err());
+        // `$` can be part of a class name, so only the packages are separated by dots
+        assert_eq!(FieldType::object("a/b$C").to_java(), "a.b$C");
+
+        // Every descriptor in a real class parses and renders back unchanged
+        let (_, class) = read_test_class("testdata/Instructions.class");
+        let pool = &class.constant_pool;
+        for method in &class.methods {
+            let descriptor = method.descriptor(pool).unwrap();
+            assert_eq!(
+                descriptor.to_internal(),
+                method.descriptor_index.get(pool).unwrap().str
+            );
+        }
+        let wide = class
+            .methods
+            .iter()
+            .find(|method| method.name_index.get(pool).unwrap().str == "wide")
+            .unwrap();
+        assert_eq!(wide.descriptor(pool).unwrap().parameter_slots(), 16);
+    }
+
+    #[test]
+    fn test_signatures() {
+        use descriptor::MAX_ARRAY_DIMENSIONS;
+        use signature::{
+            ClassSignature, MethodSignature, Names, ReferenceTypeSignature, MAX_TYPE_ARGUMENT_DEPTH,
+        };
+
+        let (_, class) = read_test_class("testdata/Generics.class");
+        let pool = &class.constant_pool;
+        let signature = class.signature().unwrap().unwrap();
+        assert_eq!(
+            signature.to_java("Generics", Names::Simple),
+            "Generics<K extends Comparable<? super K>, V> extends AbstractMap<K, V> implements Serializable"
+        );
+        assert_eq!(signature.type_parameters[0].class_bound, None);
+        assert_eq!(
+            signature.superclass.internal_name(),
+            "java/util/AbstractMap"
+        );
+
+        let field = |name: &str| {
+            class
+                .fields
+                .iter()
+                .find(|field| field.name_index.get(pool).unwrap().str == name)
+                .unwrap()
+                .signature(pool)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(
+            field("index").to_java(Names::Simple),
+            "Map<String, ? extends List<V>>"
+        );
+        assert_eq!(
+            field("index").to_java(Names::Qualified),
+            "java.util.Map<java.lang.String, ? extends java.util.List<V>>"
+        );
+        let leaf = field("leaf");
+        assert_eq!(
+            leaf.to_java(Names::Qualified),
+            "Generics<K, V>.Inner<java.lang.String>.Leaf<java.lang.Integer>"
+        );
+        match &leaf {
+            ReferenceTypeSignature::Class(class) => {
+                assert_eq!(class.internal_name(), "Generics$Inner$Leaf")
+            }
+            _ => panic!("Expected a class type"),
+        }
+        assert_eq!(field("grid").to_java(Names::Simple), "V[][]");
+
+        let method = |name: &str| {
+            class
+                .methods
+                .iter()
+                .find(|method| method.name_index.get(pool).unwrap().str == name)
+                .unwrap()
+                .signature(pool)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(
+            method("copy").to_java("copy", Names::Simple),
+            "<T extends Number & Runnable, E extends Exception> List<T> copy(List<? super T>, T[], int) throws E, IOException"
+        );
+        assert_eq!(
+            method("entrySet").to_java("entrySet", Names::Qualified),
+            "java.util.Set<java.util.Map$Entry<K, V>> entrySet()"
+        );
+        assert_eq!(
+            method("entrySet").to_java("entrySet", Names::Simple),
+            "Set<Entry<K, V>> entrySet()"
+        );
+
+        // Every signature renders back to the attribute it was parsed from
+        let internal = |attributes: &structs::attributes::Attributes| {
+            structs::attributes::find_signature(attributes)
+                .map(|signature| signature.signature_index.get(pool).unwrap().str.clone())
+        };
+        assert_eq!(internal(&class.attributes).unwrap(), signature.to_string());
+        for field in &class.fields {
+            let parsed = field.signature(pool).unwrap().unwrap();
+            assert_eq!(internal(&field.attributes).unwrap(), parsed.to_string());
+        }
+        for method in &class.methods {
+            if let Some(parsed) = method.signature(pool).unwrap() {
+                assert_eq!(internal(&method.attributes).unwrap(), parsed.to_string());
+            }
+        }
+
+        for invalid in [
+            "Ljava/util/List<>;",
+            "Ljava/util/List",
+            "TT",
+            "L;",
+            "Ljava//List;",
+        ] {
+            assert!(matches!(
+                ReferenceTypeSignature::parse(invalid),
+                Err(error::ParseError::InvalidSignature { .. })
+            ));
+        }
+        assert!(MethodSignature::parse("<>()V").is_err());
+        assert!(MethodSignature::parse("()V^[I").is_err());
+        assert!(ClassSignature::parse("<T:>Ljava/lang/Object;").is_err());
+
+        // Arrays and type arguments are bounded like the dimensions of a descriptor
+        let arrays = |dimensions: usize| format!("{}TT;", "[".repeat(dimensions));
+        let parsed = ReferenceTypeSignature::parse(&arrays(MAX_ARRAY_DIMENSIONS)).unwrap();
+        assert_eq!(parsed.to_string(), arrays(MAX_ARRAY_DIMENSIONS));
+        assert!(ReferenceTypeSignature::parse(&arrays(MAX_ARRAY_DIMENSIONS + 1)).is_err());
+        assert!(ReferenceTypeSignature::parse(&arrays(65_536)).is_err());
+        let lists = |depth: usize| {
+            format!(
+                "{}TT;{}",
+                "Ljava/util/List<".repeat(depth),
+                ">;".repeat(depth)
+            )
+        };
+        let parsed = ReferenceTypeSignature::parse(&lists(MAX_TYPE_ARGUMENT_DEPTH)).unwrap();
+        assert_eq!(parsed.to_string(), lists(MAX_TYPE_ARGUMENT_DEPTH));
+        assert!(matches!(
+            ReferenceTypeSignature::parse(&lists(65_536)),
+            Err(error::ParseError::InvalidSignature { reason, .. })
+                if reason.starts_with("type arguments nested too deep")
+        ));
+    }
+
+    #[test]
+    fn test_disassemble_matches_javap() {
+        // The expected output is `javap -c -v -p` from JDK 17, without the first three lines
+        for path in test_classes() {
+            let expected = std::fs::read_to_string(path.with_extension("javap"))
+                .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+            let (_, class) = read_test_class(path.to_str().unwrap());
+            let actual = disassemble::disassemble(&class).expect("Failed to disassemble");
+            assert_eq!(actual, expected, "{}", path.display());
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the javap of JDK 17 on the PATH"]
+    fn test_disassemble_matches_live_javap() {
+        for path in test_classes() {
+            let output = Command::new("javap")
+                .args(["-c", "-v", "-p"])
+                .arg(&path)
+                .output()
+                .expect("Failed to run javap");
+            // javap starts with the file name, modification time and checksum
+            let expected: String = String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+                .skip(3)
+                .map(|line| format!("{}\n", line))
+                .collect();
+            let (_, class) = read_test_class(path.to_str().unwrap());
+            let actual = disassemble::disassemble(&class).expect("Failed to disassemble");
+            assert_eq!(actual, expected, "{}", path.display());
+        }
+    }
+
+    /// The class files in testdata, sorted by name
+    fn test_classes() -> Vec<std::path::PathBuf> {
+        let mut paths: Vec<_> = std::fs::read_dir("testdata")
+            .expect("Failed to list testdata")
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "class"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_assemble_round_trip() {
+        for path in test_classes() {
+            let (bytes, class) = read_test_class(path.to_str().unwrap());
+            let text = assemble::print(&class).expect("Failed to print");
+            let assembled = assemble::assemble(&text)
+                .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+            assert_eq!(
+                assemble::print(&assembled).expect("Failed to print"),
+                text,
+                "{}",
+                path.display()
+            );
+
+            let mut written = Vec::new();
+            assembled
+                .write(&mut written)
+                .expect("Failed to write class file");
+            assert!(bytes == written, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_assemble_by_hand() {
+        use structs::instructions::Instruction;
+
+        let class = assemble::assemble(COUNTDOWN).expect("Failed to assemble");
+        let code = method_code(&class, "main");
+        assert_eq!(code.code.len(), 13);
+        assert_eq!(code.code[3].instruction, Instruction::ifle(16));
+        assert_eq!(code.code[8].instruction, Instruction::goto(-14));
+
+        let error = assemble::assemble(".version 52 0\n.class public Broken\n.frobnicate\n")
+            .expect_err("Assembled an unknown directive");
+        assert!(matches!(
+            error,
+            error::ParseError::InvalidAssembly { line: 3, .. }
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs java on the PATH"]
+    fn test_run_assembled_class() {
+        let class = assemble::assemble(COUNTDOWN).expect("Failed to assemble");
+        let dir = std::env::temp_dir().join(format!("jar-reader-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut bytes = Vec::new();
+        class.write(&mut bytes).expect("Failed to write class file");
+        std::fs::write(dir.join("Countdown.class"), bytes).unwrap();
+        let output = Command::new("java")
+            .arg("-cp")
+            .arg(&dir)
+            .arg("Countdown")
+            .output()
+            .expect("Failed to run java");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "3\n2\n1\nLiftoff!\n",
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// Prints a countdown from 3, assembled by `test_assemble_by_hand` and run by
+    /// `test_run_assembled_class`
+    const COUNTDOWN: &str = r#"
+            .version 52 0
+            .class public super Countdown
+            .super java/lang/Object
+
+            .method public static main ([Ljava/lang/String;)V
+                .code stack 2 locals 2
+                        bipush 3
+                        istore_1
+                loop:   iload_1
+                        ifle done
+                        getstatic java/lang/System out Ljava/io/PrintStream;
+                        iload_1
+                        invokevirtual java/io/PrintStream println (I)V
+                        iinc 1 -1
+                        goto loop
+                done:   getstatic java/lang/System out Ljava/io/PrintStream;
+                        ldc String "Liftoff!" ; a comment
+                        invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+                        return
+                    .stackmaptable
+                        append loop Integer
+                        same done
+                    .end stackmaptable
+                .end code
+            .end method
+            .end class
+    "#;
+
+    #[test]
+    fn test_const_pool_builder() {
+        use structs::{const_types::MethodHandleReferenceKind, ConstPoolBuilder};
+
+        let mut builder = ConstPoolBuilder::new();
+        let println = builder
+            .method_ref("java/io/PrintStream", "println", "(Ljava/lang/String;)V")
+            .unwrap();
+        // Utf8, Class, Utf8, Utf8, NameAndType, then the MethodRef
+        assert_eq!(println.index, 6);
+        let class = builder.class("java/io/PrintStream").unwrap();
+        assert_eq!(class.index, 2);
+        assert_eq!(
+            builder
+                .method_ref("java/io/PrintStream", "println", "(Ljava/lang/String;)V")
+                .unwrap(),
+            println
+        );
+        let long = builder.long(1 << 40).unwrap();
+        assert_eq!(long.index, 7);
+        // Strings too long for the class file are refused rather than mistaken for each other
+        let before = builder.len();
+        for text in ["a".repeat(70_000), "b".repeat(70_000)] {
+            assert!(matches!(
+                builder.utf8(&text),
+                Err(error::ParseError::Utf8TooLong(70_000))
+            ));
+        }
+        assert_eq!(builder.len(), before);
+        assert_eq!(builder.integer(1).unwrap().index, 9);
+        let handle = builder
+            .method_handle(MethodHandleReferenceKind::InvokeVirtual, println.clone())
+            .unwrap();
+        assert_eq!(builder.len(), 11);
+
+        let pool = builder.build().unwrap();
+        assert!(pool.entries[8].is_none());
+        assert_eq!(long.get(&pool).unwrap().value, 1 << 40);
+        assert_eq!(
+            handle.get(&pool).unwrap().reference_index.index(),
+            println.index
+        );
+        assert_eq!(
+            class.get(&pool).unwrap().name_index.get(&pool).unwrap().str,
+            "java/io/PrintStream"
+        );
+
+        // Seeded from a class, existing entries keep their index and new ones go at the end
+        let (_, mut class) = read_test_class("testdata/HelloWorld.class");
+        let count = class.constant_pool.entries.len();
+        let object = class.super_class.index;
+        let mut builder = ConstPoolBuilder::from_pool(class.constant_pool.clone());
+        assert_eq!(builder.class("java/lang/Object").unwrap().index, object);
+        let name = builder.utf8("A brand new constant").unwrap();
+        assert_eq!(name.index as usize, count);
+        class.constant_pool = builder.build().unwrap();
+        let mut bytes = Vec::new();
+        class.write(&mut bytes).unwrap();
+        let written = structs::Class::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(
+            name.get(&written.constant_pool).unwrap().str,
+            "A brand new constant"
+        );
+
+        // A Long needs two slots, where an Integer only needs one
+        let mut builder = ConstPoolBuilder::new();
+        for value in 1..u16::MAX as i32 - 1 {
+            builder.integer(value).unwrap();
+        }
+        assert_eq!(builder.len(), u16::MAX as usize - 1);
+        let error = builder.long(0).unwrap_err();
+        assert_eq!(error.kind(), "ConstantPoolFull");
+        builder.integer(0).unwrap();
+        assert_eq!(builder.integer(-1).unwrap_err().kind(), "ConstantPoolFull");
+        assert_eq!(builder.build().unwrap().entries.len(), u16::MAX as usize);
+    }
+
+    #[test]
+    fn test_compact_const_pool() {
+        use structs::{attributes::AttributeInfo, ConstItem, Instruction};
+
+        fn bootstrap_methods(class: &structs::Class) -> Vec<structs::attributes::BootstrapMethod> {
+            class
+                .attributes
+                .iter()
+                .find_map(|attribute| match &attribute.info {
+                    AttributeInfo::BootstrapMethods(attribute) => {
+                        Some(attribute.bootstrap_methods.clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default()
+        }
+
+        // The name of every call site, with the name and arguments of its bootstrap method
+        fn call_sites(class: &structs::Class) -> Vec<(String, String, usize)> {
+            let pool = &class.constant_pool;
+            let methods = bootstrap_methods(class);
+            let mut sites: Vec<_> = pool
+                .entries
+                .iter()
+                .flatten()
+                .filter_map(|item| match item {
+                    ConstItem::InvokeDynamic(site) => Some(site),
+                    _ => None,
+                })
+                .map(|site| {
+                    let name_and_type = site.name_and_type_index.get(pool).unwrap();
+                    let method = &methods[site.bootstrap_method_attr_index as usize];
+                    let handle = method.bootstrap_method_ref.get(pool).unwrap();
+                    (
+                        name_and_type.name_index.get(pool).unwrap().str.clone(),
+                        handle.resolve(pool).unwrap().name,
+                        method.bootstrap_arguments.len(),
+                    )
+                })
+                .collect();
+            sites.sort();
+            sites
+        }
+
+        // Nothing to drop from a class straight out of javac
+        let (bytes, mut class) = read_test_class("testdata/Lambdas.class");
+        assert_eq!(compact::compact(&mut class).unwrap(), 0);
+        let mut written = Vec::new();
+        class.write(&mut written).unwrap();
+        assert_eq!(bytes, written);
+
+        // Without the `Function` lambda, its call site and bootstrap method are dropped
+        let mut expected = call_sites(&class);
+        expected.remove(expected.iter().position(|site| site.0 == "apply").unwrap());
+        let greet = class
+            .methods
+            .iter()
+            .position(|method| method.name_index.get(&class.constant_pool).unwrap().str == "greet")
+            .unwrap();
+        let code = class.methods[greet]
+            .attributes
+            .iter_mut()
+            .find_map(|attribute| match &mut attribute.info {
+                AttributeInfo::Code(code) => Some(code),
+                _ => None,
+            })
+            .unwrap();
+        let site = code
+            .code
+            .iter_mut()
+            .find(|instruction| matches!(instruction.instruction, Instruction::invokedynamic(_)))
+            .unwrap();
+        site.instruction = Instruction::aconst_null;
+        let count = class.constant_pool.entries.len();
+        let freed = compact::compact(&mut class).unwrap();
+        assert!(freed > 0);
+        assert_eq!(class.constant_pool.entries.len(), count - freed);
+        assert_eq!(bootstrap_methods(&class).len(), 5);
+        assert_eq!(call_sites(&class), expected);
+
+        let mut written = Vec::new();
+        class.write(&mut written).unwrap();
+        let read = structs::Class::read(&mut written.as_slice()).unwrap();
+        disassemble::disassemble(&read).expect("Failed to disassemble the compacted class");
+        assert_eq!(call_sites(&read), expected);
+
+        // Without any call sites the attribute goes too
+        let (_, mut class) = read_test_class("testdata/Lambdas.class");
+        let pool = &class.constant_pool;
+        let keep: Vec<_> = class
+            .methods
+            .iter()
+            .map(|method| {
+                let name = &method.name_index.get(pool).unwrap().str;
+                name != "greet" && !name.starts_with("lambda$")
+            })
+            .collect();
+        let mut keep = keep.into_iter();
+        class.methods.retain(|_| keep.next().unwrap());
+        compact::compact(&mut class).unwrap();
+        assert!(bootstrap_methods(&class).is_empty());
+        assert!(!class
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute.info, AttributeInfo::BootstrapMethods(_))));
+        let text = assemble::print(&class).unwrap();
+        assert!(!text.contains("BootstrapMethods") && !text.contains("metafactory"));
+    }
+
+    #[test]
+    fn test_method_handles() {
+        use reader::options::ParseOptions;
+        use structs::{
+            const_types::{MethodHandleReference, MethodHandleReferenceKind},
+            ClassVersion, ConstItem, ConstPoolBuilder, MajorVersion,
+        };
+
+        let (_, class) = read_test_class("testdata/Lambdas.class");
+        let pool = &class.constant_pool;
+        let handles: Vec<_> = pool
+            .entries
+            .iter()
+            .flatten()
+            .filter_map(|item| match item {
+                ConstItem::MethodHandle(handle) => Some(handle),
+                _ => None,
+            })
+            .collect();
+        let metafactory = handles[0].resolve(pool).unwrap();
+        assert_eq!(metafactory.owner, "java/lang/invoke/LambdaMetafactory");
+        assert_eq!(metafactory.name, "metafactory");
+        assert!(metafactory
+            .descriptor
+            .ends_with(")Ljava/lang/invoke/CallSite;"));
+        let constructor = handles
+            .iter()
+            .find(|handle| handle.reference_kind == MethodHandleReferenceKind::NewInvokeSpecial)
+            .unwrap();
+        assert_eq!(constructor.resolve(pool).unwrap().name, "<init>");
+        for handle in &handles {
+            handle.validate(pool, &class.version).unwrap();
+        }
+
+        let mut builder = ConstPoolBuilder::new();
+        let println = builder
+            .method_ref("java/io/PrintStream", "println", "()V")
+            .unwrap();
+        let of = builder
+            .interface_method_ref("java/util/List", "of", "()Ljava/util/List;")
+            .unwrap();
+        let not_a_constructor = builder
+            .method_handle(MethodHandleReferenceKind::NewInvokeSpecial, println.clone())
+            .unwrap();
+        // Built as a class method, the pool knows better
+        let static_interface = builder
+            .add(structs::const_types::MethodHandle {
+                reference_kind: MethodHandleReferenceKind::InvokeStatic,
+                reference_index: MethodHandleReference::Method(structs::Index::new(of.index)),
+            })
+            .unwrap();
+        let wrong_kind = builder
+            .method_handle(MethodHandleReferenceKind::InvokeInterface, println)
+            .unwrap();
+        let pool = builder.build().unwrap();
+
+        let java_7 = ClassVersion {
+            major: MajorVersion::JDK_7,
+            minor: 0,
+        };
+        let java_8 = ClassVersion {
+            major: MajorVersion::JDK_8,
+            minor: 0,
+        };
+        let static_interface = static_interface.get(&pool).unwrap();
+        assert_eq!(
+            static_interface.reference_index,
+            MethodHandleReference::InterfaceMethod(of)
+        );
+        assert_eq!(
+            static_interface.resolve(&pool).unwrap().owner,
+            "java/util/List"
+        );
+        static_interface.validate(&pool, &java_8).unwrap();
+        let err = static_interface.validate(&pool, &java_7).unwrap_err();
+        assert_eq!(err.kind(), "InvalidMethodHandle");
+        let err = not_a_constructor
+            .get(&pool)
+            .unwrap()
+            .validate(&pool, &java_8);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Invalid method handle: NewInvokeSpecial must refer to a constructor, found println"
+        );
+        let err = wrong_kind.get(&pool).unwrap().validate(&pool, &java_8);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Invalid method handle: InvokeInterface must refer to an InterfaceMethodRef, found \
+             MethodRef"
+        );
+
+        // Reading reports broken handles as errors
+        let (_, mut class) = read_test_class("testdata/HelloWorld.class");
+        let mut builder = ConstPoolBuilder::from_pool(class.constant_pool.clone());
+        let init = builder
+            .method_ref("java/lang/Object", "<init>", "()V")
+            .unwrap();
+        let handle = builder
+            .method_handle(MethodHandleReferenceKind::InvokeVirtual, init)
+            .unwrap();
+        class.constant_pool = builder.build().unwrap();
+        let mut bytes = Vec::new();
+        class.write(&mut bytes).unwrap();
+        let err =
+            structs::Class::read_with(&mut bytes.as_slice(), &ParseOptions::strict()).unwrap_err();
+        assert_eq!(err.root().kind(), "InvalidMethodHandle");
+        let class =
+            structs::Class::read_with(&mut bytes.as_slice(), &ParseOptions::lenient()).unwrap();
+        assert_eq!(class.diagnostics.len(), 1);
+        assert_eq!(
+            class.diagnostics[0].path,
+            format!("constant_pool[{}]", handle.index)
+        );
+        assert_eq!(
+            class.diagnostics[0].message,
+            "Invalid method handle: InvokeVirtual cannot refer to <init>"
+        );
+    }
+
+    #[test]
+    fn test_resolve_dynamic() {
+        use structs::{
+            attributes::{Attribute, AttributeInfo, BootstrapMethod, BootstrapMethods},
+            bootstrap::{BootstrapArgument, DynamicKind, MAX_DYNAMIC_DEPTH},
+            const_types::MethodHandleReferenceKind,
+            ConstPoolBuilder, Index,
+        };
+
+        let (_, class) = read_test_class("testdata/Lambdas.class");
+        let dynamics = class.resolve_dynamics().unwrap();
+        assert_eq!(dynamics.len(), 6);
+        let (index, greeter) = &dynamics[0];
+        assert_eq!(*index, 7);
+        assert_eq!(greeter.kind, DynamicKind::CallSite);
+        assert_eq!(greeter.name, "apply");
+        assert_eq!(greeter.descriptor, "(I)Ljava/util/function/Function;");
+        let bootstrap = &greeter.bootstrap_method;
+        assert_eq!(
+            bootstrap.reference_kind,
+            MethodHandleReferenceKind::InvokeStatic
+        );
+        assert_eq!(bootstrap.method.owner, "java/lang/invoke/LambdaMetafactory");
+        assert_eq!(bootstrap.method.name, "metafactory");
+        match &bootstrap.arguments[..] {
+            [BootstrapArgument::MethodType(erased), BootstrapArgument::MethodHandle {
+                reference_kind,
+                member,
+            }, BootstrapArgument::MethodType(instantiated)] => {
+                assert_eq!(erased, "(Ljava/lang/Object;)Ljava/lang/Object;");
+                assert_eq!(*reference_kind, MethodHandleReferenceKind::InvokeStatic);
+                assert_eq!(member.owner, "Lambdas");
+                assert_eq!(member.name, "lambda$greet$0");
+                assert_eq!(instantiated, "(Ljava/lang/String;)Ljava/lang/String;");
+            }
+            arguments => panic!("Unexpected arguments {:?}", arguments),
+        }
+        let (_, concat) = &dynamics[5];
+        assert_eq!(
+            concat.bootstrap_method.method.name,
+            "makeConcatWithConstants"
+        );
+        assert_eq!(
+            concat.bootstrap_method.arguments,
+            [BootstrapArgument::String("Hello \u{1}!\u{1}".to_string())]
+        );
+        assert_eq!(class.bootstrap_methods().len(), 6);
+        assert_eq!(
+            class.bootstrap_method(6).unwrap_err().to_string(),
+            "Bootstrap method 6 is out of range for 6 bootstrap methods"
+        );
+
+        // Dynamic constants, one of which is an argument of the other
+        let (_, mut class) = read_test_class("testdata/HelloWorld.class");
+        let mut builder = ConstPoolBuilder::from_pool(class.constant_pool.clone());
+        let invoke = builder
+            .method_ref(
+                "java/lang/invoke/ConstantBootstraps",
+                "invoke",
+                "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;\
+                 Ljava/lang/invoke/MethodHandle;[Ljava/lang/Object;)Ljava/lang/Object;",
+            )
+            .unwrap();
+        let invoke = builder
+            .method_handle(MethodHandleReferenceKind::InvokeStatic, invoke)
+            .unwrap();
+        let max = builder
+            .method_ref("java/lang/Math", "max", "(JJ)J")
+            .unwrap();
+        let max = builder
+            .method_handle(MethodHandleReferenceKind::InvokeStatic, max)
+            .unwrap();
+        let one = builder.long(1).unwrap();
+        let larger = builder.dynamic(0, "larger", "J").unwrap();
+        let nested = builder.dynamic(1, "nested", "J").unwrap();
+        let broken = builder.dynamic(2, "broken", "J").unwrap();
+        let circular = builder.dynamic(3, "circular", "J").unwrap();
+        let name = builder.utf8("BootstrapMethods").unwrap();
+        let method = |arguments: Vec<u16>| BootstrapMethod {
+            bootstrap_method_ref: invoke.clone(),
+            bootstrap_arguments: arguments.into_iter().map(Index::new).collect(),
+        };
+        let bootstrap_methods = vec![
+            method(vec![max.index, one.index, one.index]),
+            method(vec![max.index, larger.index, one.index]),
+            method(vec![max.index, name.index]),
+            method(vec![max.index, circular.index]),
+        ];
+        class.constant_pool = builder.build().unwrap();
+        class.attributes.push(Attribute {
+            attribute_name_index: name,
+            info: AttributeInfo::BootstrapMethods(BootstrapMethods { bootstrap_methods }),
+        });
+
+        let resolved = class.resolve_dynamic(nested.index).unwrap();
+        assert_eq!(resolved.kind, DynamicKind::Constant);
+        assert_eq!(resolved.descriptor, "J");
+        assert_eq!(resolved.bootstrap_method.method.name, "invoke");
+        match &resolved.bootstrap_method.arguments[..] {
+            [BootstrapArgument::MethodHandle { member, .. }, BootstrapArgument::Dynamic(larger), BootstrapArgument::Long(1)] =>
+            {
+                assert_eq!(member.name, "max");
+                assert_eq!(larger.name, "larger");
+                assert_eq!(
+                    larger.bootstrap_method.arguments[1..],
+                    [BootstrapArgument::Long(1), BootstrapArgument::Long(1)]
+                );
+            }
+            arguments => panic!("Unexpected arguments {:?}", arguments),
+        }
+        let err = class.resolve_dynamic(broken.index).unwrap_err();
+        assert!(matches!(
+            err,
+            error::ParseError::WrongConstantType {
+                expected: "a loadable constant",
+                found: "Utf8",
+                ..
+            }
+        ));
+        let err = class.resolve_dynamic(circular.index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Dynamic constant {} is an argument of its own bootstrap method",
+                circular.index
+            )
+        );
+        assert!(class.resolve_dynamics().is_err());
+        class.attributes.pop();
+        assert_eq!(
+            class.resolve_dynamic(larger.index).unwrap_err().kind(),
+            "BootstrapMethodOutOfRange"
+        );
+
+        // Each constant passes the next one twice, which would double the work at every level if
+        // entries were resolved again for every argument
+        let chain = |length: u16| {
+            let (_, mut class) = read_test_class("testdata/HelloWorld.class");
+            let mut builder = ConstPoolBuilder::from_pool(class.constant_pool.clone());
+            let max = builder
+                .method_ref("java/lang/Math", "max", "(JJ)J")
+                .unwrap();
+            let max = builder
+                .method_handle(MethodHandleReferenceKind::InvokeStatic, max)
+                .unwrap();
+            let method = |arguments: Vec<u16>| BootstrapMethod {
+                bootstrap_method_ref: max.clone(),
+                bootstrap_arguments: arguments.into_iter().map(Index::new).collect(),
+            };
+            let links: Vec<_> = (0..length)
+                .map(|i| builder.dynamic(i, &format!("link{}", i), "J").unwrap())
+                .collect();
+            let one = builder.long(1).unwrap();
+            let mut bootstrap_methods: Vec<_> = links[1..]
+                .iter()
+                .map(|next| method(vec![next.index, next.index]))
+                .collect();
+            bootstrap_methods.push(method(vec![one.index, one.index]));
+            let name = builder.utf8("BootstrapMethods").unwrap();
+            class.constant_pool = builder.build().unwrap();
+            class.attributes.push(Attribute {
+                attribute_name_index: name,
+                info: AttributeInfo::BootstrapMethods(BootstrapMethods { bootstrap_methods }),
+            });
+            let links: Vec<_> = links.iter().map(|link| link.index).collect();
+            (class, links)
+        };
+        let (class, links) = chain(MAX_DYNAMIC_DEPTH as u16);
+        let resolved = class.resolve_dynamic(links[0]).unwrap();
+        match &resolved.bootstrap_method.arguments[..] {
+            [BootstrapArgument::Dynamic(left), BootstrapArgument::Dynamic(right)] => {
+                assert!(std::rc::Rc::ptr_eq(left, right))
+            }
+            arguments => panic!("Unexpected arguments {:?}", arguments),
+        }
+        assert_eq!(class.resolve_dynamics().unwrap().len(), MAX_DYNAMIC_DEPTH);
+        let (class, links) = chain(MAX_DYNAMIC_DEPTH as u16 + 1);
+        assert_eq!(
+            class.resolve_dynamic(links[0]).unwrap_err().to_string(),
+            format!(
+                "Dynamic constant {} is nested more than 64 levels deep",
+                links[MAX_DYNAMIC_DEPTH]
+            )
+        );
+    }
+
+    #[test]
+    fn test_lambdas() {
+        use analysis::lambda::{lambdas, LambdaKind};
+        use descriptor::{BaseType, FieldType};
+        use structs::const_types::MethodHandleReferenceKind;
+
+        let (_, class) = read_test_class("testdata/Lambdas.class");
+        let found = lambdas(&class).unwrap();
+        assert_eq!(found.len(), 4);
+        let greeter = &found[0];
+        assert_eq!(greeter.caller, "greet");
+        assert_eq!(greeter.offset, 1);
+        assert_eq!(greeter.call_site, 7);
+        assert_eq!(greeter.kind, LambdaKind::Lambda);
+        assert_eq!(greeter.interface, "java/util/function/Function");
+        assert_eq!(greeter.method_name, "apply");
+        assert_eq!(
+            greeter.method_type.to_string(),
+            "(Ljava/lang/Object;)Ljava/lang/Object;"
+        );
+        assert_eq!(
+            greeter.instantiated_method_type.to_string(),
+            "(Ljava/lang/String;)Ljava/lang/String;"
+        );
+        assert_eq!(
+            greeter.implementation_kind,
+            MethodHandleReferenceKind::InvokeStatic
+        );
+        assert_eq!(greeter.implementation.name, "lambda$greet$0");
+        assert_eq!(greeter.captured, [FieldType::Base(BaseType::Int)]);
+        assert!(!greeter.serializable);
+        let kinds: Vec<_> = found[1..]
+            .iter()
+            .map(|lambda| (lambda.kind, lambda.implementation_kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    LambdaKind::MethodReference,
+                    MethodHandleReferenceKind::NewInvokeSpecial
+                ),
+                (
+                    LambdaKind::MethodReference,
+                    MethodHandleReferenceKind::InvokeStatic
+                ),
+                (
+                    LambdaKind::MethodReference,
+                    MethodHandleReferenceKind::InvokeStatic
+                ),
+            ]
+        );
+        assert_eq!(found[2].implementation.owner, "java/lang/Math");
+
+        // altMetafactory, and a method reference bound to its receiver
+        let (_, class) = read_test_class("testdata/Callbacks.class");
+        let found = lambdas(&class).unwrap();
+        let callers: Vec<_> = found.iter().map(|lambda| lambda.caller.as_str()).collect();
+        assert_eq!(
+            callers,
+            ["remember", "tagged", "suffix", "$deserializeLambda$"]
+        );
+        let remember = &found[0];
+        assert!(remember.serializable);
+        assert_eq!(remember.kind, LambdaKind::Lambda);
+        assert_eq!(
+            remember.captured,
+            [FieldType::Object("java/lang/String".to_string())]
+        );
+        assert_eq!(found[3].call_site, remember.call_site);
+        assert_eq!(found[3].implementation, remember.implementation);
+        assert_eq!(found[1].marker_interfaces, ["Callbacks$Named"]);
+        assert!(!found[1].serializable);
+        let suffix = &found[2];
+        assert_eq!(suffix.kind, LambdaKind::MethodReference);
+        assert_eq!(suffix.implementation.name, "concat");
+        assert_eq!(
+            suffix.captured,
+            [FieldType::Object("java/lang/String".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        use analysis::concat::{concatenation, Concatenation};
+        use disassemble::options::DisassembleOptions;
+        use structs::{
+            bootstrap::{BootstrapArgument, DynamicKind, ResolvedBootstrapMethod, ResolvedDynamic},
+            const_types::{MethodHandleReferenceKind, ResolvedMemberRef},
+        };
+
+        let (_, class) = read_test_class("testdata/Lambdas.class");
+        let greeting = concatenation(&class, 53).unwrap().unwrap();
+        assert_eq!(greeting.to_string(), "\"Hello \" + arg0 + \"!\" + arg1");
+        assert_eq!(
+            concatenation(&class, 49).unwrap().unwrap().to_string(),
+            "arg0 + arg1 + arg2 + arg3"
+        );
+        assert_eq!(concatenation(&class, 7).unwrap(), None);
+
+        let text = disassemble::disassemble_with(
+            &class,
+            &DisassembleOptions {
+                decode_concat: true,
+            },
+        )
+        .unwrap();
+        assert!(text.contains(
+            "invokedynamic #53,  0             // String concatenation \"Hello \" + arg0 + \"!\" + arg1\n"
+        ));
+        assert!(text.contains("// InvokeDynamic #0:apply:(I)Ljava/util/function/Function;"));
+
+        // A recipe that does not match its call site keeps javap's comment
+        let (_, mut class) = read_test_class("testdata/Lambdas.class");
+        for entry in class.constant_pool.entries.iter_mut().flatten() {
+            if let structs::ConstItem::Utf8(text) = entry {
+                if text.str == "Hello \u{1}!\u{1}" {
+                    *text = structs::const_types::Utf8::new("\u{1}\u{1}\u{1}");
+                }
+            }
+        }
+        assert!(concatenation(&class, 53).is_err());
+        let text = disassemble::disassemble_with(
+            &class,
+            &DisassembleOptions {
+                decode_concat: true,
+            },
+        )
+        .unwrap();
+        assert!(text.contains(
+            "// InvokeDynamic #5:makeConcatWithConstants:(Ljava/lang/String;I)Ljava/lang/String;"
+        ));
+        assert!(text.contains("// String concatenation arg0 + arg1 + arg2 + arg3"));
+
+        // Constants stand in for text containing the tags, and numbers need a string in front
+        let site =
+            |name: &str, descriptor: &str, arguments: Vec<BootstrapArgument>| ResolvedDynamic {
+                kind: DynamicKind::CallSite,
+                name: name.to_string(),
+                descriptor: descriptor.to_string(),
+                bootstrap_method_attr_index: 0,
+                bootstrap_method: ResolvedBootstrapMethod {
+                    reference_kind: MethodHandleReferenceKind::InvokeStatic,
+                    method: ResolvedMemberRef {
+                        owner: "java/lang/invoke/StringConcatFactory".to_string(),
+                        name: name.to_string(),
+                        descriptor: String::new(),
+                    },
+                    arguments,
+                },
+            };
+        let decode = |site: ResolvedDynamic| Concatenation::decode(1, &site);
+        let tagged = site(
+            "makeConcatWithConstants",
+            "(IJ)Ljava/lang/String;",
+            vec![
+                BootstrapArgument::String("\u{1}\u{1}\u{2}".to_string()),
+                BootstrapArgument::String("tag\u{1}".to_string()),
+            ],
+        );
+        assert_eq!(
+            decode(tagged).unwrap().unwrap().to_string(),
+            "\"\" + arg0 + arg1 + \"tag\\u0001\""
+        );
+        let plain = site(
+            "makeConcat",
+            "(Ljava/lang/Object;I)Ljava/lang/String;",
+            vec![],
+        );
+        assert_eq!(
+            decode(plain).unwrap().unwrap().to_string(),
+            "\"\" + arg0 + arg1"
+        );
+        let short = site(
+            "makeConcatWithConstants",
+            "(I)Ljava/lang/String;",
+            vec![BootstrapArgument::String("\u{1}\u{1}".to_string())],
+        );
+        assert_eq!(
+            decode(short).unwrap_err().to_string(),
+            "The recipe of call site 1 uses more arguments than it passes"
+        );
+    }
+
+    #[test]
+    fn test_access_flags() {
+        use reader::options::ParseOptions;
+        use structs::{
+            access_flags::{ClassFlags, FieldFlags, MethodFlags},
+            ClassVersion, MajorVersion,
+        };
+
+        let (_, class) = read_test_class("testdata/Lambdas.class");
+        let flags: Vec<_> = class
+            .fields
+            .iter()
+            .map(|field| field.access_flags)
+            .collect();
+        assert!(flags.contains(&(FieldFlags::PRIVATE | FieldFlags::TRANSIENT)));
+        assert!(flags.contains(&(FieldFlags::PRIVATE | FieldFlags::VOLATILE)));
+        let text = assemble::print(&class).unwrap();
+        assert!(text.contains(".field private transient "));
+        assert!(text.contains(".field private volatile "));
+        assert_eq!(
+            FieldFlags::from_bits(0x4019),
+            Some(FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL | FieldFlags::ENUM)
+        );
+
+        let interface = ClassFlags::PUBLIC | ClassFlags::INTERFACE | ClassFlags::ABSTRACT;
+        let constant = FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL;
+        assert!(constant.validate(interface).is_ok());
+        assert_eq!(
+            FieldFlags::PUBLIC
+                .validate(interface)
+                .unwrap_err()
+                .to_string(),
+            "Illegal access flags 0x1: interface fields must be public, static and final"
+        );
+        assert_eq!(
+            (constant | FieldFlags::TRANSIENT)
+                .validate(interface)
+                .unwrap_err()
+                .to_string(),
+            "Illegal access flags 0x99: interface fields cannot be transient"
+        );
+        assert!((FieldFlags::FINAL | FieldFlags::VOLATILE)
+            .validate(ClassFlags::PUBLIC)
+            .is_err());
+        assert!((FieldFlags::PUBLIC | FieldFlags::PRIVATE)
+            .validate(ClassFlags::PUBLIC)
+            .is_err());
+
+        let java_17 = ClassVersion {
+            major: MajorVersion::JDK_17,
+            minor: 0,
+        };
+        let java_6 = ClassVersion {
+            major: MajorVersion::JDK_6,
+            minor: 0,
+        };
+        let private = MethodFlags::PRIVATE;
+        assert!(private.validate(interface, "helper", &java_17).is_ok());
+        assert_eq!(
+            private
+                .validate(interface, "helper", &java_6)
+                .unwrap_err()
+                .to_string(),
+            "Illegal access flags 0x2: interface methods must be public and abstract before Java 8"
+        );
+        assert_eq!(
+            (MethodFlags::PUBLIC | MethodFlags::FINAL)
+                .validate(interface, "run", &java_17)
+                .unwrap_err()
+                .to_string(),
+            "Illegal access flags 0x11: interface methods cannot be final"
+        );
+        assert_eq!(
+            (MethodFlags::ABSTRACT | MethodFlags::STATIC | MethodFlags::NATIVE)
+                .validate(ClassFlags::ABSTRACT, "run", &java_17)
+                .unwrap_err()
+                .to_string(),
+            "Illegal access flags 0x508: abstract methods cannot be static or native"
+        );
+        assert!((MethodFlags::ABSTRACT | MethodFlags::STRICT)
+            .validate(ClassFlags::ABSTRACT, "run", &java_6)
+            .is_err());
+        assert!((MethodFlags::ABSTRACT | MethodFlags::STRICT)
+            .validate(ClassFlags::ABSTRACT, "run", &java_17)
+            .is_ok());
+        assert!(MethodFlags::STATIC
+            .validate(ClassFlags::PUBLIC, "<init>", &java_17)
+            .is_err());
+        assert!(MethodFlags::PUBLIC
+            .validate(ClassFlags::PUBLIC, "<clinit>", &java_17)
+            .is_err());
+        assert!(MethodFlags::PUBLIC
+            .validate(ClassFlags::PUBLIC, "<clinit>", &java_6)
+            .is_ok());
+
+        // The reader checks the flags of every member against the class
+        let (_, mut class) = read_test_class("testdata/Lambdas$Shape.class");
+        class.fields[0].access_flags.remove(FieldFlags::FINAL);
+        let mut bytes = Vec::new();
+        class.write(&mut bytes).unwrap();
+        let err =
+            structs::Class::read_with(&mut bytes.as_slice(), &ParseOptions::strict()).unwrap_err();
+        assert_eq!(err.root().kind(), "IllegalAccessFlags");
+        assert_eq!(err.path(), Some("fields[0]"));
+        let class =
+            structs::Class::read_with(&mut bytes.as_slice(), &ParseOptions::lenient()).unwrap();
+        assert_eq!(class.diagnostics.len(), 1);
+        assert_eq!(class.diagnostics[0].path, "fields[0]");
+    }
 }
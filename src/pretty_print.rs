@@ -30,7 +30,7 @@ impl<T: GetPretty> GetPretty for Vec<T> {
     fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
         let mut result = String::new();
         for item in self {
-            result.push_str("\n");
+            result.push('\n');
             result.push_str(&item.get_pretty(pool, tabs)?);
         }
         Ok(result)
@@ -46,6 +46,8 @@ impl<T: GetPretty> GetPretty for Option<T> {
     }
 }
 
+/// Implements `GetPretty` and `ToJs` together, so everything that can be printed is also
+/// exposed to JavaScript with the same shape
 #[macro_export]
 macro_rules! impl_get_pretty {
     () => {};
@@ -61,13 +63,22 @@ macro_rules! impl_get_pretty {
             $($variant = $value,)*
         }
 
-        impl crate::pretty_print::GetPretty for $name {
-            fn get_pretty(&self, _pool: &crate::structs::ConstPool, tabs: usize) -> crate::Result<String> {
+        impl $crate::pretty_print::GetPretty for $name {
+            fn get_pretty(&self, _pool: &$crate::structs::ConstPool, tabs: usize) -> $crate::Result<String> {
                 match self {
                     $($name::$variant => Ok(format!("{:indent$}{}", "", stringify!($variant), indent=tabs)),)*
                 }
             }
         }
+
+        impl $crate::js::ToJs for $name {
+            fn to_js<'a, C: neon::context::Context<'a>>(&self, cx: &mut C, _pool: &$crate::structs::ConstPool) -> neon::result::JsResult<'a, neon::types::JsValue> {
+                let name = match self {
+                    $($name::$variant => stringify!($variant),)*
+                };
+                Ok(neon::handle::Handle::upcast(&cx.string(name)))
+            }
+        }
         impl_get_pretty!($($rest)*);
     };
     (
@@ -82,13 +93,22 @@ macro_rules! impl_get_pretty {
             $($variant($value),)*
         }
 
-        impl crate::pretty_print::GetPretty for $name {
-            fn get_pretty(&self, pool: &crate::structs::ConstPool, tabs: usize) -> crate::Result<String> {
+        impl $crate::pretty_print::GetPretty for $name {
+            fn get_pretty(&self, pool: &$crate::structs::ConstPool, tabs: usize) -> $crate::Result<String> {
                 match self {
                     $($name::$variant(x) => x.get_pretty(pool, tabs),)*
                 }
             }
         }
+
+        impl $crate::js::ToJs for $name {
+            fn to_js<'a, C: neon::context::Context<'a>>(&self, cx: &mut C, pool: &$crate::structs::ConstPool) -> neon::result::JsResult<'a, neon::types::JsValue> {
+                let (kind, value) = match self {
+                    $($name::$variant(x) => (stringify!($variant), $crate::js::ToJs::to_js(x, cx, pool)?),)*
+                };
+                $crate::js::tagged(cx, kind, &[("value", value)])
+            }
+        }
         impl_get_pretty!($($rest)*);
     };
     (
@@ -104,11 +124,11 @@ macro_rules! impl_get_pretty {
         }
 
 
-        impl crate::pretty_print::GetPretty for $name {
-            fn get_pretty(&self, pool: &crate::structs::ConstPool, tabs: usize) -> crate::Result<String> {
+        impl $crate::pretty_print::GetPretty for $name {
+            fn get_pretty(&self, pool: &$crate::structs::ConstPool, tabs: usize) -> $crate::Result<String> {
                 let mut result = String::new();
                 result.push_str(&format!("{:indent$}{} {{", "", stringify!($name), indent=tabs));
-                result.push_str("\n");
+                result.push('\n');
                 $(
                     result.push_str(&format!(
                         "{:indent$}{}: {}",
@@ -117,13 +137,24 @@ macro_rules! impl_get_pretty {
                         self.$field.get_pretty(pool, tabs + 4)?.trim_start_matches(' '),
                         indent=tabs + 2
                     ));
-                    result.push_str("\n");
+                    result.push('\n');
                 )*
                 result.push_str(&format!("{:indent$}}}", "", indent=tabs));
                 Ok(result)
             }
         }
 
+        impl $crate::js::ToJs for $name {
+            fn to_js<'a, C: neon::context::Context<'a>>(&self, cx: &mut C, pool: &$crate::structs::ConstPool) -> neon::result::JsResult<'a, neon::types::JsValue> {
+                let object = cx.empty_object();
+                $(
+                    let value = $crate::js::ToJs::to_js(&self.$field, cx, pool)?;
+                    neon::prelude::Object::set(&*object, cx, stringify!($field), value)?;
+                )*
+                Ok(neon::handle::Handle::upcast(&object))
+            }
+        }
+
         impl_get_pretty!($($rest)*);
     };
     (
@@ -134,20 +165,26 @@ macro_rules! impl_get_pretty {
         $(#[$attr])*
         $vis struct $name($($type)*);
 
-        impl crate::pretty_print::GetPretty for $name {
-            fn get_pretty(&self, _pool: &crate::structs::ConstPool, tabs: usize) -> crate::Result<String> {
+        impl $crate::pretty_print::GetPretty for $name {
+            fn get_pretty(&self, _pool: &$crate::structs::ConstPool, tabs: usize) -> $crate::Result<String> {
                 let mut result = String::new();
                 result.push_str(&format!("{:indent$}{} {{", "", stringify!($name), indent=tabs));
-                result.push_str("\n");
+                result.push('\n');
                 $(
                     result.push_str(&format!("{:indent$}{}:", "", stringify!($type), indent=tabs + 2));
-                    result.push_str("\n");
+                    result.push('\n');
                 )*
                 result.push_str(&format!("{:indent$}}}", "", indent=tabs));
                 Ok(result)
             }
         }
 
+        impl $crate::js::ToJs for $name {
+            fn to_js<'a, C: neon::context::Context<'a>>(&self, cx: &mut C, _pool: &$crate::structs::ConstPool) -> neon::result::JsResult<'a, neon::types::JsValue> {
+                Ok(neon::handle::Handle::upcast(&cx.empty_object()))
+            }
+        }
+
         impl_get_pretty!($($rest)*);
     };
     (
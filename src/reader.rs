@@ -4,6 +4,28 @@ pub mod class;
 pub mod class_version;
 pub mod const_pool;
 pub mod const_types;
+pub(crate) mod context;
 pub mod fields;
+pub mod instructions;
 pub mod interfaces;
 pub mod methods;
+pub mod options;
+
+use std::io::Read;
+
+use crate::{Readable, Result};
+
+/// Reads `count` items, giving any error the path `name[i]` of the item that failed.
+pub(crate) fn read_list<R: Read, T: Readable>(
+    reader: &mut R,
+    name: &str,
+    count: usize,
+) -> Result<Vec<T>> {
+    let mut items = Vec::with_capacity(count);
+    for i in 0..count {
+        items.push(context::scope(format_args!("{}[{}]", name, i), || {
+            T::read(reader)
+        })?);
+    }
+    Ok(items)
+}
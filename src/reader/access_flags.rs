@@ -4,9 +4,13 @@ use byteorder::ReadBytesExt;
 
 use crate::{
     error::ParseError,
-    structs::access_flags::{
-        ClassFlags, ExportsFlags, InnerClassFlags, MethodFlags, MethodParameterFlags, ModuleFlags,
-        RequiresFlags,
+    reader::context,
+    structs::{
+        access_flags::{
+            ClassFlags, ExportsFlags, FieldFlags, InnerClassFlags, MethodFlags,
+            MethodParameterFlags, ModuleFlags, RequiresFlags,
+        },
+        Severity,
     },
     Readable, Result,
 };
@@ -15,8 +19,14 @@ macro_rules! impl_readable_for_access_flags {
     ($($flag:ident),*) => {
         $(impl Readable for $flag {
             fn read<R: Read>(reader: &mut R) -> Result<Self> {
+                let offset = context::position();
                 let flags = reader.read_u16::<byteorder::BigEndian>()?;
-                Ok(Self::from_bits(flags).ok_or(ParseError::InvalidAccessFlags(flags))?)
+                if Self::from_bits(flags).is_none() {
+                    let err = ParseError::InvalidAccessFlags(flags).at_offset(offset);
+                    context::report(Severity::Error, err)?;
+                }
+                // Undefined bits are kept so that the class is written back unchanged
+                Ok(Self::from_bits_retain(flags))
             }
         })*
     };
@@ -24,6 +34,7 @@ macro_rules! impl_readable_for_access_flags {
 
 impl_readable_for_access_flags! {
     ClassFlags,
+    FieldFlags,
     MethodFlags,
     ExportsFlags,
     RequiresFlags,
@@ -3,6 +3,7 @@ use std::{convert::TryInto, io::Read};
 use byteorder::{BigEndian, ReadBytesExt};
 
 use crate::{
+    error::ParseError,
     structs::{
         access_flags::{
             ExportsFlags, InnerClassFlags, MethodParameterFlags, ModuleFlags, OpensFlags,
@@ -15,14 +16,12 @@ use crate::{
     Readable, Result,
 };
 
+use super::{context, instructions::read_code, read_list};
+
 impl Readable for Vec<Attribute> {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let attributes_count = reader.read_u16::<BigEndian>()?;
-        let mut attributes = Vec::with_capacity(attributes_count as usize);
-        for _ in 0..attributes_count {
-            attributes.push(Attribute::read(reader)?);
-        }
-        Ok(attributes)
+        read_list(reader, "attributes", attributes_count as usize)
     }
 }
 
@@ -31,7 +30,11 @@ impl Readable for Attribute {
         let attribute_name_index = Index::read(reader)?;
         let attribute_length = reader.read_u32::<BigEndian>()?;
         let mut data = reader.take(attribute_length as u64);
-        let info = AttributeInfo::Unknown(Unknown::read(&mut data)?);
+        let unknown = Unknown::read(&mut data)?;
+        if unknown.bytes.len() != attribute_length as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let info = AttributeInfo::Unknown(unknown);
         Ok(Self {
             attribute_name_index,
             info,
@@ -41,9 +44,10 @@ impl Readable for Attribute {
 
 impl Readable for Unknown {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let offset = context::position();
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes)?;
-        Ok(Self { bytes })
+        Ok(Self { bytes, offset })
     }
 }
 
@@ -69,15 +73,16 @@ impl Readable for Code {
         let max_stack = reader.read_u16::<BigEndian>()?;
         let max_locals = reader.read_u16::<BigEndian>()?;
         let code_length = reader.read_u32::<BigEndian>()?;
-        let mut code = Vec::with_capacity(code_length as usize);
-        for _ in 0..code_length {
-            code.push(reader.read_u8()?.try_into()?);
+        let code_start = context::position();
+        let mut bytes = Vec::new();
+        reader.take(code_length as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != code_length as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
         }
+        let code = context::at(code_start, || read_code(&bytes))?;
         let exception_table_length = reader.read_u16::<BigEndian>()?;
-        let mut exception_table = Vec::with_capacity(exception_table_length as usize);
-        for _ in 0..exception_table_length {
-            exception_table.push(ExceptionTable::read(reader)?);
-        }
+        let exception_table =
+            read_list(reader, "exception_table", exception_table_length as usize)?;
         let attributes = Attributes::read(reader)?;
         Ok(Self {
             max_stack,
@@ -104,13 +109,101 @@ impl Readable for ExceptionTable {
     }
 }
 
+impl Readable for StackMapTable {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let number_of_entries = reader.read_u16::<BigEndian>()?;
+        let entries = read_list(reader, "entries", number_of_entries as usize)?;
+        Ok(Self { entries })
+    }
+}
+
+impl Readable for StackMapFrame {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let frame_type = reader.read_u8()?;
+        Ok(match frame_type {
+            0..=63 => StackMapFrame::Same {
+                offset_delta: frame_type as u16,
+            },
+            64..=127 => StackMapFrame::SameLocals1StackItem {
+                offset_delta: frame_type as u16 - 64,
+                stack: VerificationTypeInfo::read(reader)?,
+            },
+            247 => StackMapFrame::SameLocals1StackItemExtended {
+                offset_delta: reader.read_u16::<BigEndian>()?,
+                stack: VerificationTypeInfo::read(reader)?,
+            },
+            248..=250 => StackMapFrame::Chop {
+                k: 251 - frame_type,
+                offset_delta: reader.read_u16::<BigEndian>()?,
+            },
+            251 => StackMapFrame::SameExtended {
+                offset_delta: reader.read_u16::<BigEndian>()?,
+            },
+            252..=254 => {
+                let offset_delta = reader.read_u16::<BigEndian>()?;
+                let mut locals = Vec::with_capacity(frame_type as usize - 251);
+                for _ in 251..frame_type {
+                    locals.push(VerificationTypeInfo::read(reader)?);
+                }
+                StackMapFrame::Append {
+                    offset_delta,
+                    locals,
+                }
+            }
+            255 => {
+                let offset_delta = reader.read_u16::<BigEndian>()?;
+                let number_of_locals = reader.read_u16::<BigEndian>()?;
+                let locals = read_list(reader, "locals", number_of_locals as usize)?;
+                let number_of_stack_items = reader.read_u16::<BigEndian>()?;
+                let stack = read_list(reader, "stack", number_of_stack_items as usize)?;
+                StackMapFrame::Full {
+                    offset_delta,
+                    locals,
+                    stack,
+                }
+            }
+            // 128 to 246 are reserved for future use
+            _ => {
+                return Err(ParseError::Unrecognized(
+                    "StackMapFrame",
+                    frame_type.to_string(),
+                ))
+            }
+        })
+    }
+}
+
+impl Readable for VerificationTypeInfo {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let tag = reader.read_u8()?;
+        Ok(match tag {
+            0 => VerificationTypeInfo::Top,
+            1 => VerificationTypeInfo::Integer,
+            2 => VerificationTypeInfo::Float,
+            3 => VerificationTypeInfo::Double,
+            4 => VerificationTypeInfo::Long,
+            5 => VerificationTypeInfo::Null,
+            6 => VerificationTypeInfo::UninitializedThis,
+            7 => VerificationTypeInfo::Object(Index::read(reader)?),
+            8 => VerificationTypeInfo::Uninitialized(reader.read_u16::<BigEndian>()?),
+            _ => {
+                return Err(ParseError::Unrecognized(
+                    "VerificationTypeInfo",
+                    tag.to_string(),
+                ))
+            }
+        })
+    }
+}
+
 impl Readable for Exceptions {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let number_of_exceptions = reader.read_u16::<BigEndian>()?;
-        let mut exception_index_table = Vec::with_capacity(number_of_exceptions as usize);
-        for _ in 0..number_of_exceptions {
-            exception_index_table.push(Index::read(reader)?);
-        }
+        let exception_index_table = read_list(
+            reader,
+            "exception_index_table",
+            number_of_exceptions as usize,
+        )?;
         Ok(Self {
             exception_index_table,
         })
@@ -120,10 +213,7 @@ impl Readable for Exceptions {
 impl Readable for InnerClasses {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let number_of_classes = reader.read_u16::<BigEndian>()?;
-        let mut classes = Vec::with_capacity(number_of_classes as usize);
-        for _ in 0..number_of_classes {
-            classes.push(InnerClass::read(reader)?);
-        }
+        let classes = read_list(reader, "classes", number_of_classes as usize)?;
         Ok(Self { classes })
     }
 }
@@ -184,10 +274,11 @@ impl Readable for SourceDebugExtension {
 impl Readable for LineNumberTable {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let line_number_table_length = reader.read_u16::<BigEndian>()?;
-        let mut line_number_table = Vec::with_capacity(line_number_table_length as usize);
-        for _ in 0..line_number_table_length {
-            line_number_table.push(LineNumber::read(reader)?);
-        }
+        let line_number_table = read_list(
+            reader,
+            "line_number_table",
+            line_number_table_length as usize,
+        )?;
         Ok(Self { line_number_table })
     }
 }
@@ -206,10 +297,11 @@ impl Readable for LineNumber {
 impl Readable for LocalVariableTable {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let local_variable_table_length = reader.read_u16::<BigEndian>()?;
-        let mut local_variable_table = Vec::with_capacity(local_variable_table_length as usize);
-        for _ in 0..local_variable_table_length {
-            local_variable_table.push(LocalVariable::read(reader)?);
-        }
+        let local_variable_table = read_list(
+            reader,
+            "local_variable_table",
+            local_variable_table_length as usize,
+        )?;
         Ok(Self {
             local_variable_table,
         })
@@ -236,11 +328,11 @@ impl Readable for LocalVariable {
 impl Readable for LocalVariableTypeTable {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let local_variable_type_table_length = reader.read_u16::<BigEndian>()?;
-        let mut local_variable_type_table =
-            Vec::with_capacity(local_variable_type_table_length as usize);
-        for _ in 0..local_variable_type_table_length {
-            local_variable_type_table.push(LocalVariableType::read(reader)?);
-        }
+        let local_variable_type_table = read_list(
+            reader,
+            "local_variable_type_table",
+            local_variable_type_table_length as usize,
+        )?;
         Ok(Self {
             local_variable_type_table,
         })
@@ -270,13 +362,247 @@ impl Readable for Deprecated {
     }
 }
 
+impl Readable for RuntimeVisibleAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_annotations = reader.read_u16::<BigEndian>()?;
+        let annotations = read_list(reader, "annotations", num_annotations as usize)?;
+        Ok(Self { annotations })
+    }
+}
+
+impl Readable for RuntimeInvisibleAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_annotations = reader.read_u16::<BigEndian>()?;
+        let annotations = read_list(reader, "annotations", num_annotations as usize)?;
+        Ok(Self { annotations })
+    }
+}
+
+impl Readable for ParameterAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_annotations = reader.read_u16::<BigEndian>()?;
+        let annotations = read_list(reader, "annotations", num_annotations as usize)?;
+        Ok(Self { annotations })
+    }
+}
+
+impl Readable for RuntimeVisibleParameterAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_parameters = reader.read_u8()?;
+        let parameter_annotations =
+            read_list(reader, "parameter_annotations", num_parameters as usize)?;
+        Ok(Self {
+            parameter_annotations,
+        })
+    }
+}
+
+impl Readable for RuntimeInvisibleParameterAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_parameters = reader.read_u8()?;
+        let parameter_annotations =
+            read_list(reader, "parameter_annotations", num_parameters as usize)?;
+        Ok(Self {
+            parameter_annotations,
+        })
+    }
+}
+
+impl Readable for Annotation {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let type_index = Index::read(reader)?;
+        let num_element_value_pairs = reader.read_u16::<BigEndian>()?;
+        let element_value_pairs = read_list(
+            reader,
+            "element_value_pairs",
+            num_element_value_pairs as usize,
+        )?;
+        Ok(Self {
+            type_index,
+            element_value_pairs,
+        })
+    }
+}
+
+impl Readable for ElementValuePair {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let element_name_index = Index::read(reader)?;
+        let value = ElementValue::read(reader)?;
+        Ok(Self {
+            element_name_index,
+            value,
+        })
+    }
+}
+
+impl Readable for ElementValue {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        context::nested("Element values", MAX_ELEMENT_VALUE_DEPTH, || {
+            read_element_value(reader)
+        })
+    }
+}
+
+fn read_element_value<R: Read>(reader: &mut R) -> Result<ElementValue> {
+    let tag = reader.read_u8()?;
+    Ok(match tag {
+        b'B' => ElementValue::Byte(Index::read(reader)?),
+        b'C' => ElementValue::Char(Index::read(reader)?),
+        b'D' => ElementValue::Double(Index::read(reader)?),
+        b'F' => ElementValue::Float(Index::read(reader)?),
+        b'I' => ElementValue::Int(Index::read(reader)?),
+        b'J' => ElementValue::Long(Index::read(reader)?),
+        b'S' => ElementValue::Short(Index::read(reader)?),
+        b'Z' => ElementValue::Boolean(Index::read(reader)?),
+        b's' => ElementValue::String(Index::read(reader)?),
+        b'e' => ElementValue::Enum {
+            type_name_index: Index::read(reader)?,
+            const_name_index: Index::read(reader)?,
+        },
+        b'c' => ElementValue::Class(Index::read(reader)?),
+        b'@' => ElementValue::Annotation(Annotation::read(reader)?),
+        b'[' => {
+            let num_values = reader.read_u16::<BigEndian>()?;
+            let values = read_list(reader, "values", num_values as usize)?;
+            ElementValue::Array(values)
+        }
+        _ => {
+            return Err(ParseError::Unrecognized(
+                "ElementValue",
+                (tag as char).to_string(),
+            ))
+        }
+    })
+}
+
+impl Readable for RuntimeVisibleTypeAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_annotations = reader.read_u16::<BigEndian>()?;
+        let annotations = read_list(reader, "annotations", num_annotations as usize)?;
+        Ok(Self { annotations })
+    }
+}
+
+impl Readable for RuntimeInvisibleTypeAnnotations {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let num_annotations = reader.read_u16::<BigEndian>()?;
+        let annotations = read_list(reader, "annotations", num_annotations as usize)?;
+        Ok(Self { annotations })
+    }
+}
+
+impl Readable for TypeAnnotation {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let target_type: TargetType = (reader.read_u8()? as u16).try_into()?;
+        let target_info = TargetInfo::read_for(target_type, reader)?;
+        let target_path = TypePath::read(reader)?;
+        let annotation = Annotation::read(reader)?;
+        Ok(Self {
+            target_type,
+            target_info,
+            target_path,
+            annotation,
+        })
+    }
+}
+
+impl TargetInfo {
+    /// Reads the `target_info` union, whose layout is selected by the target type
+    pub fn read_for<R: Read>(target_type: TargetType, reader: &mut R) -> Result<Self> {
+        Ok(match target_type {
+            TargetType::ClassTypeParameter | TargetType::MethodTypeParameter => {
+                TargetInfo::TypeParameter {
+                    type_parameter_index: reader.read_u8()?,
+                }
+            }
+            TargetType::Supertype => TargetInfo::Supertype {
+                supertype_index: reader.read_u16::<BigEndian>()?,
+            },
+            TargetType::ClassTypeParameterBound | TargetType::MethodTypeParameterBound => {
+                TargetInfo::TypeParameterBound {
+                    type_parameter_index: reader.read_u8()?,
+                    bound_index: reader.read_u8()?,
+                }
+            }
+            TargetType::Field | TargetType::MethodReturn | TargetType::MethodReceiver => {
+                TargetInfo::Empty
+            }
+            TargetType::MethodFormalParameter => TargetInfo::FormalParameter {
+                formal_parameter_index: reader.read_u8()?,
+            },
+            TargetType::Throws => TargetInfo::Throws {
+                throws_type_index: reader.read_u16::<BigEndian>()?,
+            },
+            TargetType::LocalVariable | TargetType::ResourceVariable => {
+                let table_length = reader.read_u16::<BigEndian>()?;
+                let table = read_list(reader, "table", table_length as usize)?;
+                TargetInfo::Localvar { table }
+            }
+            TargetType::ExceptionParameter => TargetInfo::Catch {
+                exception_table_index: reader.read_u16::<BigEndian>()?,
+            },
+            TargetType::Instanceof
+            | TargetType::New
+            | TargetType::ConstructorReference
+            | TargetType::MethodReference => TargetInfo::Offset {
+                offset: reader.read_u16::<BigEndian>()?,
+            },
+            TargetType::Cast
+            | TargetType::ConstructorInvocationTypeArgument
+            | TargetType::MethodInvocationTypeArgument
+            | TargetType::ConstructorReferenceTypeArgument
+            | TargetType::MethodReferenceTypeArgument => TargetInfo::TypeArgument {
+                offset: reader.read_u16::<BigEndian>()?,
+                type_argument_index: reader.read_u8()?,
+            },
+        })
+    }
+}
+
+impl Readable for LocalVarTarget {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let start_pc = reader.read_u16::<BigEndian>()?;
+        let length = reader.read_u16::<BigEndian>()?;
+        let index = reader.read_u16::<BigEndian>()?;
+        Ok(Self {
+            start_pc,
+            length,
+            index,
+        })
+    }
+}
+
+impl Readable for TypePath {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let path_length = reader.read_u8()?;
+        let path = read_list(reader, "path", path_length as usize)?;
+        Ok(Self { path })
+    }
+}
+
+impl Readable for TypePathEntry {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let type_path_kind = (reader.read_u8()? as u16).try_into()?;
+        let type_argument_index = reader.read_u8()?;
+        Ok(Self {
+            type_path_kind,
+            type_argument_index,
+        })
+    }
+}
+
+impl Readable for AnnotationDefault {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let default_value = ElementValue::read(reader)?;
+        Ok(Self { default_value })
+    }
+}
+
 impl Readable for BootstrapMethods {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let num_bootstrap_methods = reader.read_u16::<BigEndian>()?;
-        let mut bootstrap_methods = Vec::with_capacity(num_bootstrap_methods as usize);
-        for _ in 0..num_bootstrap_methods {
-            bootstrap_methods.push(BootstrapMethod::read(reader)?);
-        }
+        let bootstrap_methods =
+            read_list(reader, "bootstrap_methods", num_bootstrap_methods as usize)?;
         Ok(Self { bootstrap_methods })
     }
 }
@@ -285,10 +611,11 @@ impl Readable for BootstrapMethod {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let bootstrap_method_ref = Index::read(reader)?;
         let num_bootstrap_arguments = reader.read_u16::<BigEndian>()?;
-        let mut bootstrap_arguments = Vec::with_capacity(num_bootstrap_arguments as usize);
-        for _ in 0..num_bootstrap_arguments {
-            bootstrap_arguments.push(Index::read(reader)?);
-        }
+        let bootstrap_arguments = read_list(
+            reader,
+            "bootstrap_arguments",
+            num_bootstrap_arguments as usize,
+        )?;
         Ok(Self {
             bootstrap_method_ref,
             bootstrap_arguments,
@@ -299,10 +626,7 @@ impl Readable for BootstrapMethod {
 impl Readable for MethodParameters {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let parameters_count = reader.read_u8()?;
-        let mut parameters = Vec::with_capacity(parameters_count as usize);
-        for _ in 0..parameters_count {
-            parameters.push(MethodParameter::read(reader)?);
-        }
+        let parameters = read_list(reader, "parameters", parameters_count as usize)?;
         Ok(Self { parameters })
     }
 }
@@ -324,30 +648,15 @@ impl Readable for Module {
         let flags = ModuleFlags::read(reader)?;
         let version_index = Index::read(reader)?;
         let requires_count = reader.read_u16::<BigEndian>()?;
-        let mut requires = Vec::with_capacity(requires_count as usize);
-        for _ in 0..requires_count {
-            requires.push(Requires::read(reader)?);
-        }
+        let requires = read_list(reader, "requires", requires_count as usize)?;
         let exports_count = reader.read_u16::<BigEndian>()?;
-        let mut exports = Vec::with_capacity(exports_count as usize);
-        for _ in 0..exports_count {
-            exports.push(Exports::read(reader)?);
-        }
+        let exports = read_list(reader, "exports", exports_count as usize)?;
         let opens_count = reader.read_u16::<BigEndian>()?;
-        let mut opens = Vec::with_capacity(opens_count as usize);
-        for _ in 0..opens_count {
-            opens.push(Opens::read(reader)?);
-        }
+        let opens = read_list(reader, "opens", opens_count as usize)?;
         let uses_count = reader.read_u16::<BigEndian>()?;
-        let mut uses = Vec::with_capacity(uses_count as usize);
-        for _ in 0..uses_count {
-            uses.push(Uses::read(reader)?);
-        }
+        let uses = read_list(reader, "uses", uses_count as usize)?;
         let provides_count = reader.read_u16::<BigEndian>()?;
-        let mut provides = Vec::with_capacity(provides_count as usize);
-        for _ in 0..provides_count {
-            provides.push(Provides::read(reader)?);
-        }
+        let provides = read_list(reader, "provides", provides_count as usize)?;
         Ok(Self {
             name_index,
             flags,
@@ -379,10 +688,7 @@ impl Readable for Exports {
         let exports_index = Index::read(reader)?;
         let flags = ExportsFlags::read(reader)?;
         let exports_to_count = reader.read_u16::<BigEndian>()?;
-        let mut to_index = Vec::with_capacity(exports_to_count as usize);
-        for _ in 0..exports_to_count {
-            to_index.push(Index::read(reader)?);
-        }
+        let to_index = read_list(reader, "to_index", exports_to_count as usize)?;
         Ok(Self {
             exports_index,
             flags,
@@ -396,10 +702,7 @@ impl Readable for Opens {
         let opens_index = Index::read(reader)?;
         let flags = OpensFlags::read(reader)?;
         let opens_to_count = reader.read_u16::<BigEndian>()?;
-        let mut to_index = Vec::with_capacity(opens_to_count as usize);
-        for _ in 0..opens_to_count {
-            to_index.push(Index::read(reader)?);
-        }
+        let to_index = read_list(reader, "to_index", opens_to_count as usize)?;
         Ok(Self {
             opens_index,
             flags,
@@ -419,10 +722,7 @@ impl Readable for Provides {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let provides_index = Index::read(reader)?;
         let provides_with_count = reader.read_u16::<BigEndian>()?;
-        let mut with_index = Vec::with_capacity(provides_with_count as usize);
-        for _ in 0..provides_with_count {
-            with_index.push(Index::read(reader)?);
-        }
+        let with_index = read_list(reader, "with_index", provides_with_count as usize)?;
         Ok(Self {
             provides_index,
             with_index,
@@ -433,10 +733,7 @@ impl Readable for Provides {
 impl Readable for ModulePackages {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let package_count = reader.read_u16::<BigEndian>()?;
-        let mut package_index = Vec::with_capacity(package_count as usize);
-        for _ in 0..package_count {
-            package_index.push(Index::read(reader)?);
-        }
+        let package_index = read_list(reader, "package_index", package_count as usize)?;
         Ok(Self { package_index })
     }
 }
@@ -458,10 +755,7 @@ impl Readable for NestHost {
 impl Readable for NestMembers {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let number_of_classes = reader.read_u16::<BigEndian>()?;
-        let mut classes = Vec::with_capacity(number_of_classes as usize);
-        for _ in 0..number_of_classes {
-            classes.push(Index::read(reader)?);
-        }
+        let classes = read_list(reader, "classes", number_of_classes as usize)?;
         Ok(Self { classes })
     }
 }
@@ -469,10 +763,7 @@ impl Readable for NestMembers {
 impl Readable for Record {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let component_count = reader.read_u16::<BigEndian>()?;
-        let mut components = Vec::with_capacity(component_count as usize);
-        for _ in 0..component_count {
-            components.push(RecordComponent::read(reader)?);
-        }
+        let components = read_list(reader, "components", component_count as usize)?;
         Ok(Self { components })
     }
 }
@@ -493,10 +784,7 @@ impl Readable for RecordComponent {
 impl Readable for PermittedSubclasses {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let number_of_classes = reader.read_u16::<BigEndian>()?;
-        let mut classes = Vec::with_capacity(number_of_classes as usize);
-        for _ in 0..number_of_classes {
-            classes.push(Index::read(reader)?);
-        }
+        let classes = read_list(reader, "classes", number_of_classes as usize)?;
         Ok(Self { classes })
     }
 }
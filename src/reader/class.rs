@@ -2,9 +2,15 @@ use std::io::Read;
 
 use byteorder::{BigEndian, ReadBytesExt};
 
+use crate::reader::{
+    context::{self, Tracked},
+    options::ParseOptions,
+};
 use crate::structs::access_flags::ClassFlags;
 use crate::structs::attributes::Attributes;
-use crate::structs::{ClassVersion, ConstPool, Fields, Index, Interfaces, Methods};
+use crate::structs::{
+    ClassVersion, ConstItem, ConstPool, Fields, Index, Interfaces, Methods, Severity,
+};
 use crate::Classify;
 use crate::{error::ParseError, Result};
 
@@ -12,33 +18,96 @@ use crate::{structs::Class, Readable};
 
 impl Readable for Class {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
-        let magic = reader.read_u32::<BigEndian>()?;
-        if magic != 0xCAFEBABE {
-            return Err(ParseError::InvalidMagicNumber(magic));
-        }
-        let version = ClassVersion::read(reader)?;
-        let constant_pool = ConstPool::read(reader)?;
-        let access_flags = ClassFlags::read(reader)?;
-        let this_class = Index::read(reader)?;
-        let super_class = Index::read(reader)?;
-        let interfaces = Interfaces::read(reader)?;
-        let mut fields = Fields::read(reader)?;
-        let mut methods = Methods::read(reader)?;
-        let mut attributes = Attributes::read(reader)?;
-        fields.classify(&constant_pool)?;
-        methods.classify(&constant_pool)?;
-        attributes.classify(&constant_pool)?;
+        Class::read_with(reader, &ParseOptions::default())
+    }
+}
+
+impl Class {
+    pub fn read_with<R: Read>(reader: &mut R, options: &ParseOptions) -> Result<Self> {
+        let mut reader = Tracked::new(reader);
+        let (class, diagnostics) = context::with_options(options, || {
+            context::at(0, || {
+                read_class(&mut reader).map_err(|err| err.at_offset(context::position()))
+            })
+        });
         Ok(Class {
-            magic,
-            version,
-            constant_pool,
-            access_flags,
-            this_class,
-            super_class,
-            interfaces,
-            fields,
-            methods,
-            attributes,
+            diagnostics,
+            ..class?
         })
     }
 }
+
+fn read_class<R: Read>(reader: &mut R) -> Result<Class> {
+    let magic = reader.read_u32::<BigEndian>()?;
+    if magic != 0xCAFEBABE {
+        return Err(ParseError::InvalidMagicNumber(magic));
+    }
+    let version = ClassVersion::read(reader)?;
+    let constant_pool = ConstPool::read(reader)?;
+    validate_method_handles(&constant_pool, &version)?;
+    let access_flags = ClassFlags::read(reader)?;
+    let this_class = Index::read(reader)?;
+    let super_class = Index::read(reader)?;
+    let interfaces = Interfaces::read(reader)?;
+    let mut fields = Fields::read(reader)?;
+    let mut methods = Methods::read(reader)?;
+    validate_access_flags(&constant_pool, &version, access_flags, &fields, &methods)?;
+    let mut attributes = Attributes::read(reader)?;
+    fields.classify(&constant_pool)?;
+    methods.classify(&constant_pool)?;
+    attributes.classify(&constant_pool)?;
+    Ok(Class {
+        magic,
+        version,
+        constant_pool,
+        access_flags,
+        this_class,
+        super_class,
+        interfaces,
+        fields,
+        methods,
+        attributes,
+        diagnostics: Vec::new(),
+    })
+}
+
+/// Method handles can only be checked once the whole pool is read, as they may refer forward
+fn validate_method_handles(pool: &ConstPool, version: &ClassVersion) -> Result<()> {
+    for (i, entry) in pool.entries.iter().enumerate() {
+        if let Some(ConstItem::MethodHandle(handle)) = entry {
+            let segment = format!("constant_pool[{}]", i);
+            if let Err(err) = context::scope(segment, || handle.validate(pool, version)) {
+                context::report(Severity::Error, err)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The flags of fields and methods are checked against those of the class, and for methods
+/// against their name, so they can only be checked once all of them are read
+fn validate_access_flags(
+    pool: &ConstPool,
+    version: &ClassVersion,
+    class: ClassFlags,
+    fields: &Fields,
+    methods: &Methods,
+) -> Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        let segment = format!("fields[{}]", i);
+        if let Err(err) = context::scope(segment, || field.access_flags.validate(class)) {
+            context::report(Severity::Error, err)?;
+        }
+    }
+    for (i, method) in methods.iter().enumerate() {
+        let segment = format!("methods[{}]", i);
+        let validate = || {
+            let name = &method.name_index.get(pool)?.str;
+            method.access_flags.validate(class, name, version)
+        };
+        if let Err(err) = context::scope(segment, validate) {
+            context::report(Severity::Error, err)?;
+        }
+    }
+    Ok(())
+}
@@ -4,6 +4,7 @@ use crate::Result;
 use byteorder::{BigEndian, ReadBytesExt};
 
 use crate::{
+    reader::context,
     structs::{ConstItem, ConstPool},
     Readable,
 };
@@ -14,21 +15,26 @@ impl Readable for ConstPool {
         let mut entries = Vec::with_capacity(count as usize);
         entries.push(None);
         let mut skip = false;
-        for _ in 1..count {
+        for index in 1..count {
             if skip {
                 skip = false;
                 continue;
             }
 
-            let entry = ConstItem::read(reader)?;
+            let entry = context::scope(format_args!("constant_pool[{}]", index), || {
+                ConstItem::read_at(reader, index)
+            })?;
+            let wide = matches!(entry, ConstItem::Long(_) | ConstItem::Double(_));
+            entries.push(Some(entry));
 
-            if let ConstItem::Long(_) | ConstItem::Double(_) = entry {
+            // Long and Double take up two slots, the second of which is unusable
+            if wide {
                 entries.push(None);
                 skip = true;
             }
-
-            entries.push(Some(entry));
         }
-        Ok(ConstPool { entries })
+        let mut pool = ConstPool { entries };
+        pool.link_method_handles();
+        Ok(pool)
     }
 }
@@ -1,15 +1,15 @@
 use std::io::Read;
 
 use byteorder::{BigEndian, ReadBytesExt};
-use cesu8::from_java_cesu8;
 
 use crate::structs::const_types::*;
-use crate::{Readable, Result};
+use crate::{error::ParseError, Readable, Result};
 
 use crate::structs::const_pool::TryFromItem;
 
-impl Readable for ConstItem {
-    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+impl ConstItem {
+    /// Reads the entry at `index` in the constant pool, which is only used for error reporting
+    pub fn read_at<R: Read>(reader: &mut R, index: u16) -> Result<Self> {
         let tag = reader.read_u8()?;
         Ok(match tag {
             1 => ConstItem::Utf8(Utf8::read(reader)?),
@@ -29,7 +29,7 @@ impl Readable for ConstItem {
             18 => ConstItem::InvokeDynamic(InvokeDynamic::read(reader)?),
             19 => ConstItem::Module(Module::read(reader)?),
             20 => ConstItem::Package(Package::read(reader)?),
-            _ => panic!("Invalid constant pool tag"),
+            _ => return Err(ParseError::InvalidConstantTag { tag, index }),
         })
     }
 }
@@ -46,13 +46,7 @@ impl Readable for Utf8 {
         let length = reader.read_u16::<BigEndian>()?;
         let mut bytes = vec![0; length as usize];
         reader.read_exact(&mut bytes)?;
-
-        let str = match from_java_cesu8(&bytes) {
-            Ok(str) => str,
-            Err(_) => String::from_utf8_lossy(&bytes),
-        }
-        .to_string();
-        Ok(Utf8 { bytes, str })
+        Ok(Utf8::from_bytes(bytes))
     }
 }
 
@@ -147,8 +141,8 @@ impl Readable for MethodHandle {
         let reference_kind = MethodHandleReferenceKind::read(reader)?;
         let reference_index = reader.read_u16::<BigEndian>()?;
         Ok(MethodHandle {
+            reference_index: MethodHandleReference::for_kind(&reference_kind, reference_index),
             reference_kind,
-            reference_index,
         })
     }
 }
@@ -166,7 +160,12 @@ impl Readable for MethodHandleReferenceKind {
             7 => MethodHandleReferenceKind::InvokeSpecial,
             8 => MethodHandleReferenceKind::NewInvokeSpecial,
             9 => MethodHandleReferenceKind::InvokeInterface,
-            _ => panic!("Invalid method handle reference kind"),
+            _ => {
+                return Err(ParseError::Unrecognized(
+                    "MethodHandleReferenceKind",
+                    reference_kind.to_string(),
+                ))
+            }
         })
     }
 }
@@ -0,0 +1,130 @@
+//! Tracks how far into the class file reading has got, so that errors can be given an offset
+//! and a path without every reader having to pass them along. Also holds the parse mode and the
+//! diagnostics collected so far
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+    io::Read,
+};
+
+use crate::{
+    error::ParseError,
+    reader::options::{ParseMode, ParseOptions},
+    structs::{Diagnostic, Severity},
+    Result,
+};
+
+thread_local! {
+    static POSITION: Cell<usize> = const { Cell::new(0) };
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static PATH: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static MODE: Cell<ParseMode> = const { Cell::new(ParseMode::Strict) };
+    static DIAGNOSTICS: RefCell<Vec<Diagnostic>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Counts every byte read through it towards the current position
+pub(crate) struct Tracked<R> {
+    inner: R,
+}
+
+impl<R: Read> Tracked<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Read> Read for Tracked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        POSITION.with(|position| position.set(position.get() + read));
+        Ok(read)
+    }
+}
+
+/// The offset in the class file of the next byte to be read
+pub(crate) fn position() -> usize {
+    POSITION.with(Cell::get)
+}
+
+/// Runs `f` with the position starting at `start`, restoring the previous position afterwards
+pub(crate) fn at<T>(start: usize, f: impl FnOnce() -> T) -> T {
+    let previous = POSITION.with(|position| position.replace(start));
+    let result = f();
+    POSITION.with(|position| position.set(previous));
+    result
+}
+
+/// Runs `f`, prefixing the path of any error it returns with `segment`.
+/// The innermost scope also records its start as the offset of the error, so that it points at
+/// the item that failed rather than past it
+pub(crate) fn scope<T>(segment: impl Display, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let segment = segment.to_string();
+    let start = position();
+    PATH.with(|path| path.borrow_mut().push(segment.clone()));
+    let result = f();
+    PATH.with(|path| path.borrow_mut().pop());
+    result.map_err(|err| err.in_path(segment, start))
+}
+
+/// Runs `f` one level deeper into values that can contain themselves, failing instead once
+/// `limit` levels are open so that a small class file cannot overflow the stack
+pub(crate) fn nested<T>(
+    what: &'static str,
+    limit: usize,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let depth = DEPTH.with(Cell::get);
+    if depth >= limit {
+        return Err(ParseError::TooDeeplyNested { what, limit });
+    }
+    DEPTH.with(|cell| cell.set(depth + 1));
+    let result = f();
+    DEPTH.with(|cell| cell.set(depth));
+    result
+}
+
+/// Runs `f` with the given options and no path, returning the diagnostics it reported
+pub(crate) fn with_options<T>(
+    options: &ParseOptions,
+    f: impl FnOnce() -> T,
+) -> (T, Vec<Diagnostic>) {
+    let mode = MODE.with(|mode| mode.replace(options.mode));
+    let path = PATH.with(|path| path.replace(Vec::new()));
+    let diagnostics = DIAGNOSTICS.with(|diagnostics| diagnostics.replace(Vec::new()));
+    let result = f();
+    MODE.with(|cell| cell.set(mode));
+    PATH.with(|cell| cell.replace(path));
+    let reported = DIAGNOSTICS.with(|cell| cell.replace(diagnostics));
+    (result, reported)
+}
+
+/// Reports a problem the reader can recover from. Errors are returned in strict mode,
+/// everything else is recorded as a diagnostic with the current path
+pub(crate) fn report(severity: Severity, err: ParseError) -> Result<()> {
+    if severity == Severity::Error && MODE.with(Cell::get) == ParseMode::Strict {
+        return Err(err);
+    }
+
+    let mut path = PATH.with(|path| path.borrow().join("."));
+    match err.path() {
+        Some(inner) if !inner.is_empty() && !path.is_empty() => {
+            path.push('.');
+            path.push_str(inner);
+        }
+        Some(inner) if !inner.is_empty() => path.push_str(inner),
+        _ => {}
+    }
+    let diagnostic = Diagnostic {
+        severity,
+        offset: err.offset().unwrap_or_else(position),
+        path,
+        message: err.root().to_string(),
+    };
+    DIAGNOSTICS.with(|diagnostics| diagnostics.borrow_mut().push(diagnostic));
+    Ok(())
+}
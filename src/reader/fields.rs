@@ -3,6 +3,7 @@ use std::io::Read;
 use byteorder::{BigEndian, ReadBytesExt};
 
 use crate::{
+    reader::read_list,
     structs::{access_flags::FieldFlags, attributes::Attributes, Field, Fields, Index},
     Readable, Result,
 };
@@ -10,11 +11,7 @@ use crate::{
 impl Readable for Fields {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let fields_count = reader.read_u16::<BigEndian>()?;
-        let mut fields = Vec::with_capacity(fields_count as usize);
-        for _ in 0..fields_count {
-            fields.push(Field::read(reader)?);
-        }
-        Ok(fields)
+        read_list(reader, "fields", fields_count as usize)
     }
 }
 
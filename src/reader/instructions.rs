@@ -0,0 +1,217 @@
+use std::{
+    convert::TryInto,
+    io::{Cursor, Read},
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::{
+    error::ParseError,
+    reader::context,
+    structs::{
+        instructions::{
+            Instruction, LookupSwitch, MatchOffset, OffsetInstruction, TableSwitch, Wide,
+        },
+        opcodes::{ArrayType, Opcode},
+        Index,
+    },
+    Readable, Result,
+};
+
+/// Decodes a whole code array into instructions. Errors are given the offset of the instruction
+/// that failed, relative to the current position, which is the start of the code array while
+/// reading a class
+pub fn read_code(code: &[u8]) -> Result<Vec<OffsetInstruction>> {
+    let start = context::position();
+    let mut reader = Cursor::new(code);
+    let mut instructions = Vec::new();
+    while (reader.position() as usize) < code.len() {
+        let offset = reader.position() as u32;
+        let instruction = Instruction::read_at(&mut reader, offset)
+            .map_err(|err| err.in_path(format_args!("code@{}", offset), start + offset as usize))?;
+        instructions.push(OffsetInstruction {
+            offset,
+            instruction,
+        });
+    }
+    Ok(instructions)
+}
+
+/// Skips the padding that aligns the operands of a switch to a multiple of 4 bytes
+fn skip_padding<R: Read>(reader: &mut R, offset: u32) -> Result<()> {
+    for _ in 0..(3 - offset % 4) {
+        reader.read_u8()?;
+    }
+    Ok(())
+}
+
+impl Instruction {
+    /// Reads an instruction located at `offset` in the code array, which is needed to know the
+    /// amount of padding of `tableswitch` and `lookupswitch`
+    pub fn read_at<R: Read>(reader: &mut R, offset: u32) -> Result<Self> {
+        let opcode: Opcode = reader.read_u8()?.try_into()?;
+        if let Some(instruction) = Instruction::simple(opcode) {
+            return Ok(instruction);
+        }
+        Ok(match opcode {
+            Opcode::bipush => Instruction::bipush(reader.read_i8()?),
+            Opcode::sipush => Instruction::sipush(reader.read_i16::<BigEndian>()?),
+            Opcode::ldc => Instruction::ldc(Index::new(reader.read_u8()? as u16)),
+            Opcode::ldc_w => Instruction::ldc_w(Index::read(reader)?),
+            Opcode::ldc2_w => Instruction::ldc2_w(Index::read(reader)?),
+            Opcode::iload => Instruction::iload(reader.read_u8()?),
+            Opcode::lload => Instruction::lload(reader.read_u8()?),
+            Opcode::fload => Instruction::fload(reader.read_u8()?),
+            Opcode::dload => Instruction::dload(reader.read_u8()?),
+            Opcode::aload => Instruction::aload(reader.read_u8()?),
+            Opcode::istore => Instruction::istore(reader.read_u8()?),
+            Opcode::lstore => Instruction::lstore(reader.read_u8()?),
+            Opcode::fstore => Instruction::fstore(reader.read_u8()?),
+            Opcode::dstore => Instruction::dstore(reader.read_u8()?),
+            Opcode::astore => Instruction::astore(reader.read_u8()?),
+            Opcode::iinc => Instruction::iinc(reader.read_u8()?, reader.read_i8()?),
+            Opcode::ifeq => Instruction::ifeq(reader.read_i16::<BigEndian>()?),
+            Opcode::ifne => Instruction::ifne(reader.read_i16::<BigEndian>()?),
+            Opcode::iflt => Instruction::iflt(reader.read_i16::<BigEndian>()?),
+            Opcode::ifge => Instruction::ifge(reader.read_i16::<BigEndian>()?),
+            Opcode::ifgt => Instruction::ifgt(reader.read_i16::<BigEndian>()?),
+            Opcode::ifle => Instruction::ifle(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmpeq => Instruction::if_icmpeq(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmpne => Instruction::if_icmpne(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmplt => Instruction::if_icmplt(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmpge => Instruction::if_icmpge(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmpgt => Instruction::if_icmpgt(reader.read_i16::<BigEndian>()?),
+            Opcode::if_icmple => Instruction::if_icmple(reader.read_i16::<BigEndian>()?),
+            Opcode::if_acmpeq => Instruction::if_acmpeq(reader.read_i16::<BigEndian>()?),
+            Opcode::if_acmpne => Instruction::if_acmpne(reader.read_i16::<BigEndian>()?),
+            Opcode::goto => Instruction::goto(reader.read_i16::<BigEndian>()?),
+            Opcode::jsr => Instruction::jsr(reader.read_i16::<BigEndian>()?),
+            Opcode::ret => Instruction::ret(reader.read_u8()?),
+            Opcode::tableswitch => {
+                skip_padding(reader, offset)?;
+                Instruction::tableswitch(TableSwitch::read(reader)?)
+            }
+            Opcode::lookupswitch => {
+                skip_padding(reader, offset)?;
+                Instruction::lookupswitch(LookupSwitch::read(reader)?)
+            }
+            Opcode::getstatic => Instruction::getstatic(Index::read(reader)?),
+            Opcode::putstatic => Instruction::putstatic(Index::read(reader)?),
+            Opcode::getfield => Instruction::getfield(Index::read(reader)?),
+            Opcode::putfield => Instruction::putfield(Index::read(reader)?),
+            Opcode::invokevirtual => Instruction::invokevirtual(Index::read(reader)?),
+            Opcode::invokespecial => Instruction::invokespecial(Index::read(reader)?),
+            Opcode::invokestatic => Instruction::invokestatic(Index::read(reader)?),
+            Opcode::invokeinterface => {
+                let index = Index::read(reader)?;
+                let count = reader.read_u8()?;
+                // Always zero
+                reader.read_u8()?;
+                Instruction::invokeinterface(index, count)
+            }
+            Opcode::invokedynamic => {
+                let index = Index::read(reader)?;
+                // Always zero
+                reader.read_u16::<BigEndian>()?;
+                Instruction::invokedynamic(index)
+            }
+            Opcode::new => Instruction::new(Index::read(reader)?),
+            Opcode::newarray => {
+                let atype: ArrayType = reader.read_u8()?.try_into()?;
+                Instruction::newarray(atype)
+            }
+            Opcode::anewarray => Instruction::anewarray(Index::read(reader)?),
+            Opcode::checkcast => Instruction::checkcast(Index::read(reader)?),
+            Opcode::instanceof => Instruction::instanceof(Index::read(reader)?),
+            Opcode::wide => Instruction::wide(Wide::read(reader)?),
+            Opcode::multianewarray => {
+                Instruction::multianewarray(Index::read(reader)?, reader.read_u8()?)
+            }
+            Opcode::ifnull => Instruction::ifnull(reader.read_i16::<BigEndian>()?),
+            Opcode::ifnonnull => Instruction::ifnonnull(reader.read_i16::<BigEndian>()?),
+            Opcode::goto_w => Instruction::goto_w(reader.read_i32::<BigEndian>()?),
+            Opcode::jsr_w => Instruction::jsr_w(reader.read_i32::<BigEndian>()?),
+            // Opcodes only used internally by the JVM, which never appear in a class file
+            _ => {
+                return Err(ParseError::Unrecognized(
+                    "Instruction",
+                    format!("{:?}", opcode),
+                ))
+            }
+        })
+    }
+}
+
+impl Readable for TableSwitch {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let default = reader.read_i32::<BigEndian>()?;
+        let low = reader.read_i32::<BigEndian>()?;
+        let high = reader.read_i32::<BigEndian>()?;
+        if low > high {
+            return Err(ParseError::Other(format!(
+                "Invalid tableswitch range {} to {}",
+                low, high
+            )));
+        }
+        let count = high as i64 - low as i64 + 1;
+        // The count comes from the input, so the offsets are only stored as they are read
+        let mut offsets = Vec::new();
+        for _ in 0..count {
+            offsets.push(reader.read_i32::<BigEndian>()?);
+        }
+        Ok(Self {
+            default,
+            low,
+            high,
+            offsets,
+        })
+    }
+}
+
+impl Readable for LookupSwitch {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let default = reader.read_i32::<BigEndian>()?;
+        let npairs = reader.read_i32::<BigEndian>()?;
+        if npairs < 0 {
+            return Err(ParseError::Other(format!(
+                "Invalid lookupswitch pair count {}",
+                npairs
+            )));
+        }
+        let mut pairs = Vec::new();
+        for _ in 0..npairs {
+            pairs.push(MatchOffset::read(reader)?);
+        }
+        Ok(Self { default, pairs })
+    }
+}
+
+impl Readable for MatchOffset {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let key = reader.read_i32::<BigEndian>()?;
+        let offset = reader.read_i32::<BigEndian>()?;
+        Ok(Self { key, offset })
+    }
+}
+
+impl Readable for Wide {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let opcode: Opcode = reader.read_u8()?.try_into()?;
+        let index = reader.read_u16::<BigEndian>()?;
+        Ok(match opcode {
+            Opcode::iload => Wide::iload(index),
+            Opcode::lload => Wide::lload(index),
+            Opcode::fload => Wide::fload(index),
+            Opcode::dload => Wide::dload(index),
+            Opcode::aload => Wide::aload(index),
+            Opcode::istore => Wide::istore(index),
+            Opcode::lstore => Wide::lstore(index),
+            Opcode::fstore => Wide::fstore(index),
+            Opcode::dstore => Wide::dstore(index),
+            Opcode::astore => Wide::astore(index),
+            Opcode::ret => Wide::ret(index),
+            Opcode::iinc => Wide::iinc(index, reader.read_i16::<BigEndian>()?),
+            _ => return Err(ParseError::Unrecognized("Wide", format!("{:?}", opcode))),
+        })
+    }
+}
@@ -2,6 +2,7 @@ use std::io::Read;
 
 use byteorder::{BigEndian, ReadBytesExt};
 
+use crate::reader::read_list;
 use crate::structs::interfaces::{Interface, Interfaces};
 use crate::structs::Index;
 use crate::{Readable, Result};
@@ -9,12 +10,13 @@ use crate::{Readable, Result};
 impl Readable for Interfaces {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let interfaces_count = reader.read_u16::<BigEndian>()?;
-        let mut interfaces = Vec::with_capacity(interfaces_count as usize);
-        for _ in 0..interfaces_count {
-            interfaces.push(Interface {
-                index: Index::read(reader)?,
-            });
-        }
-        Ok(interfaces)
+        read_list(reader, "interfaces", interfaces_count as usize)
+    }
+}
+
+impl Readable for Interface {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let index = Index::read(reader)?;
+        Ok(Interface { index })
     }
 }
@@ -3,6 +3,7 @@ use std::io::Read;
 use byteorder::{BigEndian, ReadBytesExt};
 
 use crate::{
+    reader::read_list,
     structs::{access_flags::MethodFlags, attributes::Attributes, Index, Method, Methods},
     Readable, Result,
 };
@@ -10,11 +11,7 @@ use crate::{
 impl Readable for Methods {
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let methods_count = reader.read_u16::<BigEndian>()?;
-        let mut methods = Vec::with_capacity(methods_count as usize);
-        for _ in 0..methods_count {
-            methods.push(Method::read(reader)?);
-        }
-        Ok(methods)
+        read_list(reader, "methods", methods_count as usize)
     }
 }
 
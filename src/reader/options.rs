@@ -0,0 +1,35 @@
+/// How the reader deals with problems it can recover from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on anything that does not follow the specification
+    Strict,
+    /// Keep going where possible, leaving the raw bytes of attributes that could not be parsed
+    /// as `Unknown` and recording a diagnostic on the class instead
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+}
+
+impl ParseOptions {
+    pub fn strict() -> Self {
+        Self {
+            mode: ParseMode::Strict,
+        }
+    }
+
+    pub fn lenient() -> Self {
+        Self {
+            mode: ParseMode::Lenient,
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    /// Strict, so that malformed input is an error unless a caller asks for diagnostics
+    fn default() -> Self {
+        Self::strict()
+    }
+}
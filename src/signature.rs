@@ -0,0 +1,546 @@
+//! Generic signatures (JVMS 4.7.9.1), as stored in the `Signature` attribute of classes, methods,
+//! fields and record components, e.g. `<T:Ljava/lang/Object;>(TT;)Ljava/util/List<TT;>;`
+
+use std::fmt::{self, Display};
+
+use crate::{
+    descriptor::{java_class_name, BaseType, Parser, MAX_ARRAY_DIMENSIONS},
+    Result,
+};
+
+/// Type arguments may nest at most this deep, e.g. `List<List<String>>` nests two deep
+pub const MAX_TYPE_ARGUMENT_DEPTH: usize = 255;
+
+/// How class names are written by the `to_java` renderers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Names {
+    // `java.util.Map$Entry`
+    Qualified,
+    // `Entry`, without the package or enclosing classes
+    Simple,
+}
+
+/// `JavaTypeSignature`, the type of a parameter, return value or array element
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeSignature {
+    Base(BaseType),
+    Reference(ReferenceTypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceTypeSignature {
+    Class(ClassTypeSignature),
+    // The name of a type variable, e.g. `T`
+    TypeVariable(String),
+    Array(Box<TypeSignature>),
+}
+
+/// A possibly parameterized class type. Inner classes of a parameterized class are listed after
+/// their outer class, e.g. `Outer<String>.Inner<T>` has two entries in `classes`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassTypeSignature {
+    // `/`-separated, e.g. `java/util`, empty for the default package
+    pub package: String,
+    pub classes: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeArgument {
+    // `?`
+    Any,
+    Exact(ReferenceTypeSignature),
+    // `? extends T`
+    Extends(ReferenceTypeSignature),
+    // `? super T`
+    Super(ReferenceTypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParameter {
+    pub name: String,
+    // `None` when the only bounds are interfaces
+    pub class_bound: Option<ReferenceTypeSignature>,
+    pub interface_bounds: Vec<ReferenceTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<TypeSignature>,
+    // `None` for `void`
+    pub return_type: Option<TypeSignature>,
+    // Class types or type variables
+    pub throws: Vec<ReferenceTypeSignature>,
+}
+
+/// The signature of a field, record component or local variable
+pub type FieldSignature = ReferenceTypeSignature;
+
+impl ClassSignature {
+    pub fn parse(signature: &str) -> Result<Self> {
+        let mut parser = Parser::for_signature(signature);
+        let type_parameters = parser.type_parameters()?;
+        let superclass = parser.class_type_signature()?;
+        let mut interfaces = Vec::new();
+        while parser.peek().is_some() {
+            interfaces.push(parser.class_type_signature()?);
+        }
+        Ok(ClassSignature {
+            type_parameters,
+            superclass,
+            interfaces,
+        })
+    }
+
+    /// The declaration as it is written in Java source without modifiers, e.g.
+    /// `Box<T extends Number> implements Comparable<Box<T>>`. Extending `Object` is left out
+    pub fn to_java(&self, name: &str, names: Names) -> String {
+        let mut result = format!(
+            "{}{}",
+            name,
+            type_parameters_to_java(&self.type_parameters, names)
+        );
+        if !self.superclass.is_object() {
+            result.push_str(&format!(" extends {}", self.superclass.to_java(names)));
+        }
+        if !self.interfaces.is_empty() {
+            let interfaces: Vec<String> = self
+                .interfaces
+                .iter()
+                .map(|interface| interface.to_java(names))
+                .collect();
+            result.push_str(&format!(" implements {}", interfaces.join(", ")));
+        }
+        result
+    }
+}
+
+impl MethodSignature {
+    pub fn parse(signature: &str) -> Result<Self> {
+        let mut parser = Parser::for_signature(signature);
+        let type_parameters = parser.type_parameters()?;
+        parser.expect('(')?;
+        let mut parameters = Vec::new();
+        while !parser.eat(')') {
+            parameters.push(parser.type_signature()?);
+        }
+        let return_type = if parser.eat('V') {
+            None
+        } else {
+            Some(parser.type_signature()?)
+        };
+        let mut throws = Vec::new();
+        while parser.eat('^') {
+            let thrown = parser.reference_type_signature()?;
+            if let ReferenceTypeSignature::Array(_) = thrown {
+                return Err(parser.error("arrays cannot be thrown"));
+            }
+            throws.push(thrown);
+        }
+        parser.finish()?;
+        Ok(MethodSignature {
+            type_parameters,
+            parameters,
+            return_type,
+            throws,
+        })
+    }
+
+    /// The declaration as it is written in Java source without modifiers, e.g.
+    /// `<T> List<T> copy(List<? extends T>) throws IOException`
+    pub fn to_java(&self, name: &str, names: Names) -> String {
+        let mut result = String::new();
+        if !self.type_parameters.is_empty() {
+            result.push_str(&type_parameters_to_java(&self.type_parameters, names));
+            result.push(' ');
+        }
+        let return_type = match &self.return_type {
+            Some(return_type) => return_type.to_java(names),
+            None => "void".to_string(),
+        };
+        let parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|parameter| parameter.to_java(names))
+            .collect();
+        result.push_str(&format!(
+            "{} {}({})",
+            return_type,
+            name,
+            parameters.join(", ")
+        ));
+        if !self.throws.is_empty() {
+            let throws: Vec<String> = self.throws.iter().map(|t| t.to_java(names)).collect();
+            result.push_str(&format!(" throws {}", throws.join(", ")));
+        }
+        result
+    }
+}
+
+impl TypeSignature {
+    pub fn to_java(&self, names: Names) -> String {
+        match self {
+            TypeSignature::Base(base) => base.java_name().to_string(),
+            TypeSignature::Reference(reference) => reference.to_java(names),
+        }
+    }
+}
+
+impl ReferenceTypeSignature {
+    /// Parses a field signature
+    pub fn parse(signature: &str) -> Result<Self> {
+        let mut parser = Parser::for_signature(signature);
+        let field = parser.reference_type_signature()?;
+        parser.finish()?;
+        Ok(field)
+    }
+
+    /// The type as it is written in Java source, e.g. `Map<String, ? extends List<T>>`
+    pub fn to_java(&self, names: Names) -> String {
+        match self {
+            ReferenceTypeSignature::Class(class) => class.to_java(names),
+            ReferenceTypeSignature::TypeVariable(name) => name.clone(),
+            ReferenceTypeSignature::Array(element) => format!("{}[]", element.to_java(names)),
+        }
+    }
+}
+
+impl ClassTypeSignature {
+    /// The internal name of the class without type arguments, e.g. `java/util/Map$Entry`
+    pub fn internal_name(&self) -> String {
+        let classes: Vec<&str> = self.classes.iter().map(|c| c.name.as_str()).collect();
+        if self.package.is_empty() {
+            classes.join("$")
+        } else {
+            format!("{}/{}", self.package, classes.join("$"))
+        }
+    }
+
+    fn is_object(&self) -> bool {
+        self.internal_name() == "java/lang/Object"
+    }
+
+    pub fn to_java(&self, names: Names) -> String {
+        let classes: Vec<String> = self
+            .classes
+            .iter()
+            .map(|class| {
+                let name = match names {
+                    Names::Qualified => java_class_name(&class.name),
+                    // Enclosing classes without type arguments are part of the name, e.g. `Map$Entry`
+                    Names::Simple => match class.name.rsplit_once('$') {
+                        Some((_, name)) if !name.is_empty() => name.to_string(),
+                        _ => class.name.clone(),
+                    },
+                };
+                format!(
+                    "{}{}",
+                    name,
+                    type_arguments_to_java(&class.type_arguments, names)
+                )
+            })
+            .collect();
+        match names {
+            Names::Qualified if !self.package.is_empty() => {
+                format!("{}.{}", java_class_name(&self.package), classes.join("."))
+            }
+            Names::Qualified => classes.join("."),
+            Names::Simple => classes.last().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl TypeArgument {
+    pub fn to_java(&self, names: Names) -> String {
+        match self {
+            TypeArgument::Any => "?".to_string(),
+            TypeArgument::Exact(bound) => bound.to_java(names),
+            TypeArgument::Extends(bound) => format!("? extends {}", bound.to_java(names)),
+            TypeArgument::Super(bound) => format!("? super {}", bound.to_java(names)),
+        }
+    }
+}
+
+impl TypeParameter {
+    /// e.g. `T extends Number & Comparable<T>`, a bound of `Object` alone is left out
+    pub fn to_java(&self, names: Names) -> String {
+        let class_bound = self.class_bound.as_ref().filter(|bound| match bound {
+            ReferenceTypeSignature::Class(class) => !class.is_object(),
+            _ => true,
+        });
+        let bounds: Vec<String> = class_bound
+            .into_iter()
+            .chain(&self.interface_bounds)
+            .map(|bound| bound.to_java(names))
+            .collect();
+        if bounds.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} extends {}", self.name, bounds.join(" & "))
+        }
+    }
+}
+
+fn type_parameters_to_java(type_parameters: &[TypeParameter], names: Names) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let parameters: Vec<String> = type_parameters.iter().map(|p| p.to_java(names)).collect();
+    format!("<{}>", parameters.join(", "))
+}
+
+fn type_arguments_to_java(type_arguments: &[TypeArgument], names: Names) -> String {
+    if type_arguments.is_empty() {
+        return String::new();
+    }
+    let arguments: Vec<String> = type_arguments.iter().map(|a| a.to_java(names)).collect();
+    format!("<{}>", arguments.join(", "))
+}
+
+impl Parser<'_> {
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>> {
+        let mut type_parameters = Vec::new();
+        if !self.eat('<') {
+            return Ok(type_parameters);
+        }
+        while !self.eat('>') {
+            let name = self.identifier()?.to_string();
+            self.expect(':')?;
+            let class_bound = match self.peek() {
+                Some(':') => None,
+                _ => Some(self.reference_type_signature()?),
+            };
+            let mut interface_bounds = Vec::new();
+            while self.eat(':') {
+                interface_bounds.push(self.reference_type_signature()?);
+            }
+            type_parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+        }
+        if type_parameters.is_empty() {
+            return Err(self.error("empty type parameters"));
+        }
+        Ok(type_parameters)
+    }
+
+    fn type_signature(&mut self) -> Result<TypeSignature> {
+        if let Some(base) = self.peek().and_then(BaseType::from_char) {
+            self.next();
+            return Ok(TypeSignature::Base(base));
+        }
+        Ok(TypeSignature::Reference(self.reference_type_signature()?))
+    }
+
+    fn reference_type_signature(&mut self) -> Result<ReferenceTypeSignature> {
+        match self.peek() {
+            Some('L') => Ok(ReferenceTypeSignature::Class(self.class_type_signature()?)),
+            Some('T') => {
+                self.next();
+                let name = self.identifier()?.to_string();
+                self.expect(';')?;
+                Ok(ReferenceTypeSignature::TypeVariable(name))
+            }
+            Some('[') => {
+                let mut dimensions = 0;
+                while self.eat('[') {
+                    dimensions += 1;
+                }
+                if dimensions > MAX_ARRAY_DIMENSIONS {
+                    return Err(self.error("too many array dimensions"));
+                }
+                let mut array = ReferenceTypeSignature::Array(Box::new(self.type_signature()?));
+                for _ in 1..dimensions {
+                    array =
+                        ReferenceTypeSignature::Array(Box::new(TypeSignature::Reference(array)));
+                }
+                Ok(array)
+            }
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn class_type_signature(&mut self) -> Result<ClassTypeSignature> {
+        self.expect('L')?;
+        // Every identifier followed by `/` is part of the package
+        let mut package: Vec<&str> = Vec::new();
+        let mut name = self.identifier()?;
+        while self.eat('/') {
+            package.push(name);
+            name = self.identifier()?;
+        }
+        let mut classes = vec![self.simple_class_type_signature(name)?];
+        while self.eat('.') {
+            let name = self.identifier()?;
+            classes.push(self.simple_class_type_signature(name)?);
+        }
+        self.expect(';')?;
+        Ok(ClassTypeSignature {
+            package: package.join("/"),
+            classes,
+        })
+    }
+
+    fn simple_class_type_signature(&mut self, name: &str) -> Result<SimpleClassTypeSignature> {
+        let type_arguments = if self.eat('<') {
+            self.nested(
+                MAX_TYPE_ARGUMENT_DEPTH,
+                "type arguments nested too deep",
+                Self::type_arguments,
+            )?
+        } else {
+            Vec::new()
+        };
+        Ok(SimpleClassTypeSignature {
+            name: name.to_string(),
+            type_arguments,
+        })
+    }
+
+    /// Reads the type arguments after their `<`
+    fn type_arguments(&mut self) -> Result<Vec<TypeArgument>> {
+        let mut type_arguments = Vec::new();
+        while !self.eat('>') {
+            let argument = if self.eat('*') {
+                TypeArgument::Any
+            } else if self.eat('+') {
+                TypeArgument::Extends(self.reference_type_signature()?)
+            } else if self.eat('-') {
+                TypeArgument::Super(self.reference_type_signature()?)
+            } else {
+                TypeArgument::Exact(self.reference_type_signature()?)
+            };
+            type_arguments.push(argument);
+        }
+        if type_arguments.is_empty() {
+            return Err(self.error("empty type arguments"));
+        }
+        Ok(type_arguments)
+    }
+}
+
+impl Display for TypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeSignature::Base(base) => write!(f, "{}", base.as_char()),
+            TypeSignature::Reference(reference) => write!(f, "{}", reference),
+        }
+    }
+}
+
+impl Display for ReferenceTypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceTypeSignature::Class(class) => write!(f, "{}", class),
+            ReferenceTypeSignature::TypeVariable(name) => write!(f, "T{};", name),
+            ReferenceTypeSignature::Array(element) => write!(f, "[{}", element),
+        }
+    }
+}
+
+impl Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L")?;
+        if !self.package.is_empty() {
+            write!(f, "{}/", self.package)?;
+        }
+        for (i, class) in self.classes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", class.name)?;
+            if !class.type_arguments.is_empty() {
+                write!(f, "<")?;
+                for argument in &class.type_arguments {
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ">")?;
+            }
+        }
+        write!(f, ";")
+    }
+}
+
+impl Display for TypeArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeArgument::Any => write!(f, "*"),
+            TypeArgument::Exact(bound) => write!(f, "{}", bound),
+            TypeArgument::Extends(bound) => write!(f, "+{}", bound),
+            TypeArgument::Super(bound) => write!(f, "-{}", bound),
+        }
+    }
+}
+
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.name)?;
+        if let Some(bound) = &self.class_bound {
+            write!(f, "{}", bound)?;
+        }
+        for bound in &self.interface_bounds {
+            write!(f, ":{}", bound)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_type_parameters(
+    f: &mut fmt::Formatter<'_>,
+    type_parameters: &[TypeParameter],
+) -> fmt::Result {
+    if type_parameters.is_empty() {
+        return Ok(());
+    }
+    write!(f, "<")?;
+    for parameter in type_parameters {
+        write!(f, "{}", parameter)?;
+    }
+    write!(f, ">")
+}
+
+impl Display for ClassSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        write!(f, "{}", self.superclass)?;
+        for interface in &self.interfaces {
+            write!(f, "{}", interface)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MethodSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        write!(f, "(")?;
+        for parameter in &self.parameters {
+            write!(f, "{}", parameter)?;
+        }
+        write!(f, ")")?;
+        match &self.return_type {
+            Some(return_type) => write!(f, "{}", return_type)?,
+            None => write!(f, "V")?,
+        }
+        for thrown in &self.throws {
+            write!(f, "^{}", thrown)?;
+        }
+        Ok(())
+    }
+}
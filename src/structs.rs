@@ -1,18 +1,23 @@
 pub mod access_flags;
 pub mod attributes;
+pub mod bootstrap;
 pub mod class;
 pub mod class_version;
 pub mod const_pool;
 pub mod const_types;
+pub mod diagnostics;
 pub mod fields;
+pub mod instructions;
 pub mod interfaces;
 pub mod methods;
 pub mod opcodes;
 
 pub use class::Class;
 pub use class_version::{ClassVersion, MajorVersion};
-pub use const_pool::ConstPool;
+pub use const_pool::{ConstPool, ConstPoolBuilder};
 pub use const_types::{ConstItem, Index, OptionalIndex};
+pub use diagnostics::{Diagnostic, Severity};
 pub use fields::{Field, Fields};
+pub use instructions::{Instruction, OffsetInstruction};
 pub use interfaces::{Interface, Interfaces};
 pub use methods::{Method, Methods};
@@ -1,6 +1,23 @@
-use bitflags::bitflags;
+use bitflags::{bitflags, Flags};
+
+use crate::{error::ParseError, Result};
+
+use super::{ClassVersion, MajorVersion};
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FieldFlags: u16 {
+        const PUBLIC = 0x0001;
+        const PRIVATE = 0x0002;
+        const PROTECTED = 0x0004;
+        const STATIC = 0x0008;
+        const FINAL = 0x0010;
+        const VOLATILE = 0x0040;
+        const TRANSIENT = 0x0080;
+        const SYNTHETIC = 0x1000;
+        const ENUM = 0x4000;
+    }
+
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct MethodFlags: u16 {
         const PUBLIC = 0x0001;
@@ -17,7 +34,6 @@ bitflags! {
         const SYNTHETIC = 0x1000;
     }
 
-
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ClassFlags: u16 {
         const PUBLIC = 0x0001;
@@ -74,7 +90,6 @@ bitflags! {
     }
 }
 
-pub type FieldFlags = MethodFlags;
 pub type OpensFlags = ExportsFlags;
 
 macro_rules! impl_get_pretty {
@@ -90,11 +105,19 @@ macro_rules! impl_get_pretty {
                     Ok(result)
                 }
             }
+
+            impl crate::js::ToJs for $name {
+                fn to_js<'a, C: neon::context::Context<'a>>(&self, cx: &mut C, pool: &super::ConstPool) -> neon::result::JsResult<'a, neon::types::JsValue> {
+                    let names: Vec<String> = self.iter_names().map(|(name, _)| name.to_string()).collect();
+                    crate::js::ToJs::to_js(&names, cx, pool)
+                }
+            }
         )*
     };
 }
 
 impl_get_pretty! {
+    FieldFlags,
     MethodFlags,
     ClassFlags,
     InnerClassFlags,
@@ -103,3 +126,118 @@ impl_get_pretty! {
     RequiresFlags,
     ExportsFlags,
 }
+
+impl FieldFlags {
+    /// Checks the combination of flags against the rules of JVMS 4.5 for a field of a class with
+    /// the `class` flags
+    pub fn validate(self, class: ClassFlags) -> Result<()> {
+        let illegal = |reason: &str| {
+            Err(ParseError::IllegalAccessFlags {
+                flags: self.bits(),
+                reason: reason.to_string(),
+            })
+        };
+        if class.contains(ClassFlags::INTERFACE) {
+            let required = FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL;
+            if !self.contains(required) {
+                return illegal("interface fields must be public, static and final");
+            }
+            let forbidden = self & FieldFlags::all() & !(required | FieldFlags::SYNTHETIC);
+            if !forbidden.is_empty() {
+                return illegal(&format!("interface fields cannot be {}", names(forbidden)));
+            }
+        }
+        if (self & (FieldFlags::PUBLIC | FieldFlags::PRIVATE | FieldFlags::PROTECTED))
+            .bits()
+            .count_ones()
+            > 1
+        {
+            return illegal("at most one of public, private and protected can be set");
+        }
+        if self.contains(FieldFlags::FINAL | FieldFlags::VOLATILE) {
+            return illegal("a field cannot be both final and volatile");
+        }
+        Ok(())
+    }
+}
+
+impl MethodFlags {
+    /// Checks the combination of flags against the rules of JVMS 4.6 for a method called `name`
+    /// of a class with the `class` flags. The flags of `<clinit>` are ignored by the JVM, except
+    /// for `static` since Java 7.
+    pub fn validate(self, class: ClassFlags, name: &str, version: &ClassVersion) -> Result<()> {
+        let illegal = |reason: &str| {
+            Err(ParseError::IllegalAccessFlags {
+                flags: self.bits(),
+                reason: reason.to_string(),
+            })
+        };
+        let major = version.major as u16;
+        if name == "<clinit>" {
+            if major >= MajorVersion::JDK_7 as u16 && !self.contains(MethodFlags::STATIC) {
+                return illegal("<clinit> must be static");
+            }
+            return Ok(());
+        }
+
+        let visibility =
+            self & (MethodFlags::PUBLIC | MethodFlags::PRIVATE | MethodFlags::PROTECTED);
+        if visibility.bits().count_ones() > 1 {
+            return illegal("at most one of public, private and protected can be set");
+        }
+        if class.contains(ClassFlags::INTERFACE) {
+            let forbidden = self
+                & (MethodFlags::PROTECTED
+                    | MethodFlags::FINAL
+                    | MethodFlags::SYNCHRONIZED
+                    | MethodFlags::NATIVE);
+            if !forbidden.is_empty() {
+                return illegal(&format!("interface methods cannot be {}", names(forbidden)));
+            }
+            if major < MajorVersion::JDK_8 as u16 {
+                if !self.contains(MethodFlags::PUBLIC | MethodFlags::ABSTRACT) {
+                    return illegal("interface methods must be public and abstract before Java 8");
+                }
+            } else if visibility.is_empty() {
+                return illegal("interface methods must be either public or private");
+            }
+        }
+        if self.contains(MethodFlags::ABSTRACT) {
+            let mut forbidden = MethodFlags::PRIVATE
+                | MethodFlags::STATIC
+                | MethodFlags::FINAL
+                | MethodFlags::SYNCHRONIZED
+                | MethodFlags::NATIVE;
+            // Every method is strict since Java 17, and the flag is no longer set
+            if (MajorVersion::JDK_1_2 as u16..MajorVersion::JDK_17 as u16).contains(&major) {
+                forbidden |= MethodFlags::STRICT;
+            }
+            let forbidden = self & forbidden;
+            if !forbidden.is_empty() {
+                return illegal(&format!("abstract methods cannot be {}", names(forbidden)));
+            }
+        }
+        if name == "<init>" {
+            let allowed = MethodFlags::PUBLIC
+                | MethodFlags::PRIVATE
+                | MethodFlags::PROTECTED
+                | MethodFlags::VARARGS
+                | MethodFlags::STRICT
+                | MethodFlags::SYNTHETIC;
+            let forbidden = self & MethodFlags::all() & !allowed;
+            if !forbidden.is_empty() {
+                return illegal(&format!("constructors cannot be {}", names(forbidden)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The names of the flags in lowercase, like `static or final`
+fn names<F: Flags>(flags: F) -> String {
+    let names: Vec<_> = flags
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+    names.join(" or ")
+}
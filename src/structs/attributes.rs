@@ -1,14 +1,25 @@
 use std::io::Cursor;
 
-use crate::{impl_get_pretty, Classify, Readable, Result};
+use crate::{
+    descriptor::FieldType,
+    error::ParseError,
+    gen_try_from, impl_get_pretty,
+    pretty_print::GetPretty,
+    reader::context::{self, Tracked},
+    signature::{ClassSignature, FieldSignature, MethodSignature},
+    Classify, Readable, Result,
+};
 
 use super::{
     access_flags::{
         ExportsFlags, InnerClassFlags, MethodParameterFlags, ModuleFlags, OpensFlags, RequiresFlags,
     },
-    const_types::{Class, MethodHandle, Module as ModuleConst, NameAndType, Package, Utf8},
-    opcodes::Opcode,
-    ConstItem, ConstPool, Index, OptionalIndex,
+    const_types::{
+        Class, Double, Float, Integer, Long, MethodHandle, Module as ModuleConst, NameAndType,
+        Package, Utf8,
+    },
+    instructions::OffsetInstruction,
+    ConstItem, ConstPool, Index, OptionalIndex, Severity,
 };
 
 // #[derive(Debug, Clone, PartialEq)]
@@ -29,29 +40,44 @@ pub struct Attribute {
 
 impl Classify for Vec<Attribute> {
     fn classify(&mut self, const_pool: &ConstPool) -> Result<()> {
-        Ok(for attribute in self.iter_mut() {
-            match attribute.classify(const_pool) {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error classifying attribute: {:?}", e);
-                }
+        for (i, attribute) in self.iter_mut().enumerate() {
+            let segment = match attribute.attribute_name_index.get(const_pool) {
+                Ok(name) => format!("attributes[{}]({})", i, name.str),
+                Err(_) => format!("attributes[{}]", i),
+            };
+            if let Err(err) = context::scope(segment, || attribute.classify(const_pool)) {
+                // The attribute is left as `Unknown`
+                context::report(Severity::Error, err)?;
             }
-        })
+        }
+        Ok(())
     }
 }
 
 impl Attribute {
     pub fn classify(&mut self, pool: &ConstPool) -> Result<()> {
-        let bytes = match &self.info {
-            AttributeInfo::Unknown(info) => &info.bytes,
+        let (bytes, offset) = match &self.info {
+            AttributeInfo::Unknown(info) => (&info.bytes, info.offset),
             _ => return Ok(()),
         };
 
         let name = self.attribute_name_index.get(pool)?;
-        let mut reader = Cursor::new(bytes);
+        if !AttributeInfo::recognizes(&name.str) {
+            // Unknown attributes are allowed, and must be ignored
+            let err = ParseError::Unrecognized("AttributeInfo", name.str.clone()).at_offset(offset);
+            return context::report(Severity::Warning, err);
+        }
 
-        let info = AttributeInfo::get(&name.str, &mut reader)?;
-        self.info = info;
+        let mut reader = Tracked::new(Cursor::new(bytes));
+        let mut read_info = || {
+            let info = AttributeInfo::get(&name.str, &mut reader)?;
+            let remaining = bytes.len() - reader.get_ref().position() as usize;
+            if remaining > 0 {
+                return Err(ParseError::TrailingBytes(remaining));
+            }
+            Ok(info)
+        };
+        self.info = context::at(offset, || read_info().map_err(|err| err.at_offset(offset)))?;
 
         if let AttributeInfo::Code(code) = &mut self.info {
             code.attributes.classify(pool)?;
@@ -85,6 +111,20 @@ macro_rules! gen_get {
         }
 
 
+        impl $name {
+            /// Whether `attr_name` is one of the attributes that are parsed
+            pub fn recognizes(attr_name: &str) -> bool {
+                matches!(attr_name, $(stringify!($variant))|*)
+            }
+
+            /// The name of the attribute, `Unknown` for the ones that were not parsed
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant(_) => stringify!($variant),)*
+                }
+            }
+        }
+
         impl Get for $name {
             fn get<R: std::io::Read>(attr_name: &str, reader: &mut R) -> Result<Self> {
                 match attr_name {
@@ -93,6 +133,14 @@ macro_rules! gen_get {
                 }
             }
         }
+
+        impl crate::Writable for $name {
+            fn write<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+                match self {
+                    $($name::$variant(x) => x.write(writer),)*
+                }
+            }
+        }
     };
 }
 // impl TryFrom<&str> for AttributeTypes {
@@ -122,6 +170,8 @@ gen_get! {
         RuntimeInvisibleAnnotations(RuntimeInvisibleAnnotations),
         RuntimeVisibleParameterAnnotations(RuntimeVisibleParameterAnnotations),
         RuntimeInvisibleParameterAnnotations(RuntimeInvisibleParameterAnnotations),
+        RuntimeVisibleTypeAnnotations(RuntimeVisibleTypeAnnotations),
+        RuntimeInvisibleTypeAnnotations(RuntimeInvisibleTypeAnnotations),
         AnnotationDefault(AnnotationDefault),
         BootstrapMethods(BootstrapMethods),
         MethodParameters(MethodParameters),
@@ -146,7 +196,7 @@ pub struct ConstantValue {
 pub struct Code {
     pub max_stack: u16,
     pub max_locals: u16,
-    pub code: Vec<Opcode>,
+    pub code: Vec<OffsetInstruction>,
     pub exception_table: Vec<ExceptionTable>,
     pub attributes: Attributes,
 }
@@ -159,13 +209,10 @@ pub struct ExceptionTable {
     pub catch_type: OptionalIndex<Class>,
 }
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct StackMapTable {
-//     pub bytes: Vec<u8>,
-// }
-// }
-
-pub type StackMapTable = NotImplemented;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackMapTable {
+    pub entries: Vec<StackMapFrame>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Exceptions {
@@ -251,72 +298,83 @@ pub struct LocalVariableType {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Deprecated();
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct RuntimeVisibleAnnotations {
-//     // pub annotations: Vec<Annotation>,
-//     pub annotations: Vec<u8>,
-// }
-pub type RuntimeVisibleAnnotations = NotImplemented;
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleAnnotations {
+    pub annotations: Vec<Annotation>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct Annotation {
-//     pub type_index: Index<Utf8>,
-//     pub element_value_pairs: Vec<ElementValuePair>,
-// }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub type_index: Index<Utf8>,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct ElementValuePair {
-//     pub element_name_index: Index<Utf8>,
-//     pub value: ElementValue,
-// }
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementValuePair {
+    pub element_name_index: Index<Utf8>,
+    pub value: ElementValue,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub enum ElementValue {
-//     Byte(u8),
-//     Char(u16),
-//     Double(f64),
-//     Float(f32),
-//     Int(u32),
-//     Long(u64),
-//     Short(u16),
-//     Boolean(bool),
-//     String(Index<Utf8>),
-//     EnumConstValue {
-//         type_name_index: Index<Utf8>,
-//         const_name_index: Index<Utf8>,
-//     },
-//     ClassInfoIndex(Index<Class>),
-//     AnnotationValue(RuntimeAnnotation),
-//     ArrayValue(Vec<ElementValue>),
-// }
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleAnnotations {
+    pub annotations: Vec<Annotation>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct RuntimeInvisibleAnnotations {
-//     // pub annotations: Vec<Annotation>,
-//     pub annotations: Vec<u8>,
-// }
-pub type RuntimeInvisibleAnnotations = NotImplemented;
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleParameterAnnotations {
+    pub parameter_annotations: Vec<ParameterAnnotations>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct RuntimeVisibleParameterAnnotations {
-//     // pub parameter_annotations: Vec<Vec<Annotation>>,
-//     pub parameter_annotations: Vec<u8>,
-// }
-pub type RuntimeVisibleParameterAnnotations = NotImplemented;
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleParameterAnnotations {
+    pub parameter_annotations: Vec<ParameterAnnotations>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct RuntimeInvisibleParameterAnnotations {
-//     // pub parameter_annotations: Vec<Vec<Annotation>>,
-//     pub parameter_annotations: Vec<u8>,
-// }
-pub type RuntimeInvisibleParameterAnnotations = NotImplemented;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterAnnotations {
+    pub annotations: Vec<Annotation>,
+}
 
-// #[derive(Debug, Clone, PartialEq, Eq)]
-// pub struct AnnotationDefault {
-//     // pub default_value: ElementValue,
-//     pub default_value: Vec<u8>,
-// }
-pub type AnnotationDefault = NotImplemented;
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleTypeAnnotations {
+    pub annotations: Vec<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleTypeAnnotations {
+    pub annotations: Vec<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnnotation {
+    pub target_type: TargetType,
+    pub target_info: TargetInfo,
+    pub target_path: TypePath,
+    pub annotation: Annotation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePath {
+    pub path: Vec<TypePathEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePathEntry {
+    pub type_path_kind: TypePathKind,
+    pub type_argument_index: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationDefault {
+    pub default_value: ElementValue,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BootstrapMethods {
@@ -424,6 +482,8 @@ pub struct PermittedSubclasses {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Unknown {
     pub bytes: Vec<u8>,
+    // Where `bytes` started in the class file, used to give errors an offset
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -431,3 +491,681 @@ pub struct NotImplemented {
     pub bytes: Vec<u8>,
 }
 }
+
+/// A compressed stack map frame, the offset of a frame is relative to the previous one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackMapFrame {
+    /// `same_frame`, frame types 0 to 63 where the type is the offset delta
+    Same { offset_delta: u16 },
+    /// `same_locals_1_stack_item_frame`, frame types 64 to 127
+    SameLocals1StackItem {
+        offset_delta: u16,
+        stack: VerificationTypeInfo,
+    },
+    /// `same_locals_1_stack_item_frame_extended`, frame type 247
+    SameLocals1StackItemExtended {
+        offset_delta: u16,
+        stack: VerificationTypeInfo,
+    },
+    /// `chop_frame`, frame types 248 to 250 which remove the last 1 to 3 locals
+    Chop { k: u8, offset_delta: u16 },
+    /// `same_frame_extended`, frame type 251
+    SameExtended { offset_delta: u16 },
+    /// `append_frame`, frame types 252 to 254 which add 1 to 3 locals
+    Append {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo>,
+    },
+    /// `full_frame`, frame type 255
+    Full {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo>,
+        stack: Vec<VerificationTypeInfo>,
+    },
+}
+
+impl Signature {
+    pub fn class_signature(&self, pool: &ConstPool) -> Result<ClassSignature> {
+        ClassSignature::parse(&self.signature_index.get(pool)?.str)
+    }
+
+    pub fn method_signature(&self, pool: &ConstPool) -> Result<MethodSignature> {
+        MethodSignature::parse(&self.signature_index.get(pool)?.str)
+    }
+
+    /// The signature of a field or record component
+    pub fn field_signature(&self, pool: &ConstPool) -> Result<FieldSignature> {
+        FieldSignature::parse(&self.signature_index.get(pool)?.str)
+    }
+}
+
+/// The `Signature` attribute among `attributes`, only present on generic declarations
+pub fn find_signature(attributes: &[Attribute]) -> Option<&Signature> {
+    attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfo::Signature(signature) => Some(signature),
+            _ => None,
+        })
+}
+
+impl RecordComponent {
+    pub fn signature(&self, pool: &ConstPool) -> Result<Option<FieldSignature>> {
+        find_signature(&self.attributes)
+            .map(|signature| signature.field_signature(pool))
+            .transpose()
+    }
+}
+
+impl StackMapFrame {
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            StackMapFrame::Same { offset_delta }
+            | StackMapFrame::SameLocals1StackItem { offset_delta, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { offset_delta, .. }
+            | StackMapFrame::Chop { offset_delta, .. }
+            | StackMapFrame::SameExtended { offset_delta }
+            | StackMapFrame::Append { offset_delta, .. }
+            | StackMapFrame::Full { offset_delta, .. } => *offset_delta,
+        }
+    }
+
+    /// The `frame_type` byte this frame is encoded with, failing for frames whose fields
+    /// cannot be represented by their frame type
+    pub fn frame_type(&self) -> Result<u8> {
+        let valid = match self {
+            StackMapFrame::Same { offset_delta } => *offset_delta <= 63,
+            StackMapFrame::SameLocals1StackItem { offset_delta, .. } => *offset_delta <= 63,
+            StackMapFrame::Chop { k, .. } => (1..=3).contains(k),
+            StackMapFrame::Append { locals, .. } => (1..=3).contains(&locals.len()),
+            _ => true,
+        };
+        if !valid {
+            return Err(ParseError::Other(format!(
+                "Invalid stack map frame {:?}",
+                self
+            )));
+        }
+        Ok(match self {
+            StackMapFrame::Same { offset_delta } => *offset_delta as u8,
+            StackMapFrame::SameLocals1StackItem { offset_delta, .. } => 64 + *offset_delta as u8,
+            StackMapFrame::SameLocals1StackItemExtended { .. } => 247,
+            StackMapFrame::Chop { k, .. } => 251 - k,
+            StackMapFrame::SameExtended { .. } => 251,
+            StackMapFrame::Append { locals, .. } => 251 + locals.len() as u8,
+            StackMapFrame::Full { .. } => 255,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object(Index<Class>),
+    /// The offset of the `new` instruction that created the object
+    Uninitialized(u16),
+}
+
+impl VerificationTypeInfo {
+    pub fn tag(&self) -> u8 {
+        match self {
+            VerificationTypeInfo::Top => 0,
+            VerificationTypeInfo::Integer => 1,
+            VerificationTypeInfo::Float => 2,
+            VerificationTypeInfo::Double => 3,
+            VerificationTypeInfo::Long => 4,
+            VerificationTypeInfo::Null => 5,
+            VerificationTypeInfo::UninitializedThis => 6,
+            VerificationTypeInfo::Object(_) => 7,
+            VerificationTypeInfo::Uninitialized(_) => 8,
+        }
+    }
+}
+
+/// A stack map frame with its absolute offset and the complete locals and stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub offset: u16,
+    pub locals: Vec<VerificationTypeInfo>,
+    pub stack: Vec<VerificationTypeInfo>,
+}
+
+impl StackMapTable {
+    /// Expands the compressed frames into absolute frames
+    ///
+    /// `initial_locals` is the implicit frame derived from the method descriptor: `this` (or
+    /// `UninitializedThis` in constructors) followed by the parameters. Like in the stack map
+    /// table itself, `Long` and `Double` are a single entry.
+    pub fn frames(&self, initial_locals: &[VerificationTypeInfo]) -> Result<Vec<Frame>> {
+        let mut frames = Vec::with_capacity(self.entries.len());
+        let mut locals = initial_locals.to_vec();
+        let mut offset: Option<u16> = None;
+        for entry in &self.entries {
+            let delta = entry.offset_delta();
+            // Every frame but the first is one past the previous offset plus the delta
+            let next = match offset {
+                None => Some(delta),
+                Some(previous) => previous.checked_add(delta).and_then(|o| o.checked_add(1)),
+            }
+            .ok_or_else(|| ParseError::Other("Stack map frame offset overflow".to_string()))?;
+            offset = Some(next);
+
+            let stack = match entry {
+                StackMapFrame::Same { .. } | StackMapFrame::SameExtended { .. } => Vec::new(),
+                StackMapFrame::SameLocals1StackItem { stack, .. }
+                | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => vec![stack.clone()],
+                StackMapFrame::Chop { k, .. } => {
+                    let k = *k as usize;
+                    if k > locals.len() {
+                        return Err(ParseError::Other(format!(
+                            "Chop frame at offset {} removes {} locals but only {} are defined",
+                            next,
+                            k,
+                            locals.len()
+                        )));
+                    }
+                    locals.truncate(locals.len() - k);
+                    Vec::new()
+                }
+                StackMapFrame::Append {
+                    locals: appended, ..
+                } => {
+                    locals.extend(appended.iter().cloned());
+                    Vec::new()
+                }
+                StackMapFrame::Full {
+                    locals: full_locals,
+                    stack,
+                    ..
+                } => {
+                    locals = full_locals.clone();
+                    stack.clone()
+                }
+            };
+            frames.push(Frame {
+                offset: next,
+                locals: locals.clone(),
+                stack,
+            });
+        }
+        Ok(frames)
+    }
+}
+
+impl GetPretty for StackMapFrame {
+    fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
+        let mut result = format!(
+            "{:indent$}frame_type = {} offset_delta = {}",
+            "",
+            self.frame_type()?,
+            self.offset_delta(),
+            indent = tabs
+        );
+        match self {
+            StackMapFrame::SameLocals1StackItem { stack, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                result.push_str(&format!("\n{:indent$}stack:", "", indent = tabs + 2));
+                result.push_str(&vec![stack.clone()].get_pretty(pool, tabs + 4)?);
+            }
+            StackMapFrame::Append { locals, .. } => {
+                result.push_str(&format!("\n{:indent$}locals:", "", indent = tabs + 2));
+                result.push_str(&locals.get_pretty(pool, tabs + 4)?);
+            }
+            StackMapFrame::Full { locals, stack, .. } => {
+                result.push_str(&format!("\n{:indent$}locals:", "", indent = tabs + 2));
+                result.push_str(&locals.get_pretty(pool, tabs + 4)?);
+                result.push_str(&format!("\n{:indent$}stack:", "", indent = tabs + 2));
+                result.push_str(&stack.get_pretty(pool, tabs + 4)?);
+            }
+            _ => {}
+        }
+        Ok(result)
+    }
+}
+
+impl GetPretty for VerificationTypeInfo {
+    fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
+        match self {
+            VerificationTypeInfo::Object(index) => Ok(format!(
+                "{:indent$}Object {}",
+                "",
+                index.get_pretty(pool, 0)?,
+                indent = tabs
+            )),
+            VerificationTypeInfo::Uninitialized(offset) => Ok(format!(
+                "{:indent$}Uninitialized {}",
+                "",
+                offset,
+                indent = tabs
+            )),
+            _ => Ok(format!("{:indent$}{:?}", "", self, indent = tabs)),
+        }
+    }
+}
+
+/// How deep arrays and annotations may nest within an element value when it is read
+pub const MAX_ELEMENT_VALUE_DEPTH: usize = 256;
+
+/// The value of an annotation element, constants are stored in the constant pool
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    Byte(Index<Integer>),
+    Char(Index<Integer>),
+    Double(Index<Double>),
+    Float(Index<Float>),
+    Int(Index<Integer>),
+    Long(Index<Long>),
+    Short(Index<Integer>),
+    Boolean(Index<Integer>),
+    String(Index<Utf8>),
+    Enum {
+        /// Field descriptor of the enum type
+        type_name_index: Index<Utf8>,
+        const_name_index: Index<Utf8>,
+    },
+    /// Return descriptor of the class, `V` for `void.class`
+    Class(Index<Utf8>),
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+impl ElementValue {
+    pub fn tag(&self) -> u8 {
+        match self {
+            ElementValue::Byte(_) => b'B',
+            ElementValue::Char(_) => b'C',
+            ElementValue::Double(_) => b'D',
+            ElementValue::Float(_) => b'F',
+            ElementValue::Int(_) => b'I',
+            ElementValue::Long(_) => b'J',
+            ElementValue::Short(_) => b'S',
+            ElementValue::Boolean(_) => b'Z',
+            ElementValue::String(_) => b's',
+            ElementValue::Enum { .. } => b'e',
+            ElementValue::Class(_) => b'c',
+            ElementValue::Annotation(_) => b'@',
+            ElementValue::Array(_) => b'[',
+        }
+    }
+
+    /// Looks up the constants referenced by this value
+    pub fn resolve(&self, pool: &ConstPool) -> Result<ResolvedElementValue> {
+        Ok(match self {
+            ElementValue::Byte(index) => ResolvedElementValue::Byte(index.get(pool)?.value as i8),
+            ElementValue::Char(index) => ResolvedElementValue::Char(index.get(pool)?.value as u16),
+            ElementValue::Double(index) => ResolvedElementValue::Double(index.get(pool)?.value),
+            ElementValue::Float(index) => ResolvedElementValue::Float(index.get(pool)?.value),
+            ElementValue::Int(index) => ResolvedElementValue::Int(index.get(pool)?.value),
+            ElementValue::Long(index) => ResolvedElementValue::Long(index.get(pool)?.value),
+            ElementValue::Short(index) => {
+                ResolvedElementValue::Short(index.get(pool)?.value as i16)
+            }
+            ElementValue::Boolean(index) => {
+                ResolvedElementValue::Boolean(index.get(pool)?.value != 0)
+            }
+            ElementValue::String(index) => {
+                ResolvedElementValue::String(index.get(pool)?.str.clone())
+            }
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => ResolvedElementValue::Enum {
+                type_name: type_name_index.get(pool)?.str.clone(),
+                const_name: const_name_index.get(pool)?.str.clone(),
+            },
+            ElementValue::Class(index) => ResolvedElementValue::Class(index.get(pool)?.str.clone()),
+            ElementValue::Annotation(annotation) => {
+                ResolvedElementValue::Annotation(annotation.resolve(pool)?)
+            }
+            ElementValue::Array(values) => ResolvedElementValue::Array(
+                values
+                    .iter()
+                    .map(|value| value.resolve(pool))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+impl ElementValue {
+    /// Renders the value the way it would be written in Java source, e.g. `42L`,
+    /// `java.lang.annotation.ElementType.FIELD` or `{"a", "b"}`
+    pub fn to_java_literal(&self, pool: &ConstPool) -> Result<String> {
+        Ok(match self {
+            ElementValue::Byte(index) | ElementValue::Short(index) | ElementValue::Int(index) => {
+                index.get(pool)?.value.to_string()
+            }
+            ElementValue::Char(index) => {
+                let value = index.get(pool)?.value as u16;
+                format!(
+                    "'{}'",
+                    escape_java(&String::from_utf16_lossy(&[value]), '\'')
+                )
+            }
+            ElementValue::Long(index) => format!("{}L", index.get(pool)?.value),
+            ElementValue::Float(index) => {
+                let value = index.get(pool)?.value;
+                if value.is_nan() {
+                    "0.0f / 0.0f".to_string()
+                } else if value.is_infinite() {
+                    format!("{}1.0f / 0.0f", if value < 0.0 { "-" } else { "" })
+                } else {
+                    format!("{:?}f", value)
+                }
+            }
+            ElementValue::Double(index) => {
+                let value = index.get(pool)?.value;
+                if value.is_nan() {
+                    "0.0 / 0.0".to_string()
+                } else if value.is_infinite() {
+                    format!("{}1.0 / 0.0", if value < 0.0 { "-" } else { "" })
+                } else {
+                    format!("{:?}", value)
+                }
+            }
+            ElementValue::Boolean(index) => (index.get(pool)?.value != 0).to_string(),
+            ElementValue::String(index) => {
+                format!("\"{}\"", escape_java(&index.get(pool)?.str, '"'))
+            }
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "{}.{}",
+                java_type_name(&type_name_index.get(pool)?.str)?,
+                const_name_index.get(pool)?.str
+            ),
+            ElementValue::Class(index) => {
+                format!("{}.class", java_type_name(&index.get(pool)?.str)?)
+            }
+            ElementValue::Annotation(annotation) => annotation.to_java_literal(pool)?,
+            ElementValue::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.to_java_literal(pool))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{{{}}}", values.join(", "))
+            }
+        })
+    }
+}
+
+/// Escapes a string for use inside a Java literal delimited by `quote`
+pub(crate) fn escape_java(value: &str, quote: char) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{c}' => result.push_str("\\f"),
+            c if c == quote => {
+                result.push('\\');
+                result.push(c);
+            }
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// The Java source name of the type in a field descriptor, or `void` as used by class literals
+fn java_type_name(descriptor: &str) -> Result<String> {
+    if descriptor == "V" {
+        return Ok("void".to_string());
+    }
+    Ok(FieldType::parse(descriptor)?.to_java())
+}
+
+impl Annotation {
+    /// Renders the annotation the way it would be written in Java source
+    pub fn to_java_literal(&self, pool: &ConstPool) -> Result<String> {
+        let mut result = format!("@{}", java_type_name(self.type_name(pool)?)?);
+        if !self.element_value_pairs.is_empty() {
+            let elements = self
+                .element_value_pairs
+                .iter()
+                .map(|pair| {
+                    Ok(format!(
+                        "{} = {}",
+                        pair.element_name_index.get(pool)?.str,
+                        pair.value.to_java_literal(pool)?
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            result.push_str(&format!("({})", elements.join(", ")));
+        }
+        Ok(result)
+    }
+
+    /// The field descriptor of the annotation interface, e.g. `Ljavax/persistence/Entity;`
+    pub fn type_name<'a>(&self, pool: &'a ConstPool) -> Result<&'a str> {
+        Ok(&self.type_index.get(pool)?.str)
+    }
+
+    pub fn resolve(&self, pool: &ConstPool) -> Result<ResolvedAnnotation> {
+        let mut elements = Vec::with_capacity(self.element_value_pairs.len());
+        for pair in &self.element_value_pairs {
+            elements.push((
+                pair.element_name_index.get(pool)?.str.clone(),
+                pair.value.resolve(pool)?,
+            ));
+        }
+        Ok(ResolvedAnnotation {
+            type_name: self.type_name(pool)?.to_string(),
+            elements,
+        })
+    }
+}
+
+/// An annotation with all constant pool references looked up
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAnnotation {
+    pub type_name: String,
+    pub elements: Vec<(String, ResolvedElementValue)>,
+}
+
+impl ResolvedAnnotation {
+    /// Returns the explicitly set value of an element, defaults are stored on the annotation
+    /// interface instead
+    pub fn get(&self, name: &str) -> Option<&ResolvedElementValue> {
+        self.elements
+            .iter()
+            .find(|(element, _)| element == name)
+            .map(|(_, value)| value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedElementValue {
+    Byte(i8),
+    /// A UTF-16 code unit
+    Char(u16),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    Long(i64),
+    Short(i16),
+    Boolean(bool),
+    String(String),
+    Enum {
+        type_name: String,
+        const_name: String,
+    },
+    Class(String),
+    Annotation(ResolvedAnnotation),
+    Array(Vec<ResolvedElementValue>),
+}
+
+impl GetPretty for ElementValue {
+    fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
+        match self {
+            ElementValue::Byte(index)
+            | ElementValue::Char(index)
+            | ElementValue::Int(index)
+            | ElementValue::Short(index)
+            | ElementValue::Boolean(index) => index.get_pretty(pool, tabs),
+            ElementValue::Double(index) => index.get_pretty(pool, tabs),
+            ElementValue::Float(index) => index.get_pretty(pool, tabs),
+            ElementValue::Long(index) => index.get_pretty(pool, tabs),
+            ElementValue::String(index) | ElementValue::Class(index) => {
+                index.get_pretty(pool, tabs)
+            }
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => Ok(format!(
+                "{:indent$}Enum {}.{}",
+                "",
+                type_name_index.get(pool)?.str,
+                const_name_index.get(pool)?.str,
+                indent = tabs
+            )),
+            ElementValue::Annotation(annotation) => annotation.get_pretty(pool, tabs),
+            ElementValue::Array(values) => Ok(format!(
+                "{:indent$}Array{}",
+                "",
+                values.get_pretty(pool, tabs + 2)?,
+                indent = tabs
+            )),
+        }
+    }
+}
+
+gen_try_from! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum TargetType {
+        ClassTypeParameter = 0x00,
+        MethodTypeParameter = 0x01,
+        Supertype = 0x10,
+        ClassTypeParameterBound = 0x11,
+        MethodTypeParameterBound = 0x12,
+        Field = 0x13,
+        MethodReturn = 0x14,
+        MethodReceiver = 0x15,
+        MethodFormalParameter = 0x16,
+        Throws = 0x17,
+        LocalVariable = 0x40,
+        ResourceVariable = 0x41,
+        ExceptionParameter = 0x42,
+        Instanceof = 0x43,
+        New = 0x44,
+        ConstructorReference = 0x45,
+        MethodReference = 0x46,
+        Cast = 0x47,
+        ConstructorInvocationTypeArgument = 0x48,
+        MethodInvocationTypeArgument = 0x49,
+        ConstructorReferenceTypeArgument = 0x4A,
+        MethodReferenceTypeArgument = 0x4B,
+    }
+}
+
+gen_try_from! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum TypePathKind {
+        Array = 0,
+        Nested = 1,
+        WildcardBound = 2,
+        TypeArgument = 3,
+    }
+}
+
+/// Which type in a declaration or expression a type annotation applies to, the variant is
+/// determined by the `TargetType`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetInfo {
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    /// 65535 for the superclass, otherwise an index into the interfaces
+    Supertype {
+        supertype_index: u16,
+    },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Empty,
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    Throws {
+        throws_type_index: u16,
+    },
+    Localvar {
+        table: Vec<LocalVarTarget>,
+    },
+    Catch {
+        exception_table_index: u16,
+    },
+    Offset {
+        offset: u16,
+    },
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+impl TargetType {
+    /// Whether `info` has the layout used by this target type
+    pub fn accepts(&self, info: &TargetInfo) -> bool {
+        matches!(
+            (self, info),
+            (
+                TargetType::ClassTypeParameter | TargetType::MethodTypeParameter,
+                TargetInfo::TypeParameter { .. }
+            ) | (TargetType::Supertype, TargetInfo::Supertype { .. })
+                | (
+                    TargetType::ClassTypeParameterBound | TargetType::MethodTypeParameterBound,
+                    TargetInfo::TypeParameterBound { .. }
+                )
+                | (
+                    TargetType::Field | TargetType::MethodReturn | TargetType::MethodReceiver,
+                    TargetInfo::Empty
+                )
+                | (
+                    TargetType::MethodFormalParameter,
+                    TargetInfo::FormalParameter { .. }
+                )
+                | (TargetType::Throws, TargetInfo::Throws { .. })
+                | (
+                    TargetType::LocalVariable | TargetType::ResourceVariable,
+                    TargetInfo::Localvar { .. }
+                )
+                | (TargetType::ExceptionParameter, TargetInfo::Catch { .. })
+                | (
+                    TargetType::Instanceof
+                        | TargetType::New
+                        | TargetType::ConstructorReference
+                        | TargetType::MethodReference,
+                    TargetInfo::Offset { .. }
+                )
+                | (
+                    TargetType::Cast
+                        | TargetType::ConstructorInvocationTypeArgument
+                        | TargetType::MethodInvocationTypeArgument
+                        | TargetType::ConstructorReferenceTypeArgument
+                        | TargetType::MethodReferenceTypeArgument,
+                    TargetInfo::TypeArgument { .. }
+                )
+        )
+    }
+}
+
+impl GetPretty for TargetInfo {
+    fn get_pretty(&self, _pool: &ConstPool, tabs: usize) -> Result<String> {
+        Ok(format!("{:indent$}{:?}", "", self, indent = tabs))
+    }
+}
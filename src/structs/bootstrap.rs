@@ -0,0 +1,198 @@
+//! Links `InvokeDynamic` call sites and `Dynamic` constants to the entry of the `BootstrapMethods`
+//! attribute that bootstraps them, with the bootstrap method and its static arguments looked up
+
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{error::ParseError, Result};
+
+use super::{
+    attributes::BootstrapMethod,
+    const_types::{MethodHandleReferenceKind, ResolvedMemberRef},
+    Class, ConstItem, ConstPool, Index,
+};
+
+/// Whether a dynamically computed entry is a call site or a constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicKind {
+    /// An `InvokeDynamic` entry, used by `invokedynamic`
+    CallSite,
+    /// A `Dynamic` entry, loaded with `ldc` or passed as a bootstrap argument
+    Constant,
+}
+
+/// An `InvokeDynamic` or `Dynamic` entry with its bootstrap method looked up
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDynamic {
+    pub kind: DynamicKind,
+    pub name: String,
+    /// A method descriptor for call sites, a field descriptor for constants
+    pub descriptor: String,
+    pub bootstrap_method_attr_index: u16,
+    pub bootstrap_method: ResolvedBootstrapMethod,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedBootstrapMethod {
+    pub reference_kind: MethodHandleReferenceKind,
+    pub method: ResolvedMemberRef,
+    pub arguments: Vec<BootstrapArgument>,
+}
+
+/// A static argument of a bootstrap method, which can be any loadable constant
+#[derive(Debug, Clone, PartialEq)]
+pub enum BootstrapArgument {
+    Int(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    /// The internal name of the class, or the descriptor of an array class
+    Class(String),
+    String(String),
+    MethodHandle {
+        reference_kind: MethodHandleReferenceKind,
+        member: ResolvedMemberRef,
+    },
+    /// The method descriptor of the type
+    MethodType(String),
+    /// Shared by every argument that refers to the same entry
+    Dynamic(Rc<ResolvedDynamic>),
+}
+
+/// How deeply `Dynamic` entries can be nested as arguments of each other's bootstrap methods.
+/// javac never nests them, and a hand written class has no need to go anywhere near this deep.
+pub const MAX_DYNAMIC_DEPTH: usize = 64;
+
+/// Resolves entries once, so that an entry used by many arguments costs no more than one
+pub(crate) struct Resolver<'a> {
+    pool: &'a ConstPool,
+    methods: &'a [BootstrapMethod],
+    // The `Dynamic` entries being resolved, which a bootstrap argument must not refer back to
+    resolving: Vec<u16>,
+    resolved: HashMap<u16, Rc<ResolvedDynamic>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(class: &'a Class) -> Self {
+        Resolver {
+            pool: &class.constant_pool,
+            methods: class.bootstrap_methods(),
+            resolving: Vec::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn bootstrap_method(&self, index: u16) -> Result<&'a BootstrapMethod> {
+        self.methods
+            .get(index as usize)
+            .ok_or(ParseError::BootstrapMethodOutOfRange {
+                index,
+                count: self.methods.len(),
+            })
+    }
+
+    /// Resolves the `InvokeDynamic` or `Dynamic` entry at `index`
+    pub(crate) fn dynamic(&mut self, index: u16) -> Result<ResolvedDynamic> {
+        Ok(self.shared(index)?.as_ref().clone())
+    }
+
+    fn shared(&mut self, index: u16) -> Result<Rc<ResolvedDynamic>> {
+        if let Some(resolved) = self.resolved.get(&index) {
+            return Ok(resolved.clone());
+        }
+        let (kind, bootstrap_method_attr_index, name_and_type_index) =
+            match Index::<ConstItem>::new(index).get(self.pool)? {
+                ConstItem::InvokeDynamic(site) => (
+                    DynamicKind::CallSite,
+                    site.bootstrap_method_attr_index,
+                    &site.name_and_type_index,
+                ),
+                ConstItem::Dynamic(constant) => (
+                    DynamicKind::Constant,
+                    constant.bootstrap_method_attr_index,
+                    &constant.name_and_type_index,
+                ),
+                item => {
+                    return Err(ParseError::WrongConstantType {
+                        index,
+                        expected: "InvokeDynamic or Dynamic",
+                        found: item.name(),
+                    })
+                }
+            };
+        let name_and_type = name_and_type_index.get(self.pool)?;
+        let name = name_and_type.name_index.get(self.pool)?.str.clone();
+        let descriptor = name_and_type.descriptor_index.get(self.pool)?.str.clone();
+        let method = self.bootstrap_method(bootstrap_method_attr_index)?;
+
+        if self.resolving.contains(&index) {
+            return Err(ParseError::Other(format!(
+                "Dynamic constant {} is an argument of its own bootstrap method",
+                index
+            )));
+        }
+        if self.resolving.len() >= MAX_DYNAMIC_DEPTH {
+            return Err(ParseError::Other(format!(
+                "Dynamic constant {} is nested more than {} levels deep",
+                index, MAX_DYNAMIC_DEPTH
+            )));
+        }
+        self.resolving.push(index);
+        let bootstrap_method = self.method(method);
+        self.resolving.pop();
+
+        let resolved = Rc::new(ResolvedDynamic {
+            kind,
+            name,
+            descriptor,
+            bootstrap_method_attr_index,
+            bootstrap_method: bootstrap_method?,
+        });
+        self.resolved.insert(index, resolved.clone());
+        Ok(resolved)
+    }
+
+    fn method(&mut self, method: &BootstrapMethod) -> Result<ResolvedBootstrapMethod> {
+        let handle = method.bootstrap_method_ref.get(self.pool)?;
+        let arguments = method
+            .bootstrap_arguments
+            .iter()
+            .map(|argument| self.argument(argument))
+            .collect::<Result<_>>()?;
+        Ok(ResolvedBootstrapMethod {
+            reference_kind: handle.reference_kind.clone(),
+            method: handle.resolve(self.pool)?,
+            arguments,
+        })
+    }
+
+    fn argument(&mut self, index: &Index<ConstItem>) -> Result<BootstrapArgument> {
+        let pool = self.pool;
+        Ok(match index.get(pool)? {
+            ConstItem::Integer(value) => BootstrapArgument::Int(value.value),
+            ConstItem::Float(value) => BootstrapArgument::Float(value.value),
+            ConstItem::Long(value) => BootstrapArgument::Long(value.value),
+            ConstItem::Double(value) => BootstrapArgument::Double(value.value),
+            ConstItem::Class(class) => {
+                BootstrapArgument::Class(class.name_index.get(pool)?.str.clone())
+            }
+            ConstItem::StringJ(string) => {
+                BootstrapArgument::String(string.string_index.get(pool)?.str.clone())
+            }
+            ConstItem::MethodHandle(handle) => BootstrapArgument::MethodHandle {
+                reference_kind: handle.reference_kind.clone(),
+                member: handle.resolve(pool)?,
+            },
+            ConstItem::MethodType(method_type) => {
+                BootstrapArgument::MethodType(method_type.descriptor_index.get(pool)?.str.clone())
+            }
+            ConstItem::Dynamic(_) => BootstrapArgument::Dynamic(self.shared(index.index)?),
+            item => {
+                return Err(ParseError::WrongConstantType {
+                    index: index.index,
+                    expected: "a loadable constant",
+                    found: item.name(),
+                })
+            }
+        })
+    }
+}
@@ -1,8 +1,11 @@
-use crate::impl_get_pretty;
+use crate::{error::Result, impl_get_pretty, signature::ClassSignature};
 
 use super::{
-    access_flags::ClassFlags, attributes::Attributes, const_types::Class as ConstClass,
-    ClassVersion, ConstPool, Fields, Index, Interfaces, Methods,
+    access_flags::ClassFlags,
+    attributes::{find_signature, AttributeInfo, Attributes, BootstrapMethod},
+    bootstrap::{ResolvedDynamic, Resolver},
+    const_types::Class as ConstClass,
+    ClassVersion, ConstItem, ConstPool, Diagnostic, Fields, Index, Interfaces, Methods,
 };
 
 pub const MAGIC: u32 = 0xCAFEBABE;
@@ -20,5 +23,52 @@ pub struct Class {
     pub fields: Fields,
     pub methods: Methods,
     pub attributes: Attributes,
+    pub diagnostics: Vec<Diagnostic>,
 }
 }
+
+impl Class {
+    /// The entries of the `BootstrapMethods` attribute, empty if the class has none
+    pub fn bootstrap_methods(&self) -> &[BootstrapMethod] {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::BootstrapMethods(attribute) => {
+                    Some(&attribute.bootstrap_methods[..])
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The bootstrap method an `InvokeDynamic` or `Dynamic` entry refers to with its
+    /// `bootstrap_method_attr_index`
+    pub fn bootstrap_method(&self, index: u16) -> Result<&BootstrapMethod> {
+        Resolver::new(self).bootstrap_method(index)
+    }
+
+    /// The `InvokeDynamic` or `Dynamic` entry at `index`, with its bootstrap method and the
+    /// static arguments looked up
+    pub fn resolve_dynamic(&self, index: u16) -> Result<ResolvedDynamic> {
+        Resolver::new(self).dynamic(index)
+    }
+
+    /// Every `InvokeDynamic` and `Dynamic` entry of the constant pool with its index
+    pub fn resolve_dynamics(&self) -> Result<Vec<(u16, ResolvedDynamic)>> {
+        let mut resolver = Resolver::new(self);
+        let mut resolved = Vec::new();
+        for (index, entry) in self.constant_pool.entries.iter().enumerate() {
+            if let Some(ConstItem::InvokeDynamic(_) | ConstItem::Dynamic(_)) = entry {
+                resolved.push((index as u16, resolver.dynamic(index as u16)?));
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// The generic signature, if the class has one
+    pub fn signature(&self) -> Result<Option<ClassSignature>> {
+        find_signature(&self.attributes)
+            .map(|signature| signature.class_signature(&self.constant_pool))
+            .transpose()
+    }
+}
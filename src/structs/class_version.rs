@@ -1,5 +1,8 @@
+use std::convert::TryInto;
+
 use crate::gen_try_from;
 use crate::impl_get_pretty;
+use crate::{error::ParseError, Result};
 
 impl_get_pretty! {
 #[derive(Debug, PartialEq)]
@@ -71,6 +74,28 @@ gen_try_from! {
 // }
 // }
 
+impl MajorVersion {
+    /// The Java SE release number, e.g. 17 for `JDK_17`.
+    /// Everything before Java 5 is reported as release 1
+    pub fn release(self) -> u16 {
+        match self as u16 {
+            major if major < MajorVersion::JDK_5 as u16 => 1,
+            major => major - 44,
+        }
+    }
+
+    /// The class file version produced when compiling for a release (5 and up)
+    pub fn from_release(release: u16) -> Result<Self> {
+        if release < 5 {
+            return Err(ParseError::Unrecognized("release", release.to_string()));
+        }
+        release
+            .checked_add(44)
+            .ok_or_else(|| ParseError::Unrecognized("release", release.to_string()))?
+            .try_into()
+    }
+}
+
 impl From<MajorVersion> for u16 {
     fn from(major: MajorVersion) -> u16 {
         major as u16
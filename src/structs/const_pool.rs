@@ -1,16 +1,19 @@
+use std::collections::HashMap;
+
 use super::{ConstItem, Index};
 use crate::{
     error::ParseError,
     impl_get_pretty,
     structs::const_types::{
         Class, Double, Dynamic, FieldRef, Float, Integer, InterfaceMethodRef, InvokeDynamic, Long,
-        MethodHandle, MethodRef, MethodType, Module, NameAndType, Package, StringJ, Utf8,
+        MethodHandle, MethodHandleReference, MethodHandleReferenceKind, MethodRef, MethodType,
+        Module, NameAndType, Package, StringJ, Utf8,
     },
-    Result,
+    Result, Writable,
 };
 
 impl_get_pretty! {
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct ConstPool {
     pub entries: Vec<Option<ConstItem>>,
 }
@@ -18,22 +21,364 @@ pub struct ConstPool {
 
 impl ConstPool {
     pub fn get<T: TryFromItem>(&self, index: &Index<T>) -> Result<&T> {
-        let entry = &self.entries[index.index as usize];
-        let entry = entry.as_ref().unwrap();
-        T::try_from(entry).ok_or(ParseError::Other(
-            "Invalid constant pool entry access".to_string(),
-        ))
+        let entry = self
+            .entries
+            .get(index.index as usize)
+            .ok_or(ParseError::ConstantIndexOutOfRange {
+                index: index.index,
+                count: self.entries.len(),
+            })?
+            .as_ref()
+            // Index 0 and the slot after a Long or Double
+            .ok_or(ParseError::UnusableConstantIndex(index.index))?;
+        T::try_from(entry).ok_or(ParseError::WrongConstantType {
+            index: index.index,
+            expected: T::NAME,
+            found: entry.name(),
+        })
+    }
+}
+
+fn is_wide(item: &ConstItem) -> bool {
+    matches!(item, ConstItem::Long(_) | ConstItem::Double(_))
+}
+
+// Entries are compared by their encoding, so floats compare by their bits and `Utf8` by its bytes.
+// Writing to a `Vec` cannot fail, but a `Utf8` too long for the class file cannot be encoded
+fn key(item: &ConstItem) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    item.write(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Builds a constant pool where every entry is added once, no matter how often it is added.
+/// Long and Double entries take up the slot after them, and the pool cannot grow beyond 65535
+/// slots.
+///
+/// A builder seeded with `from_pool` keeps the existing entries at their indices, so the pool
+/// of a class can be extended without touching the indices that refer into it.
+#[derive(Debug, Clone)]
+pub struct ConstPoolBuilder {
+    entries: Vec<Option<ConstItem>>,
+    // The first index of every entry
+    indices: HashMap<Vec<u8>, u16>,
+}
+
+impl Default for ConstPoolBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ConstPool> for ConstPoolBuilder {
+    fn from(pool: ConstPool) -> Self {
+        Self::from_pool(pool)
+    }
+}
+
+impl ConstPoolBuilder {
+    pub fn new() -> Self {
+        ConstPoolBuilder {
+            entries: vec![None],
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Starts from the entries of `pool`, where duplicates resolve to the first of them
+    pub fn from_pool(pool: ConstPool) -> Self {
+        let mut builder = ConstPoolBuilder {
+            entries: pool.entries,
+            indices: HashMap::new(),
+        };
+        if builder.entries.is_empty() {
+            builder.entries.push(None);
+        }
+        for (i, entry) in builder.entries.iter().enumerate() {
+            // An entry that cannot be encoded is kept, but never matches another
+            if let Some(Ok(key)) = entry.as_ref().map(key) {
+                builder.indices.entry(key).or_insert(i as u16);
+            }
+        }
+        builder
+    }
+
+    /// The number of slots, including slot 0 and the ones after Long and Double entries, which
+    /// is the `constant_pool_count` of the class file
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries besides slot 0
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    /// The entry at `index`, `None` for slot 0 and the slot after a Long or Double
+    pub fn entry(&self, index: u16) -> Option<&ConstItem> {
+        self.entries.get(index as usize)?.as_ref()
+    }
+
+    /// The first index holding `item`
+    pub fn find(&self, item: &ConstItem) -> Option<u16> {
+        self.indices.get(&key(item).ok()?).copied()
+    }
+
+    /// Returns the index of `value`, adding it to the end of the pool if it is not present yet
+    pub fn add<T: TryFromItem>(&mut self, value: T) -> Result<Index<T>>
+    where
+        ConstItem: From<T>,
+    {
+        let item = ConstItem::from(value);
+        if let Some(&index) = self.indices.get(&key(&item)?) {
+            return Ok(Index::new(index));
+        }
+        let index = self.entries.len();
+        let end = if is_wide(&item) { index + 2 } else { index + 1 };
+        if end > u16::MAX as usize {
+            return Err(ParseError::ConstantPoolFull(item.name()));
+        }
+        self.declare(index as u16, item)?;
+        Ok(Index::new(index as u16))
+    }
+
+    pub fn utf8(&mut self, text: &str) -> Result<Index<Utf8>> {
+        self.add(Utf8::new(text))
+    }
+
+    pub fn integer(&mut self, value: i32) -> Result<Index<Integer>> {
+        self.add(Integer { value })
+    }
+
+    pub fn float(&mut self, value: f32) -> Result<Index<Float>> {
+        self.add(Float { value })
+    }
+
+    pub fn long(&mut self, value: i64) -> Result<Index<Long>> {
+        self.add(Long { value })
+    }
+
+    pub fn double(&mut self, value: f64) -> Result<Index<Double>> {
+        self.add(Double { value })
+    }
+
+    /// A class by its internal name, like `java/lang/Object`
+    pub fn class(&mut self, name: &str) -> Result<Index<Class>> {
+        let name_index = self.utf8(name)?;
+        self.add(Class { name_index })
+    }
+
+    pub fn string(&mut self, text: &str) -> Result<Index<StringJ>> {
+        let string_index = self.utf8(text)?;
+        self.add(StringJ { string_index })
+    }
+
+    pub fn name_and_type(&mut self, name: &str, descriptor: &str) -> Result<Index<NameAndType>> {
+        let name_index = self.utf8(name)?;
+        let descriptor_index = self.utf8(descriptor)?;
+        self.add(NameAndType {
+            name_index,
+            descriptor_index,
+        })
+    }
+
+    pub fn field_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Index<FieldRef>> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(FieldRef {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    pub fn method_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Index<MethodRef>> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(MethodRef {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    pub fn interface_method_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Index<InterfaceMethodRef>> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(InterfaceMethodRef {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    /// A method handle to a field or method reference that was added before
+    pub fn method_handle(
+        &mut self,
+        reference_kind: MethodHandleReferenceKind,
+        reference_index: impl Into<MethodHandleReference>,
+    ) -> Result<Index<MethodHandle>> {
+        self.add(MethodHandle {
+            reference_kind,
+            reference_index: reference_index.into(),
+        })
+    }
+
+    pub fn method_type(&mut self, descriptor: &str) -> Result<Index<MethodType>> {
+        let descriptor_index = self.utf8(descriptor)?;
+        self.add(MethodType { descriptor_index })
+    }
+
+    /// A dynamically computed constant, bootstrapped by the entry of the `BootstrapMethods`
+    /// attribute at `bootstrap_method_attr_index`
+    pub fn dynamic(
+        &mut self,
+        bootstrap_method_attr_index: u16,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Index<Dynamic>> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        })
+    }
+
+    /// A call site for `invokedynamic`, bootstrapped by the entry of the `BootstrapMethods`
+    /// attribute at `bootstrap_method_attr_index`
+    pub fn invoke_dynamic(
+        &mut self,
+        bootstrap_method_attr_index: u16,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Index<InvokeDynamic>> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        })
+    }
+
+    pub fn module(&mut self, name: &str) -> Result<Index<Module>> {
+        let name_index = self.utf8(name)?;
+        self.add(Module { name_index })
+    }
+
+    pub fn package(&mut self, name: &str) -> Result<Index<Package>> {
+        let name_index = self.utf8(name)?;
+        self.add(Package { name_index })
+    }
+
+    /// Makes room for entries placed with `declare` up to `end`, so the ones added later come
+    /// after them
+    pub(crate) fn reserve(&mut self, end: usize) {
+        if self.entries.len() < end {
+            self.entries.resize(end, None);
+        }
+    }
+
+    /// Places `item` at `index`, which is only valid once `build` checks that every slot is filled
+    pub(crate) fn declare(&mut self, index: u16, item: ConstItem) -> Result<()> {
+        let key = key(&item)?;
+        self.reserve(index as usize + if is_wide(&item) { 2 } else { 1 });
+        let first = self.indices.entry(key).or_insert(index);
+        *first = (*first).min(index);
+        self.entries[index as usize] = Some(item);
+        Ok(())
+    }
+
+    /// Checks that every slot holds an entry, except for slot 0 and the ones after Long and
+    /// Double entries
+    pub fn build(self) -> Result<ConstPool> {
+        if self.entries.len() > u16::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "The constant pool has {} slots, the limit is {}",
+                self.entries.len(),
+                u16::MAX
+            )));
+        }
+        let mut after_wide = false;
+        for (i, entry) in self.entries.iter().enumerate().skip(1) {
+            match entry {
+                Some(item) if after_wide => {
+                    return Err(ParseError::Other(format!(
+                        "Constant pool slot {} holds {}, but is the second half of the entry \
+                         before it",
+                        i,
+                        item.name()
+                    )))
+                }
+                None if !after_wide => return Err(ParseError::UnusableConstantIndex(i as u16)),
+                _ => {}
+            }
+            after_wide = entry.as_ref().is_some_and(is_wide);
+        }
+        let mut pool = ConstPool {
+            entries: self.entries,
+        };
+        pool.link_method_handles();
+        Ok(pool)
+    }
+}
+
+impl ConstPool {
+    /// Points `InvokeStatic` and `InvokeSpecial` method handles at an interface method when
+    /// that is what they refer to, which is only known once the whole pool has been read
+    pub(crate) fn link_method_handles(&mut self) {
+        for i in 0..self.entries.len() {
+            let target = match &self.entries[i] {
+                Some(ConstItem::MethodHandle(handle)) => match &handle.reference_kind {
+                    MethodHandleReferenceKind::InvokeStatic
+                    | MethodHandleReferenceKind::InvokeSpecial => handle.reference_index.index(),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let interface = matches!(
+                self.entries.get(target as usize),
+                Some(Some(ConstItem::InterfaceMethodRef(_)))
+            );
+            if let Some(ConstItem::MethodHandle(handle)) = &mut self.entries[i] {
+                handle.reference_index = if interface {
+                    MethodHandleReference::InterfaceMethod(Index::new(target))
+                } else {
+                    MethodHandleReference::Method(Index::new(target))
+                };
+            }
+        }
     }
 }
 
 pub trait TryFromItem: Sized {
+    /// The name used in errors when an index points to the wrong kind of entry
+    const NAME: &'static str;
+
     fn try_from(item: &ConstItem) -> Option<&Self>;
 }
 
 macro_rules! impl_try_from_item {
     ($($name:ident),*) => {
         $(
+            impl From<$name> for ConstItem {
+                fn from(value: $name) -> Self {
+                    ConstItem::$name(value)
+                }
+            }
+
             impl TryFromItem for $name {
+                const NAME: &'static str = stringify!($name);
+
                 fn try_from(item: &ConstItem) -> Option<&Self> {
                     if let ConstItem::$name(x) = item {
                         Some(x)
@@ -47,6 +392,8 @@ macro_rules! impl_try_from_item {
 }
 
 impl TryFromItem for ConstItem {
+    const NAME: &'static str = "ConstItem";
+
     fn try_from(item: &ConstItem) -> Option<&Self> {
         Some(item)
     }
@@ -1,8 +1,8 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData};
 
-use crate::{impl_get_pretty, pretty_print::GetPretty, Result};
+use crate::{error::ParseError, impl_get_pretty, pretty_print::GetPretty, Result};
 
-use super::{const_pool::TryFromItem, ConstPool};
+use super::{const_pool::TryFromItem, ClassVersion, ConstPool, MajorVersion};
 
 impl_get_pretty! {
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +27,31 @@ pub enum ConstItem {
 }
 }
 
+impl ConstItem {
+    /// The name of the variant, matching `TryFromItem::NAME`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConstItem::Utf8(_) => "Utf8",
+            ConstItem::Integer(_) => "Integer",
+            ConstItem::Float(_) => "Float",
+            ConstItem::Long(_) => "Long",
+            ConstItem::Double(_) => "Double",
+            ConstItem::Class(_) => "Class",
+            ConstItem::StringJ(_) => "StringJ",
+            ConstItem::FieldRef(_) => "FieldRef",
+            ConstItem::MethodRef(_) => "MethodRef",
+            ConstItem::InterfaceMethodRef(_) => "InterfaceMethodRef",
+            ConstItem::NameAndType(_) => "NameAndType",
+            ConstItem::MethodHandle(_) => "MethodHandle",
+            ConstItem::MethodType(_) => "MethodType",
+            ConstItem::Dynamic(_) => "Dynamic",
+            ConstItem::InvokeDynamic(_) => "InvokeDynamic",
+            ConstItem::Module(_) => "Module",
+            ConstItem::Package(_) => "Package",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Index<T: TryFromItem> {
     pub index: u16,
@@ -71,6 +96,37 @@ pub struct Utf8 {
     pub str: String,
 }
 
+impl Utf8 {
+    /// Encodes `text` in the modified UTF-8 used by class files
+    pub fn new(text: &str) -> Self {
+        Utf8 {
+            bytes: cesu8::to_java_cesu8(text).into_owned(),
+            str: text.to_string(),
+        }
+    }
+
+    /// Decodes modified UTF-8 bytes, replacing what cannot be decoded
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        let str = match cesu8::from_java_cesu8(&bytes) {
+            Ok(str) => str,
+            Err(_) => String::from_utf8_lossy(&bytes),
+        }
+        .to_string();
+        Utf8 { bytes, str }
+    }
+
+    /// The bytes a class file stores for `str`. The original bytes are kept while they still
+    /// decode to `str`, so that strings which could not be decoded round-trip exactly
+    pub fn encoded(&self) -> Cow<'_, [u8]> {
+        let encoded = cesu8::to_java_cesu8(&self.str);
+        if *encoded == self.bytes[..] || Utf8::from_bytes(self.bytes.clone()).str == self.str {
+            Cow::Borrowed(&self.bytes)
+        } else {
+            encoded
+        }
+    }
+}
+
 impl GetPretty for Utf8 {
     fn get_pretty(&self, _pool: &ConstPool, tabs: usize) -> Result<String> {
         Ok(format!("{:indent$}{:?}", "", self.str, indent = tabs))
@@ -136,11 +192,7 @@ pub struct NameAndType {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MethodHandle {
     pub reference_kind: MethodHandleReferenceKind,
-    // Index into the constant pool table
-    // If the reference_kind is 1-4, then this points to a FieldRef
-    // If the reference_kind is 5-8, then this points to a MethodRef
-    // If the reference_kind is 9, then this points to an InterfaceMethodRef
-    pub reference_index: u16,
+    pub reference_index: MethodHandleReference,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -185,3 +237,164 @@ pub struct Package {
     pub name_index: Index<Utf8>,
 }
 }
+
+/// The field or method a method handle refers to. The kind of entry follows from the reference
+/// kind, except that `InvokeStatic` and `InvokeSpecial` can refer to an interface method since
+/// Java 8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MethodHandleReference {
+    Field(Index<FieldRef>),
+    Method(Index<MethodRef>),
+    InterfaceMethod(Index<InterfaceMethodRef>),
+}
+
+impl From<Index<FieldRef>> for MethodHandleReference {
+    fn from(index: Index<FieldRef>) -> Self {
+        MethodHandleReference::Field(index)
+    }
+}
+
+impl From<Index<MethodRef>> for MethodHandleReference {
+    fn from(index: Index<MethodRef>) -> Self {
+        MethodHandleReference::Method(index)
+    }
+}
+
+impl From<Index<InterfaceMethodRef>> for MethodHandleReference {
+    fn from(index: Index<InterfaceMethodRef>) -> Self {
+        MethodHandleReference::InterfaceMethod(index)
+    }
+}
+
+impl MethodHandleReference {
+    /// The reference a handle of `kind` makes to `index`, where `InvokeStatic` and `InvokeSpecial`
+    /// are taken to refer to a class method until the pool says otherwise
+    pub fn for_kind(kind: &MethodHandleReferenceKind, index: u16) -> Self {
+        match kind {
+            MethodHandleReferenceKind::GetField
+            | MethodHandleReferenceKind::GetStatic
+            | MethodHandleReferenceKind::PutField
+            | MethodHandleReferenceKind::PutStatic => {
+                MethodHandleReference::Field(Index::new(index))
+            }
+            MethodHandleReferenceKind::InvokeInterface => {
+                MethodHandleReference::InterfaceMethod(Index::new(index))
+            }
+            _ => MethodHandleReference::Method(Index::new(index)),
+        }
+    }
+
+    pub fn index(&self) -> u16 {
+        match self {
+            MethodHandleReference::Field(index) => index.index,
+            MethodHandleReference::Method(index) => index.index,
+            MethodHandleReference::InterfaceMethod(index) => index.index,
+        }
+    }
+
+    /// The class, name and descriptor of the referenced entry
+    pub fn resolve(&self, pool: &ConstPool) -> Result<ResolvedMemberRef> {
+        let (class_index, name_and_type_index) = match self {
+            MethodHandleReference::Field(index) => {
+                let field = index.get(pool)?;
+                (&field.class_index, &field.name_and_type_index)
+            }
+            MethodHandleReference::Method(index) => {
+                let method = index.get(pool)?;
+                (&method.class_index, &method.name_and_type_index)
+            }
+            MethodHandleReference::InterfaceMethod(index) => {
+                let method = index.get(pool)?;
+                (&method.class_index, &method.name_and_type_index)
+            }
+        };
+        let name_and_type = name_and_type_index.get(pool)?;
+        Ok(ResolvedMemberRef {
+            owner: class_index.get(pool)?.name_index.get(pool)?.str.clone(),
+            name: name_and_type.name_index.get(pool)?.str.clone(),
+            descriptor: name_and_type.descriptor_index.get(pool)?.str.clone(),
+        })
+    }
+}
+
+impl GetPretty for MethodHandleReference {
+    fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
+        match self {
+            MethodHandleReference::Field(index) => index.get_pretty(pool, tabs),
+            MethodHandleReference::Method(index) => index.get_pretty(pool, tabs),
+            MethodHandleReference::InterfaceMethod(index) => index.get_pretty(pool, tabs),
+        }
+    }
+}
+
+/// A field or method reference with its constant pool references looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedMemberRef {
+    /// The internal name of the class, like `java/lang/Object`
+    pub owner: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl MethodHandle {
+    /// The field or method the handle refers to
+    pub fn resolve(&self, pool: &ConstPool) -> Result<ResolvedMemberRef> {
+        self.reference_index.resolve(pool)
+    }
+
+    /// Checks the handle against the rules of JVMS 4.4.8: the kind of entry it refers to must
+    /// match the reference kind, and only `NewInvokeSpecial` may refer to a constructor
+    pub fn validate(&self, pool: &ConstPool, version: &ClassVersion) -> Result<()> {
+        use MethodHandleReferenceKind::*;
+
+        let invalid = |reason: String| Err(ParseError::InvalidMethodHandle(reason));
+        let interface_allowed = version.major as u16 >= MajorVersion::JDK_8 as u16;
+        let expected = match (&self.reference_kind, &self.reference_index) {
+            (GetField | GetStatic | PutField | PutStatic, MethodHandleReference::Field(_))
+            | (InvokeVirtual | NewInvokeSpecial, MethodHandleReference::Method(_))
+            | (InvokeStatic | InvokeSpecial, MethodHandleReference::Method(_))
+            | (InvokeInterface, MethodHandleReference::InterfaceMethod(_)) => None,
+            (InvokeStatic | InvokeSpecial, MethodHandleReference::InterfaceMethod(_))
+                if interface_allowed =>
+            {
+                None
+            }
+            (GetField | GetStatic | PutField | PutStatic, _) => Some("a FieldRef"),
+            (InvokeVirtual | NewInvokeSpecial, _) => Some("a MethodRef"),
+            (InvokeStatic | InvokeSpecial, _) if interface_allowed => {
+                Some("a MethodRef or InterfaceMethodRef")
+            }
+            (InvokeStatic | InvokeSpecial, _) => Some("a MethodRef before Java 8"),
+            (InvokeInterface, _) => Some("an InterfaceMethodRef"),
+        };
+        if let Some(expected) = expected {
+            return invalid(format!(
+                "{:?} must refer to {}, found {}",
+                self.reference_kind,
+                expected,
+                pool.entries
+                    .get(self.reference_index.index() as usize)
+                    .and_then(Option::as_ref)
+                    .map_or("nothing", ConstItem::name)
+            ));
+        }
+
+        // Also checks that the entry is of the kind the reference says
+        let member = self.resolve(pool)?;
+        match self.reference_kind {
+            NewInvokeSpecial if member.name != "<init>" => invalid(format!(
+                "NewInvokeSpecial must refer to a constructor, found {}",
+                member.name
+            )),
+            InvokeVirtual | InvokeStatic | InvokeSpecial | InvokeInterface
+                if member.name == "<init>" || member.name == "<clinit>" =>
+            {
+                invalid(format!(
+                    "{:?} cannot refer to {}",
+                    self.reference_kind, member.name
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::impl_get_pretty;
+
+impl_get_pretty! {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    // Allowed by the specification, but worth knowing about, e.g. an unrecognized attribute
+    Warning = 0,
+    // Off-spec, only recovered from when parsing leniently
+    Error = 1,
+}
+
+/// A problem found while reading a class that did not stop it from being read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub offset: usize,
+    pub path: String,
+    pub message: String,
+}
+}
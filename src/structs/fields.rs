@@ -1,7 +1,13 @@
-use crate::{error::Result, impl_get_pretty, Classify};
+use crate::{
+    descriptor::FieldType, error::Result, impl_get_pretty, reader::context,
+    signature::FieldSignature, Classify,
+};
 
 use super::{
-    access_flags::FieldFlags, attributes::Attributes, const_types::Utf8, ConstPool, Index,
+    access_flags::FieldFlags,
+    attributes::{find_signature, Attributes},
+    const_types::Utf8,
+    ConstPool, Index,
 };
 
 // #[derive(Debug, Clone, PartialEq)]
@@ -21,15 +27,25 @@ pub struct Field {
 }
 }
 
+impl Field {
+    pub fn descriptor(&self, pool: &ConstPool) -> Result<FieldType> {
+        FieldType::parse(&self.descriptor_index.get(pool)?.str)
+    }
+
+    /// The generic signature, if the field has one
+    pub fn signature(&self, pool: &ConstPool) -> Result<Option<FieldSignature>> {
+        find_signature(&self.attributes)
+            .map(|signature| signature.field_signature(pool))
+            .transpose()
+    }
+}
+
 impl Classify for Fields {
     fn classify(&mut self, const_pool: &ConstPool) -> Result<()> {
-        for field in self.iter_mut() {
-            match field.attributes.classify(const_pool) {
-                Ok(_) => {}
-                Err(_) => {
-                    println!("Error classifying field attributes")
-                }
-            };
+        for (i, field) in self.iter_mut().enumerate() {
+            context::scope(format_args!("fields[{}]", i), || {
+                field.attributes.classify(const_pool)
+            })?;
         }
         Ok(())
     }
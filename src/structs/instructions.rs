@@ -0,0 +1,362 @@
+#![allow(non_camel_case_types)]
+use crate::{pretty_print::GetPretty, Result};
+
+use super::{
+    const_types::{Class, FieldRef, InterfaceMethodRef, InvokeDynamic, MethodRef},
+    opcodes::{ArrayType, Opcode},
+    ConstItem, ConstPool, Index,
+};
+
+// Generates the `Instruction` enum from the opcodes without operands and the ones with operands,
+// along with the mapping back to the `Opcode` of every instruction
+macro_rules! gen_instructions {
+    (
+        simple {
+            $($simple:ident,)*
+        }
+        operands {
+            $($variant:ident($($operand:ty),*),)*
+        }
+    ) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Instruction {
+            $($simple,)*
+            $($variant($($operand),*),)*
+        }
+
+        impl Instruction {
+            pub fn opcode(&self) -> Opcode {
+                match self {
+                    $(Instruction::$simple => Opcode::$simple,)*
+                    $(Instruction::$variant(..) => Opcode::$variant,)*
+                }
+            }
+
+            /// Returns the instruction for an opcode that takes no operands
+            pub fn simple(opcode: Opcode) -> Option<Self> {
+                match opcode {
+                    $(Opcode::$simple => Some(Instruction::$simple),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+gen_instructions! {
+    simple {
+        nop, aconst_null, iconst_m1, iconst_0, iconst_1, iconst_2,
+        iconst_3, iconst_4, iconst_5, lconst_0, lconst_1, fconst_0,
+        fconst_1, fconst_2, dconst_0, dconst_1, iload_0, iload_1,
+        iload_2, iload_3, lload_0, lload_1, lload_2, lload_3,
+        fload_0, fload_1, fload_2, fload_3, dload_0, dload_1,
+        dload_2, dload_3, aload_0, aload_1, aload_2, aload_3,
+        iaload, laload, faload, daload, aaload, baload,
+        caload, saload, istore_0, istore_1, istore_2, istore_3,
+        lstore_0, lstore_1, lstore_2, lstore_3, fstore_0, fstore_1,
+        fstore_2, fstore_3, dstore_0, dstore_1, dstore_2, dstore_3,
+        astore_0, astore_1, astore_2, astore_3, iastore, lastore,
+        fastore, dastore, aastore, bastore, castore, sastore,
+        pop, pop2, dup, dup_x1, dup_x2, dup2,
+        dup2_x1, dup2_x2, swap, iadd, ladd, fadd,
+        dadd, isub, lsub, fsub, dsub, imul,
+        lmul, fmul, dmul, idiv, ldiv, fdiv,
+        ddiv, irem, lrem, frem, drem, ineg,
+        lneg, fneg, dneg, ishl, lshl, ishr,
+        lshr, iushr, lushr, iand, land, ior,
+        lor, ixor, lxor, i2l, i2f, i2d,
+        l2i, l2f, l2d, f2i, f2l, f2d,
+        d2i, d2l, d2f, i2b, i2c, i2s,
+        lcmp, fcmpl, fcmpg, dcmpl, dcmpg, ireturn,
+        lreturn, freturn, dreturn, areturn, return_, arraylength,
+        athrow, monitorenter, monitorexit,
+    }
+    operands {
+        bipush(i8),
+        sipush(i16),
+        // ldc only has room for a one byte index
+        ldc(Index<ConstItem>),
+        ldc_w(Index<ConstItem>),
+        ldc2_w(Index<ConstItem>),
+        iload(u8),
+        lload(u8),
+        fload(u8),
+        dload(u8),
+        aload(u8),
+        istore(u8),
+        lstore(u8),
+        fstore(u8),
+        dstore(u8),
+        astore(u8),
+        iinc(u8, i8),
+        ifeq(i16),
+        ifne(i16),
+        iflt(i16),
+        ifge(i16),
+        ifgt(i16),
+        ifle(i16),
+        if_icmpeq(i16),
+        if_icmpne(i16),
+        if_icmplt(i16),
+        if_icmpge(i16),
+        if_icmpgt(i16),
+        if_icmple(i16),
+        if_acmpeq(i16),
+        if_acmpne(i16),
+        goto(i16),
+        jsr(i16),
+        ret(u8),
+        tableswitch(TableSwitch),
+        lookupswitch(LookupSwitch),
+        getstatic(Index<FieldRef>),
+        putstatic(Index<FieldRef>),
+        getfield(Index<FieldRef>),
+        putfield(Index<FieldRef>),
+        invokevirtual(Index<MethodRef>),
+        // Since Java 8 invokespecial and invokestatic may point to either a MethodRef or an
+        // InterfaceMethodRef
+        invokespecial(Index<ConstItem>),
+        invokestatic(Index<ConstItem>),
+        // The count operand is redundant with the descriptor, but is still stored in the class file
+        invokeinterface(Index<InterfaceMethodRef>, u8),
+        invokedynamic(Index<InvokeDynamic>),
+        new(Index<Class>),
+        newarray(ArrayType),
+        anewarray(Index<Class>),
+        checkcast(Index<Class>),
+        instanceof(Index<Class>),
+        wide(Wide),
+        multianewarray(Index<Class>, u8),
+        ifnull(i16),
+        ifnonnull(i16),
+        goto_w(i32),
+        jsr_w(i32),
+    }
+}
+
+/// An instruction together with its offset in the code array
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetInstruction {
+    pub offset: u32,
+    pub instruction: Instruction,
+}
+
+/// A `tableswitch`, the jump offsets are relative to the offset of the instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSwitch {
+    pub default: i32,
+    pub low: i32,
+    pub high: i32,
+    pub offsets: Vec<i32>,
+}
+
+/// A `lookupswitch`, the jump offsets are relative to the offset of the instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupSwitch {
+    pub default: i32,
+    pub pairs: Vec<MatchOffset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchOffset {
+    pub key: i32,
+    pub offset: i32,
+}
+
+/// The instructions that can be modified by the `wide` prefix, with their widened operands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Wide {
+    iload(u16),
+    lload(u16),
+    fload(u16),
+    dload(u16),
+    aload(u16),
+    istore(u16),
+    lstore(u16),
+    fstore(u16),
+    dstore(u16),
+    astore(u16),
+    ret(u16),
+    iinc(u16, i16),
+}
+
+impl Wide {
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Wide::iload(_) => Opcode::iload,
+            Wide::lload(_) => Opcode::lload,
+            Wide::fload(_) => Opcode::fload,
+            Wide::dload(_) => Opcode::dload,
+            Wide::aload(_) => Opcode::aload,
+            Wide::istore(_) => Opcode::istore,
+            Wide::lstore(_) => Opcode::lstore,
+            Wide::fstore(_) => Opcode::fstore,
+            Wide::dstore(_) => Opcode::dstore,
+            Wide::astore(_) => Opcode::astore,
+            Wide::ret(_) => Opcode::ret,
+            Wide::iinc(..) => Opcode::iinc,
+        }
+    }
+
+    /// The local variable slot accessed by the instruction
+    pub fn index(&self) -> u16 {
+        match self {
+            Wide::iload(index)
+            | Wide::lload(index)
+            | Wide::fload(index)
+            | Wide::dload(index)
+            | Wide::aload(index)
+            | Wide::istore(index)
+            | Wide::lstore(index)
+            | Wide::fstore(index)
+            | Wide::dstore(index)
+            | Wide::astore(index)
+            | Wide::ret(index)
+            | Wide::iinc(index, _) => *index,
+        }
+    }
+}
+
+impl Instruction {
+    /// Returns the absolute offsets this instruction may jump to, given its own offset
+    pub fn branch_targets(&self, offset: u32) -> Vec<u32> {
+        let target = |relative: i32| (offset as i64 + relative as i64) as u32;
+        match self {
+            Instruction::ifeq(relative)
+            | Instruction::ifne(relative)
+            | Instruction::iflt(relative)
+            | Instruction::ifge(relative)
+            | Instruction::ifgt(relative)
+            | Instruction::ifle(relative)
+            | Instruction::if_icmpeq(relative)
+            | Instruction::if_icmpne(relative)
+            | Instruction::if_icmplt(relative)
+            | Instruction::if_icmpge(relative)
+            | Instruction::if_icmpgt(relative)
+            | Instruction::if_icmple(relative)
+            | Instruction::if_acmpeq(relative)
+            | Instruction::if_acmpne(relative)
+            | Instruction::goto(relative)
+            | Instruction::jsr(relative)
+            | Instruction::ifnull(relative)
+            | Instruction::ifnonnull(relative) => vec![target(*relative as i32)],
+            Instruction::goto_w(relative) | Instruction::jsr_w(relative) => vec![target(*relative)],
+            Instruction::tableswitch(switch) => std::iter::once(switch.default)
+                .chain(switch.offsets.iter().copied())
+                .map(target)
+                .collect(),
+            Instruction::lookupswitch(switch) => std::iter::once(switch.default)
+                .chain(switch.pairs.iter().map(|pair| pair.offset))
+                .map(target)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl GetPretty for Instruction {
+    fn get_pretty(&self, _pool: &ConstPool, tabs: usize) -> Result<String> {
+        let name = format!("{:?}", self.opcode());
+        let operands = match self {
+            Instruction::bipush(value) => value.to_string(),
+            Instruction::sipush(value) => value.to_string(),
+            Instruction::ldc(index)
+            | Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => format!("#{}", index.index),
+            Instruction::iload(slot)
+            | Instruction::lload(slot)
+            | Instruction::fload(slot)
+            | Instruction::dload(slot)
+            | Instruction::aload(slot)
+            | Instruction::istore(slot)
+            | Instruction::lstore(slot)
+            | Instruction::fstore(slot)
+            | Instruction::dstore(slot)
+            | Instruction::astore(slot)
+            | Instruction::ret(slot) => slot.to_string(),
+            Instruction::iinc(slot, value) => format!("{}, {}", slot, value),
+            Instruction::ifeq(relative)
+            | Instruction::ifne(relative)
+            | Instruction::iflt(relative)
+            | Instruction::ifge(relative)
+            | Instruction::ifgt(relative)
+            | Instruction::ifle(relative)
+            | Instruction::if_icmpeq(relative)
+            | Instruction::if_icmpne(relative)
+            | Instruction::if_icmplt(relative)
+            | Instruction::if_icmpge(relative)
+            | Instruction::if_icmpgt(relative)
+            | Instruction::if_icmple(relative)
+            | Instruction::if_acmpeq(relative)
+            | Instruction::if_acmpne(relative)
+            | Instruction::goto(relative)
+            | Instruction::jsr(relative)
+            | Instruction::ifnull(relative)
+            | Instruction::ifnonnull(relative) => format!("{:+}", relative),
+            Instruction::goto_w(relative) | Instruction::jsr_w(relative) => {
+                format!("{:+}", relative)
+            }
+            Instruction::tableswitch(switch) => {
+                let mut result = format!("{} to {}", switch.low, switch.high);
+                for (key, offset) in (switch.low..).zip(&switch.offsets) {
+                    result.push_str(&format!(", {}: {:+}", key, offset));
+                }
+                result.push_str(&format!(", default: {:+}", switch.default));
+                result
+            }
+            Instruction::lookupswitch(switch) => {
+                let mut result = format!("{}", switch.pairs.len());
+                for pair in &switch.pairs {
+                    result.push_str(&format!(", {}: {:+}", pair.key, pair.offset));
+                }
+                result.push_str(&format!(", default: {:+}", switch.default));
+                result
+            }
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => format!("#{}", index.index),
+            Instruction::invokevirtual(index) => format!("#{}", index.index),
+            Instruction::invokeinterface(index, count) => format!("#{}, {}", index.index, count),
+            Instruction::invokedynamic(index) => format!("#{}", index.index),
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index) => format!("#{}", index.index),
+            Instruction::newarray(atype) => format!("{:?}", atype),
+            Instruction::wide(wide) => match wide {
+                Wide::iinc(slot, value) => format!("{:?} {}, {}", wide.opcode(), slot, value),
+                _ => format!("{:?} {}", wide.opcode(), wide.index()),
+            },
+            Instruction::multianewarray(index, dimensions) => {
+                format!("#{}, {}", index.index, dimensions)
+            }
+            _ => String::new(),
+        };
+        if operands.is_empty() {
+            Ok(format!("{:indent$}{}", "", name, indent = tabs))
+        } else {
+            Ok(format!(
+                "{:indent$}{} {}",
+                "",
+                name,
+                operands,
+                indent = tabs
+            ))
+        }
+    }
+}
+
+impl GetPretty for OffsetInstruction {
+    fn get_pretty(&self, pool: &ConstPool, tabs: usize) -> Result<String> {
+        Ok(format!(
+            "{:indent$}{}: {}",
+            "",
+            self.offset,
+            self.instruction.get_pretty(pool, 0)?,
+            indent = tabs
+        ))
+    }
+}
@@ -1,7 +1,13 @@
-use crate::{error::Result, impl_get_pretty, Classify};
+use crate::{
+    descriptor::MethodDescriptor, error::Result, impl_get_pretty, reader::context,
+    signature::MethodSignature, Classify,
+};
 
 use super::{
-    access_flags::MethodFlags, attributes::Attributes, const_types::Utf8, ConstPool, Index,
+    access_flags::MethodFlags,
+    attributes::{find_signature, AttributeInfo, Attributes},
+    const_types::Utf8,
+    ConstPool, Index,
 };
 
 // #[derive(Debug, Clone, PartialEq)]
@@ -21,15 +27,37 @@ pub struct Method {
 }
 }
 
+impl Method {
+    pub fn descriptor(&self, pool: &ConstPool) -> Result<MethodDescriptor> {
+        MethodDescriptor::parse(&self.descriptor_index.get(pool)?.str)
+    }
+
+    /// The generic signature, if the method has one
+    pub fn signature(&self, pool: &ConstPool) -> Result<Option<MethodSignature>> {
+        find_signature(&self.attributes)
+            .map(|signature| signature.method_signature(pool))
+            .transpose()
+    }
+
+    /// The default value of an annotation interface element, rendered as a Java literal
+    pub fn annotation_default(&self, pool: &ConstPool) -> Result<Option<String>> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfo::AnnotationDefault(default) => Some(default),
+                _ => None,
+            })
+            .map(|default| default.default_value.to_java_literal(pool))
+            .transpose()
+    }
+}
+
 impl Classify for Methods {
     fn classify(&mut self, const_pool: &ConstPool) -> Result<()> {
-        for method in self.iter_mut() {
-            match method.attributes.classify(const_pool) {
-                Ok(_) => {}
-                Err(_) => {
-                    println!("Error classifying method attributes")
-                }
-            };
+        for (i, method) in self.iter_mut().enumerate() {
+            context::scope(format_args!("methods[{}]", i), || {
+                method.attributes.classify(const_pool)
+            })?;
         }
         Ok(())
     }
@@ -276,3 +276,18 @@ gen_try_from! {
         impdep2 = 0xFF,
     }
 }
+
+gen_try_from! {
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    #[repr(u8)]
+    pub enum ArrayType {
+        T_BOOLEAN = 4,
+        T_CHAR = 5,
+        T_FLOAT = 6,
+        T_DOUBLE = 7,
+        T_BYTE = 8,
+        T_SHORT = 9,
+        T_INT = 10,
+        T_LONG = 11,
+    }
+}
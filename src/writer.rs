@@ -0,0 +1,24 @@
+pub mod access_flags;
+pub mod attributes;
+pub mod class;
+pub mod class_version;
+pub mod const_pool;
+pub mod const_types;
+pub mod fields;
+pub mod instructions;
+pub mod interfaces;
+pub mod methods;
+
+use std::{convert::TryFrom, io::Write};
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{error::ParseError, Result};
+
+/// Writes a `u16` length prefix, failing instead of silently truncating.
+pub(crate) fn write_length<W: Write>(writer: &mut W, length: usize) -> Result<()> {
+    let length = u16::try_from(length)
+        .map_err(|_| ParseError::Other(format!("Length {} does not fit in a u16", length)))?;
+    writer.write_u16::<BigEndian>(length)?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{
+    structs::access_flags::{
+        ClassFlags, ExportsFlags, FieldFlags, InnerClassFlags, MethodFlags, MethodParameterFlags,
+        ModuleFlags, RequiresFlags,
+    },
+    Result, Writable,
+};
+
+macro_rules! impl_writable_for_access_flags {
+    ($($flag:ident),*) => {
+        $(impl Writable for $flag {
+            fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+                writer.write_u16::<BigEndian>(self.bits())?;
+                Ok(())
+            }
+        })*
+    };
+}
+
+impl_writable_for_access_flags! {
+    ClassFlags,
+    FieldFlags,
+    MethodFlags,
+    ExportsFlags,
+    RequiresFlags,
+    ModuleFlags,
+    InnerClassFlags,
+    MethodParameterFlags
+}
@@ -0,0 +1,688 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{error::ParseError, structs::attributes::*, writer::write_length, Result, Writable};
+
+use super::instructions::write_code;
+
+impl Writable for Vec<Attribute> {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.len())?;
+        for attribute in self {
+            attribute.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Attribute {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // The length prefix is only known once the info has been serialized
+        let mut info = Vec::new();
+        self.info.write(&mut info)?;
+        if info.len() > u32::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "Attribute length {} does not fit in a u32",
+                info.len()
+            )));
+        }
+        self.attribute_name_index.write(writer)?;
+        writer.write_u32::<BigEndian>(info.len() as u32)?;
+        writer.write_all(&info)?;
+        Ok(())
+    }
+}
+
+impl Writable for Unknown {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.bytes)?;
+        Ok(())
+    }
+}
+
+impl Writable for NotImplemented {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.bytes)?;
+        Ok(())
+    }
+}
+
+impl Writable for ConstantValue {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.constant_value_index.write(writer)
+    }
+}
+
+impl Writable for Code {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.max_stack)?;
+        writer.write_u16::<BigEndian>(self.max_locals)?;
+        let code = write_code(&self.code)?;
+        writer.write_u32::<BigEndian>(code.len() as u32)?;
+        writer.write_all(&code)?;
+        write_length(writer, self.exception_table.len())?;
+        for exception in &self.exception_table {
+            exception.write(writer)?;
+        }
+        self.attributes.write(writer)
+    }
+}
+
+impl Writable for ExceptionTable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.start_pc)?;
+        writer.write_u16::<BigEndian>(self.end_pc)?;
+        writer.write_u16::<BigEndian>(self.handler_pc)?;
+        self.catch_type.write(writer)
+    }
+}
+
+impl Writable for StackMapTable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.entries.len())?;
+        for entry in &self.entries {
+            entry.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for StackMapFrame {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.frame_type()?)?;
+        match self {
+            StackMapFrame::Same { .. } => {}
+            StackMapFrame::SameLocals1StackItem { stack, .. } => stack.write(writer)?,
+            StackMapFrame::SameLocals1StackItemExtended {
+                offset_delta,
+                stack,
+            } => {
+                writer.write_u16::<BigEndian>(*offset_delta)?;
+                stack.write(writer)?;
+            }
+            StackMapFrame::Chop { offset_delta, .. }
+            | StackMapFrame::SameExtended { offset_delta } => {
+                writer.write_u16::<BigEndian>(*offset_delta)?;
+            }
+            StackMapFrame::Append {
+                offset_delta,
+                locals,
+            } => {
+                writer.write_u16::<BigEndian>(*offset_delta)?;
+                for local in locals {
+                    local.write(writer)?;
+                }
+            }
+            StackMapFrame::Full {
+                offset_delta,
+                locals,
+                stack,
+            } => {
+                writer.write_u16::<BigEndian>(*offset_delta)?;
+                write_length(writer, locals.len())?;
+                for local in locals {
+                    local.write(writer)?;
+                }
+                write_length(writer, stack.len())?;
+                for item in stack {
+                    item.write(writer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Writable for VerificationTypeInfo {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.tag())?;
+        match self {
+            VerificationTypeInfo::Object(index) => index.write(writer)?,
+            VerificationTypeInfo::Uninitialized(offset) => {
+                writer.write_u16::<BigEndian>(*offset)?
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Exceptions {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.exception_index_table.len())?;
+        for exception in &self.exception_index_table {
+            exception.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for InnerClasses {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.classes.len())?;
+        for class in &self.classes {
+            class.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for InnerClass {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.inner_class_info_index.write(writer)?;
+        self.outer_class_info_index.write(writer)?;
+        self.inner_name_index.write(writer)?;
+        self.inner_class_access_flags.write(writer)
+    }
+}
+
+impl Writable for EnclosingMethod {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.class_index.write(writer)?;
+        self.method_index.write(writer)
+    }
+}
+
+impl Writable for Synthetic {
+    fn write<W: Write>(&self, _writer: &mut W) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Writable for Signature {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.signature_index.write(writer)
+    }
+}
+
+impl Writable for SourceFile {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.sourcefile_index.write(writer)
+    }
+}
+
+impl Writable for SourceDebugExtension {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.debug_extension.write(writer)
+    }
+}
+
+impl Writable for LineNumberTable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.line_number_table.len())?;
+        for line_number in &self.line_number_table {
+            line_number.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for LineNumber {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.start_pc)?;
+        writer.write_u16::<BigEndian>(self.line_number)?;
+        Ok(())
+    }
+}
+
+impl Writable for LocalVariableTable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.local_variable_table.len())?;
+        for local_variable in &self.local_variable_table {
+            local_variable.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for LocalVariable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.start_pc)?;
+        writer.write_u16::<BigEndian>(self.length)?;
+        self.name_index.write(writer)?;
+        self.descriptor_index.write(writer)?;
+        writer.write_u16::<BigEndian>(self.index)?;
+        Ok(())
+    }
+}
+
+impl Writable for LocalVariableTypeTable {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.local_variable_type_table.len())?;
+        for local_variable_type in &self.local_variable_type_table {
+            local_variable_type.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for LocalVariableType {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.start_pc)?;
+        writer.write_u16::<BigEndian>(self.length)?;
+        self.name_index.write(writer)?;
+        self.signature_index.write(writer)?;
+        writer.write_u16::<BigEndian>(self.index)?;
+        Ok(())
+    }
+}
+
+impl Writable for Deprecated {
+    fn write<W: Write>(&self, _writer: &mut W) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Writable for RuntimeVisibleAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.annotations.len())?;
+        for annotation in &self.annotations {
+            annotation.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for RuntimeInvisibleAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.annotations.len())?;
+        for annotation in &self.annotations {
+            annotation.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for ParameterAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.annotations.len())?;
+        for annotation in &self.annotations {
+            annotation.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for RuntimeVisibleParameterAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.parameter_annotations.len() > u8::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "Parameter count {} does not fit in a u8",
+                self.parameter_annotations.len()
+            )));
+        }
+        writer.write_u8(self.parameter_annotations.len() as u8)?;
+        for parameter in &self.parameter_annotations {
+            parameter.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for RuntimeInvisibleParameterAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.parameter_annotations.len() > u8::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "Parameter count {} does not fit in a u8",
+                self.parameter_annotations.len()
+            )));
+        }
+        writer.write_u8(self.parameter_annotations.len() as u8)?;
+        for parameter in &self.parameter_annotations {
+            parameter.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Annotation {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.type_index.write(writer)?;
+        write_length(writer, self.element_value_pairs.len())?;
+        for pair in &self.element_value_pairs {
+            pair.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for ElementValuePair {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.element_name_index.write(writer)?;
+        self.value.write(writer)
+    }
+}
+
+impl Writable for ElementValue {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.tag())?;
+        match self {
+            ElementValue::Byte(index)
+            | ElementValue::Char(index)
+            | ElementValue::Int(index)
+            | ElementValue::Short(index)
+            | ElementValue::Boolean(index) => index.write(writer),
+            ElementValue::Double(index) => index.write(writer),
+            ElementValue::Float(index) => index.write(writer),
+            ElementValue::Long(index) => index.write(writer),
+            ElementValue::String(index) | ElementValue::Class(index) => index.write(writer),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => {
+                type_name_index.write(writer)?;
+                const_name_index.write(writer)
+            }
+            ElementValue::Annotation(annotation) => annotation.write(writer),
+            ElementValue::Array(values) => {
+                write_length(writer, values.len())?;
+                for value in values {
+                    value.write(writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Writable for RuntimeVisibleTypeAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.annotations.len())?;
+        for annotation in &self.annotations {
+            annotation.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for RuntimeInvisibleTypeAnnotations {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.annotations.len())?;
+        for annotation in &self.annotations {
+            annotation.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for TypeAnnotation {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if !self.target_type.accepts(&self.target_info) {
+            return Err(ParseError::Other(format!(
+                "Target info {:?} does not match target type {:?}",
+                self.target_info, self.target_type
+            )));
+        }
+        writer.write_u8(self.target_type as u8)?;
+        self.target_info.write(writer)?;
+        self.target_path.write(writer)?;
+        self.annotation.write(writer)
+    }
+}
+
+impl Writable for TargetInfo {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            TargetInfo::TypeParameter {
+                type_parameter_index,
+            } => writer.write_u8(*type_parameter_index)?,
+            TargetInfo::Supertype { supertype_index } => {
+                writer.write_u16::<BigEndian>(*supertype_index)?
+            }
+            TargetInfo::TypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => {
+                writer.write_u8(*type_parameter_index)?;
+                writer.write_u8(*bound_index)?;
+            }
+            TargetInfo::Empty => {}
+            TargetInfo::FormalParameter {
+                formal_parameter_index,
+            } => writer.write_u8(*formal_parameter_index)?,
+            TargetInfo::Throws { throws_type_index } => {
+                writer.write_u16::<BigEndian>(*throws_type_index)?
+            }
+            TargetInfo::Localvar { table } => {
+                write_length(writer, table.len())?;
+                for entry in table {
+                    entry.write(writer)?;
+                }
+            }
+            TargetInfo::Catch {
+                exception_table_index,
+            } => writer.write_u16::<BigEndian>(*exception_table_index)?,
+            TargetInfo::Offset { offset } => writer.write_u16::<BigEndian>(*offset)?,
+            TargetInfo::TypeArgument {
+                offset,
+                type_argument_index,
+            } => {
+                writer.write_u16::<BigEndian>(*offset)?;
+                writer.write_u8(*type_argument_index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Writable for LocalVarTarget {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.start_pc)?;
+        writer.write_u16::<BigEndian>(self.length)?;
+        writer.write_u16::<BigEndian>(self.index)?;
+        Ok(())
+    }
+}
+
+impl Writable for TypePath {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.path.len() > u8::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "Type path length {} does not fit in a u8",
+                self.path.len()
+            )));
+        }
+        writer.write_u8(self.path.len() as u8)?;
+        for entry in &self.path {
+            entry.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for TypePathEntry {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.type_path_kind as u8)?;
+        writer.write_u8(self.type_argument_index)?;
+        Ok(())
+    }
+}
+
+impl Writable for AnnotationDefault {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.default_value.write(writer)
+    }
+}
+
+impl Writable for BootstrapMethods {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.bootstrap_methods.len())?;
+        for bootstrap_method in &self.bootstrap_methods {
+            bootstrap_method.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for BootstrapMethod {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.bootstrap_method_ref.write(writer)?;
+        write_length(writer, self.bootstrap_arguments.len())?;
+        for argument in &self.bootstrap_arguments {
+            argument.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for MethodParameters {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.parameters.len() > u8::MAX as usize {
+            return Err(ParseError::Other(format!(
+                "Parameter count {} does not fit in a u8",
+                self.parameters.len()
+            )));
+        }
+        writer.write_u8(self.parameters.len() as u8)?;
+        for parameter in &self.parameters {
+            parameter.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for MethodParameter {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)?;
+        self.access_flags.write(writer)
+    }
+}
+
+impl Writable for Module {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)?;
+        self.flags.write(writer)?;
+        self.version_index.write(writer)?;
+        write_length(writer, self.requires.len())?;
+        for requires in &self.requires {
+            requires.write(writer)?;
+        }
+        write_length(writer, self.exports.len())?;
+        for exports in &self.exports {
+            exports.write(writer)?;
+        }
+        write_length(writer, self.opens.len())?;
+        for opens in &self.opens {
+            opens.write(writer)?;
+        }
+        write_length(writer, self.uses.len())?;
+        for uses in &self.uses {
+            uses.write(writer)?;
+        }
+        write_length(writer, self.provides.len())?;
+        for provides in &self.provides {
+            provides.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Requires {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.requires_index.write(writer)?;
+        self.flags.write(writer)?;
+        self.version_index.write(writer)
+    }
+}
+
+impl Writable for Exports {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.exports_index.write(writer)?;
+        self.flags.write(writer)?;
+        write_length(writer, self.to_index.len())?;
+        for to in &self.to_index {
+            to.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Opens {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.opens_index.write(writer)?;
+        self.flags.write(writer)?;
+        write_length(writer, self.to_index.len())?;
+        for to in &self.to_index {
+            to.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Uses {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.uses_index.write(writer)
+    }
+}
+
+impl Writable for Provides {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.provides_index.write(writer)?;
+        write_length(writer, self.with_index.len())?;
+        for with in &self.with_index {
+            with.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for ModulePackages {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.package_index.len())?;
+        for package in &self.package_index {
+            package.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for ModuleMainClass {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.main_class_index.write(writer)
+    }
+}
+
+impl Writable for NestHost {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.host_class_index.write(writer)
+    }
+}
+
+impl Writable for NestMembers {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.classes.len())?;
+        for class in &self.classes {
+            class.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Record {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.components.len())?;
+        for component in &self.components {
+            component.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for RecordComponent {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)?;
+        self.descriptor_index.write(writer)?;
+        self.attributes.write(writer)
+    }
+}
+
+impl Writable for PermittedSubclasses {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.classes.len())?;
+        for class in &self.classes {
+            class.write(writer)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{structs::Class, Result, Writable};
+
+impl Writable for Class {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<BigEndian>(self.magic)?;
+        self.version.write(writer)?;
+        self.constant_pool.write(writer)?;
+        self.access_flags.write(writer)?;
+        self.this_class.write(writer)?;
+        self.super_class.write(writer)?;
+        self.interfaces.write(writer)?;
+        self.fields.write(writer)?;
+        self.methods.write(writer)?;
+        self.attributes.write(writer)
+    }
+}
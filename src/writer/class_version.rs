@@ -0,0 +1,13 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{structs::ClassVersion, Result, Writable};
+
+impl Writable for ClassVersion {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.minor)?;
+        writer.write_u16::<BigEndian>(self.major.into())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use std::io::Write;
+
+use crate::{structs::ConstPool, writer::write_length, Result, Writable};
+
+impl Writable for ConstPool {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // The count includes the unused slot 0 and the phantom slots after Long/Double entries,
+        // both of which are stored as `None`
+        write_length(writer, self.entries.len())?;
+        for entry in self.entries.iter().flatten() {
+            entry.write(writer)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::structs::const_pool::TryFromItem;
+use crate::structs::const_types::*;
+use crate::writer::write_length;
+use crate::{error::ParseError, Result, Writable};
+
+impl Writable for ConstItem {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            ConstItem::Utf8(x) => {
+                writer.write_u8(1)?;
+                x.write(writer)
+            }
+            ConstItem::Integer(x) => {
+                writer.write_u8(3)?;
+                x.write(writer)
+            }
+            ConstItem::Float(x) => {
+                writer.write_u8(4)?;
+                x.write(writer)
+            }
+            ConstItem::Long(x) => {
+                writer.write_u8(5)?;
+                x.write(writer)
+            }
+            ConstItem::Double(x) => {
+                writer.write_u8(6)?;
+                x.write(writer)
+            }
+            ConstItem::Class(x) => {
+                writer.write_u8(7)?;
+                x.write(writer)
+            }
+            ConstItem::StringJ(x) => {
+                writer.write_u8(8)?;
+                x.write(writer)
+            }
+            ConstItem::FieldRef(x) => {
+                writer.write_u8(9)?;
+                x.write(writer)
+            }
+            ConstItem::MethodRef(x) => {
+                writer.write_u8(10)?;
+                x.write(writer)
+            }
+            ConstItem::InterfaceMethodRef(x) => {
+                writer.write_u8(11)?;
+                x.write(writer)
+            }
+            ConstItem::NameAndType(x) => {
+                writer.write_u8(12)?;
+                x.write(writer)
+            }
+            ConstItem::MethodHandle(x) => {
+                writer.write_u8(15)?;
+                x.write(writer)
+            }
+            ConstItem::MethodType(x) => {
+                writer.write_u8(16)?;
+                x.write(writer)
+            }
+            ConstItem::Dynamic(x) => {
+                writer.write_u8(17)?;
+                x.write(writer)
+            }
+            ConstItem::InvokeDynamic(x) => {
+                writer.write_u8(18)?;
+                x.write(writer)
+            }
+            ConstItem::Module(x) => {
+                writer.write_u8(19)?;
+                x.write(writer)
+            }
+            ConstItem::Package(x) => {
+                writer.write_u8(20)?;
+                x.write(writer)
+            }
+        }
+    }
+}
+
+impl<T: TryFromItem> Writable for Index<T> {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.index)?;
+        Ok(())
+    }
+}
+
+impl Writable for Utf8 {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // Edits to `str` are written even if `bytes` was left as it was
+        let bytes = self.encoded();
+        if bytes.len() > u16::MAX as usize {
+            return Err(ParseError::Utf8TooLong(bytes.len()));
+        }
+        write_length(writer, bytes.len())?;
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+impl Writable for Integer {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32::<BigEndian>(self.value)?;
+        Ok(())
+    }
+}
+
+impl Writable for Float {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f32::<BigEndian>(self.value)?;
+        Ok(())
+    }
+}
+
+impl Writable for Long {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i64::<BigEndian>(self.value)?;
+        Ok(())
+    }
+}
+
+impl Writable for Double {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_f64::<BigEndian>(self.value)?;
+        Ok(())
+    }
+}
+
+impl Writable for Class {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)
+    }
+}
+
+impl Writable for StringJ {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.string_index.write(writer)
+    }
+}
+
+impl Writable for FieldRef {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.class_index.write(writer)?;
+        self.name_and_type_index.write(writer)
+    }
+}
+
+impl Writable for MethodRef {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.class_index.write(writer)?;
+        self.name_and_type_index.write(writer)
+    }
+}
+
+impl Writable for InterfaceMethodRef {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.class_index.write(writer)?;
+        self.name_and_type_index.write(writer)
+    }
+}
+
+impl Writable for NameAndType {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)?;
+        self.descriptor_index.write(writer)
+    }
+}
+
+impl Writable for MethodHandle {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.reference_kind.write(writer)?;
+        writer.write_u16::<BigEndian>(self.reference_index.index())?;
+        Ok(())
+    }
+}
+
+impl Writable for MethodHandleReferenceKind {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.clone() as u8)?;
+        Ok(())
+    }
+}
+
+impl Writable for MethodType {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.descriptor_index.write(writer)
+    }
+}
+
+impl Writable for Dynamic {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.bootstrap_method_attr_index)?;
+        self.name_and_type_index.write(writer)
+    }
+}
+
+impl Writable for InvokeDynamic {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u16::<BigEndian>(self.bootstrap_method_attr_index)?;
+        self.name_and_type_index.write(writer)
+    }
+}
+
+impl Writable for Module {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)
+    }
+}
+
+impl Writable for Package {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.name_index.write(writer)
+    }
+}
@@ -0,0 +1,26 @@
+use std::io::Write;
+
+use crate::{
+    structs::{Field, Fields},
+    writer::write_length,
+    Result, Writable,
+};
+
+impl Writable for Fields {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.len())?;
+        for field in self {
+            field.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Field {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.access_flags.write(writer)?;
+        self.name_index.write(writer)?;
+        self.descriptor_index.write(writer)?;
+        self.attributes.write(writer)
+    }
+}
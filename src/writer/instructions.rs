@@ -0,0 +1,175 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+use crate::{
+    error::ParseError,
+    structs::instructions::{
+        Instruction, LookupSwitch, MatchOffset, OffsetInstruction, TableSwitch, Wide,
+    },
+    Result, Writable,
+};
+
+/// Encodes instructions into a code array
+///
+/// The offsets stored alongside the instructions are ignored, the switch padding is computed from
+/// where each instruction actually ends up.
+pub fn write_code(instructions: &[OffsetInstruction]) -> Result<Vec<u8>> {
+    let mut code = Vec::new();
+    for instruction in instructions {
+        let offset = code.len() as u32;
+        instruction.instruction.write_at(&mut code, offset)?;
+    }
+    Ok(code)
+}
+
+fn write_padding<W: Write>(writer: &mut W, offset: u32) -> Result<()> {
+    for _ in 0..(3 - offset % 4) {
+        writer.write_u8(0)?;
+    }
+    Ok(())
+}
+
+impl Instruction {
+    /// Writes an instruction located at `offset` in the code array
+    pub fn write_at<W: Write>(&self, writer: &mut W, offset: u32) -> Result<()> {
+        writer.write_u8(self.opcode() as u8)?;
+        match self {
+            Instruction::bipush(value) => writer.write_i8(*value)?,
+            Instruction::sipush(value) => writer.write_i16::<BigEndian>(*value)?,
+            Instruction::ldc(index) => {
+                if index.index > u8::MAX as u16 {
+                    return Err(ParseError::Other(format!(
+                        "Constant pool index {} is too large for ldc",
+                        index.index
+                    )));
+                }
+                writer.write_u8(index.index as u8)?
+            }
+            Instruction::ldc_w(index)
+            | Instruction::ldc2_w(index)
+            | Instruction::invokespecial(index)
+            | Instruction::invokestatic(index) => index.write(writer)?,
+            Instruction::iload(slot)
+            | Instruction::lload(slot)
+            | Instruction::fload(slot)
+            | Instruction::dload(slot)
+            | Instruction::aload(slot)
+            | Instruction::istore(slot)
+            | Instruction::lstore(slot)
+            | Instruction::fstore(slot)
+            | Instruction::dstore(slot)
+            | Instruction::astore(slot)
+            | Instruction::ret(slot) => writer.write_u8(*slot)?,
+            Instruction::iinc(slot, value) => {
+                writer.write_u8(*slot)?;
+                writer.write_i8(*value)?
+            }
+            Instruction::ifeq(relative)
+            | Instruction::ifne(relative)
+            | Instruction::iflt(relative)
+            | Instruction::ifge(relative)
+            | Instruction::ifgt(relative)
+            | Instruction::ifle(relative)
+            | Instruction::if_icmpeq(relative)
+            | Instruction::if_icmpne(relative)
+            | Instruction::if_icmplt(relative)
+            | Instruction::if_icmpge(relative)
+            | Instruction::if_icmpgt(relative)
+            | Instruction::if_icmple(relative)
+            | Instruction::if_acmpeq(relative)
+            | Instruction::if_acmpne(relative)
+            | Instruction::goto(relative)
+            | Instruction::jsr(relative)
+            | Instruction::ifnull(relative)
+            | Instruction::ifnonnull(relative) => writer.write_i16::<BigEndian>(*relative)?,
+            Instruction::goto_w(relative) | Instruction::jsr_w(relative) => {
+                writer.write_i32::<BigEndian>(*relative)?
+            }
+            Instruction::tableswitch(switch) => {
+                write_padding(writer, offset)?;
+                switch.write(writer)?
+            }
+            Instruction::lookupswitch(switch) => {
+                write_padding(writer, offset)?;
+                switch.write(writer)?
+            }
+            Instruction::getstatic(index)
+            | Instruction::putstatic(index)
+            | Instruction::getfield(index)
+            | Instruction::putfield(index) => index.write(writer)?,
+            Instruction::invokevirtual(index) => index.write(writer)?,
+            Instruction::invokeinterface(index, count) => {
+                index.write(writer)?;
+                writer.write_u8(*count)?;
+                writer.write_u8(0)?
+            }
+            Instruction::invokedynamic(index) => {
+                index.write(writer)?;
+                writer.write_u16::<BigEndian>(0)?
+            }
+            Instruction::new(index)
+            | Instruction::anewarray(index)
+            | Instruction::checkcast(index)
+            | Instruction::instanceof(index) => index.write(writer)?,
+            Instruction::newarray(atype) => writer.write_u8(*atype as u8)?,
+            Instruction::wide(wide) => wide.write(writer)?,
+            Instruction::multianewarray(index, dimensions) => {
+                index.write(writer)?;
+                writer.write_u8(*dimensions)?
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Writable for TableSwitch {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.offsets.len() as i64 != self.high as i64 - self.low as i64 + 1 {
+            return Err(ParseError::Other(format!(
+                "tableswitch from {} to {} has {} offsets",
+                self.low,
+                self.high,
+                self.offsets.len()
+            )));
+        }
+        writer.write_i32::<BigEndian>(self.default)?;
+        writer.write_i32::<BigEndian>(self.low)?;
+        writer.write_i32::<BigEndian>(self.high)?;
+        for offset in &self.offsets {
+            writer.write_i32::<BigEndian>(*offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for LookupSwitch {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32::<BigEndian>(self.default)?;
+        writer.write_i32::<BigEndian>(self.pairs.len() as i32)?;
+        for pair in &self.pairs {
+            pair.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for MatchOffset {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32::<BigEndian>(self.key)?;
+        writer.write_i32::<BigEndian>(self.offset)?;
+        Ok(())
+    }
+}
+
+impl Writable for Wide {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.opcode() as u8)?;
+        writer.write_u16::<BigEndian>(self.index())?;
+        if let Wide::iinc(_, value) = self {
+            writer.write_i16::<BigEndian>(*value)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use std::io::Write;
+
+use crate::structs::interfaces::{Interface, Interfaces};
+use crate::writer::write_length;
+use crate::{Result, Writable};
+
+impl Writable for Interfaces {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.len())?;
+        for interface in self {
+            interface.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Interface {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.index.write(writer)
+    }
+}
@@ -0,0 +1,26 @@
+use std::io::Write;
+
+use crate::{
+    structs::{Method, Methods},
+    writer::write_length,
+    Result, Writable,
+};
+
+impl Writable for Methods {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_length(writer, self.len())?;
+        for method in self {
+            method.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writable for Method {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.access_flags.write(writer)?;
+        self.name_index.write(writer)?;
+        self.descriptor_index.write(writer)?;
+        self.attributes.write(writer)
+    }
+}
@@ -0,0 +1,50 @@
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+
+@Annotated.Entity(name = "annotated", tags = {"a", "b"}, kind = ElementType.TYPE, type = String.class,
+        nested = @Annotated.Marker)
+@Annotated.Invisible
+public class Annotated {
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Entity {
+        String name() default "";
+        String[] tags() default {};
+        ElementType kind() default ElementType.FIELD;
+        Class<?> type() default Object.class;
+        Marker nested() default @Marker(priority = 5);
+        long id() default 42L;
+        char letter() default 'x';
+        double ratio() default 0.5;
+        float scale() default 1.5f;
+        boolean enabled() default true;
+        byte small() default -1;
+        short medium() default 300;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Marker {
+        int priority() default 0;
+    }
+
+    public @interface Invisible {
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target(ElementType.TYPE_USE)
+    public @interface NotNull {
+    }
+
+    @Marker(priority = 1)
+    private java.util.List<@NotNull String> names;
+
+    public void handle(@Marker(priority = 2) String first, @Invisible int second) {
+        @NotNull Object local = first;
+        try {
+            names.add((@NotNull String) local);
+        } catch (@NotNull RuntimeException e) {
+            throw e;
+        }
+    }
+}
@@ -0,0 +1,21 @@
+import java.io.Serializable;
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+public class Callbacks {
+    interface Named {
+    }
+
+    public static Supplier<String> remember(String value) {
+        return (Supplier<String> & Serializable) () -> value;
+    }
+
+    public static Runnable tagged() {
+        return (Runnable & Named) () -> {
+        };
+    }
+
+    public static Function<String, String> suffix(String value) {
+        return value::concat;
+    }
+}
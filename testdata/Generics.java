@@ -0,0 +1,24 @@
+import java.io.IOException;
+import java.util.List;
+import java.util.Map;
+
+public class Generics<K extends Comparable<? super K>, V> extends java.util.AbstractMap<K, V>
+        implements java.io.Serializable {
+    public Map<String, ? extends List<V>> index;
+    public Inner<String>.Leaf<Integer> leaf;
+    public V[][] grid;
+
+    public <T extends Number & Runnable, E extends Exception> List<T> copy(
+            List<? super T> target, T[] values, int count) throws E, IOException {
+        return null;
+    }
+
+    public java.util.Set<Map.Entry<K, V>> entrySet() {
+        return null;
+    }
+
+    public class Inner<A> {
+        public class Leaf<B> {
+        }
+    }
+}
@@ -0,0 +1,192 @@
+import java.util.ArrayList;
+import java.util.List;
+
+public class Instructions {
+    public static int tableSwitch(int value) {
+        switch (value) {
+            case 1: return 10;
+            case 2: return 20;
+            case 3: return 30;
+            default: return -1;
+        }
+    }
+
+    public static int lookupSwitch(int value) {
+        switch (value) {
+            case -1000: return 1;
+            case 0: return 2;
+            case 1000000: return 3;
+            default: return 0;
+        }
+    }
+
+    public static long wide(long a, long b, long c, long d, long e, long f, long g, long h) {
+        long[] locals = new long[4];
+        int counter = 0;
+        for (int i = 0; i < 200; i++) {
+            long x0 = a + i, x1 = b + i;
+            long x2 = x1 + x0;
+            long x3 = x2 + x1;
+            long x4 = x3 + x2;
+            long x5 = x4 + x3;
+            long x6 = x5 + x4;
+            long x7 = x6 + x5;
+            long x8 = x7 + x6;
+            long x9 = x8 + x7;
+            long x10 = x9 + x8;
+            long x11 = x10 + x9;
+            long x12 = x11 + x10;
+            long x13 = x12 + x11;
+            long x14 = x13 + x12;
+            long x15 = x14 + x13;
+            long x16 = x15 + x14;
+            long x17 = x16 + x15;
+            long x18 = x17 + x16;
+            long x19 = x18 + x17;
+            long x20 = x19 + x18;
+            long x21 = x20 + x19;
+            long x22 = x21 + x20;
+            long x23 = x22 + x21;
+            long x24 = x23 + x22;
+            long x25 = x24 + x23;
+            long x26 = x25 + x24;
+            long x27 = x26 + x25;
+            long x28 = x27 + x26;
+            long x29 = x28 + x27;
+            long x30 = x29 + x28;
+            long x31 = x30 + x29;
+            long x32 = x31 + x30;
+            long x33 = x32 + x31;
+            long x34 = x33 + x32;
+            long x35 = x34 + x33;
+            long x36 = x35 + x34;
+            long x37 = x36 + x35;
+            long x38 = x37 + x36;
+            long x39 = x38 + x37;
+            long x40 = x39 + x38;
+            long x41 = x40 + x39;
+            long x42 = x41 + x40;
+            long x43 = x42 + x41;
+            long x44 = x43 + x42;
+            long x45 = x44 + x43;
+            long x46 = x45 + x44;
+            long x47 = x46 + x45;
+            long x48 = x47 + x46;
+            long x49 = x48 + x47;
+            long x50 = x49 + x48;
+            long x51 = x50 + x49;
+            long x52 = x51 + x50;
+            long x53 = x52 + x51;
+            long x54 = x53 + x52;
+            long x55 = x54 + x53;
+            long x56 = x55 + x54;
+            long x57 = x56 + x55;
+            long x58 = x57 + x56;
+            long x59 = x58 + x57;
+            long x60 = x59 + x58;
+            long x61 = x60 + x59;
+            long x62 = x61 + x60;
+            long x63 = x62 + x61;
+            long x64 = x63 + x62;
+            long x65 = x64 + x63;
+            long x66 = x65 + x64;
+            long x67 = x66 + x65;
+            long x68 = x67 + x66;
+            long x69 = x68 + x67;
+            long x70 = x69 + x68;
+            long x71 = x70 + x69;
+            long x72 = x71 + x70;
+            long x73 = x72 + x71;
+            long x74 = x73 + x72;
+            long x75 = x74 + x73;
+            long x76 = x75 + x74;
+            long x77 = x76 + x75;
+            long x78 = x77 + x76;
+            long x79 = x78 + x77;
+            long x80 = x79 + x78;
+            long x81 = x80 + x79;
+            long x82 = x81 + x80;
+            long x83 = x82 + x81;
+            long x84 = x83 + x82;
+            long x85 = x84 + x83;
+            long x86 = x85 + x84;
+            long x87 = x86 + x85;
+            long x88 = x87 + x86;
+            long x89 = x88 + x87;
+            long x90 = x89 + x88;
+            long x91 = x90 + x89;
+            long x92 = x91 + x90;
+            long x93 = x92 + x91;
+            long x94 = x93 + x92;
+            long x95 = x94 + x93;
+            long x96 = x95 + x94;
+            long x97 = x96 + x95;
+            long x98 = x97 + x96;
+            long x99 = x98 + x97;
+            long x100 = x99 + x98;
+            long x101 = x100 + x99;
+            long x102 = x101 + x100;
+            long x103 = x102 + x101;
+            long x104 = x103 + x102;
+            long x105 = x104 + x103;
+            long x106 = x105 + x104;
+            long x107 = x106 + x105;
+            long x108 = x107 + x106;
+            long x109 = x108 + x107;
+            long x110 = x109 + x108;
+            long x111 = x110 + x109;
+            long x112 = x111 + x110;
+            long x113 = x112 + x111;
+            long x114 = x113 + x112;
+            long x115 = x114 + x113;
+            long x116 = x115 + x114;
+            long x117 = x116 + x115;
+            long x118 = x117 + x116;
+            long x119 = x118 + x117;
+            long x120 = x119 + x118;
+            long x121 = x120 + x119;
+            long x122 = x121 + x120;
+            long x123 = x122 + x121;
+            long x124 = x123 + x122;
+            long x125 = x124 + x123;
+            long x126 = x125 + x124;
+            long x127 = x126 + x125;
+            long x128 = x127 + x126;
+            long x129 = x128 + x127;
+            long x130 = x129 + x128;
+            long x131 = x130 + x129;
+            long x132 = x131 + x130;
+            long x133 = x132 + x131;
+            long x134 = x133 + x132;
+            long x135 = x134 + x133;
+            long x136 = x135 + x134;
+            long x137 = x136 + x135;
+            long x138 = x137 + x136;
+            long x139 = x138 + x137;
+            int wideCounter = counter;
+            wideCounter += 1000;
+            counter = wideCounter;
+            locals[i % 4] = x139;
+        }
+        return locals[0] + counter;
+    }
+
+    public static int interfaces(List<String> list) {
+        list.add("hello");
+        return list.size();
+    }
+
+    public static int[][][] arrays() {
+        int[][][] cube = new int[2][3][4];
+        byte[] bytes = new byte[8];
+        cube[0][0][0] = bytes.length;
+        return cube;
+    }
+
+    public static Object instances(Object o) {
+        if (o instanceof String) {
+            return (String) o;
+        }
+        return new ArrayList<String>();
+    }
+}
@@ -0,0 +1,38 @@
+import java.util.List;
+import java.util.function.Function;
+import java.util.function.IntBinaryOperator;
+import java.util.function.Supplier;
+
+public class Lambdas {
+    public static final long BIG = 123456789012L;
+    public static final double RATIO = 0.000125;
+    public static final float HUGE = 1.0e10f;
+    public static final char LETTER = 'c';
+    public static final String ESCAPED = "tab\there \"quoted\" \u0001";
+    private transient int counter;
+    private volatile String label;
+
+    public interface Shape {
+        double SCALE = 2.5;
+
+        double area();
+
+        default String describe(String... tags) {
+            return "Shape " + area() + " tagged " + tags.length;
+        }
+    }
+
+    public record Point(int x, List<String> names) implements Shape {
+        public double area() {
+            return 0;
+        }
+    }
+
+    public synchronized String greet(String name, int times) {
+        Function<String, String> greeter = n -> "Hello " + n + "!" + times;
+        Supplier<Lambdas> factory = Lambdas::new;
+        IntBinaryOperator max = Math::max;
+        Function<Object, String> show = String::valueOf;
+        return greeter.apply(name) + factory.get().counter + max.applyAsInt(times, 3) + show.apply(label);
+    }
+}